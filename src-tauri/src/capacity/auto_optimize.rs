@@ -0,0 +1,240 @@
+use super::{auto_optimize, OptimizationOptions, OptimizationResult};
+use crate::db::DbPool;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Quiet time after the last input change before a planning period is re-optimized
+pub const AUTO_OPTIMIZATION_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Whether a planning period re-optimizes itself when its inputs change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoOptimizationSetting {
+    pub planning_period_id: i64,
+    pub enabled: bool,
+    pub strategy: String, // Strategy the automatic runs use
+    pub options: OptimizationOptions,
+}
+
+/// Result of an automatic re-optimization, sent to the listener
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoOptimizationEvent {
+    pub planning_period_id: i64,
+    pub result: OptimizationResult,
+}
+
+/// Counts changes per planning period, so only the last change of a burst starts a run
+#[derive(Debug, Default)]
+pub struct ChangeDebouncer {
+    generations: HashMap<i64, u64>,
+}
+
+impl ChangeDebouncer {
+    /// Note a change and return its generation
+    pub fn bump(&mut self, planning_period_id: i64) -> u64 {
+        let generation = self.generations.entry(planning_period_id).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    /// Whether no change came after the one with this generation
+    pub fn is_latest(&self, planning_period_id: i64, generation: u64) -> bool {
+        self.generations.get(&planning_period_id) == Some(&generation)
+    }
+}
+
+type AutoOptimizationListener = Box<dyn Fn(&AutoOptimizationEvent) + Send + Sync>;
+
+/// Re-optimizes planning periods that opted in, once their inputs stop changing
+pub struct AutoOptimizer {
+    pool: DbPool,
+    debounce: Duration,
+    listener: AutoOptimizationListener,
+    debouncer: Mutex<ChangeDebouncer>,
+}
+
+impl AutoOptimizer {
+    /// The listener receives the result of every automatic run
+    pub fn new(
+        pool: DbPool,
+        debounce: Duration,
+        listener: impl Fn(&AutoOptimizationEvent) + Send + Sync + 'static,
+    ) -> Arc<Self> {
+        Arc::new(AutoOptimizer {
+            pool,
+            debounce,
+            listener: Box::new(listener),
+            debouncer: Mutex::new(ChangeDebouncer::default()),
+        })
+    }
+
+    /// Re-optimize a planning period after the debounce time, unless it changes again
+    pub fn schedule(self: &Arc<Self>, planning_period_id: i64) {
+        let generation = self
+            .debouncer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .bump(planning_period_id);
+
+        let optimizer = Arc::clone(self);
+        tokio::spawn(async move {
+            tokio::time::sleep(optimizer.debounce).await;
+            let latest = optimizer
+                .debouncer
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .is_latest(planning_period_id, generation);
+            if !latest {
+                return;
+            }
+
+            match auto_optimize(planning_period_id, &optimizer.pool).await {
+                Ok(Some(result)) => (optimizer.listener)(&AutoOptimizationEvent {
+                    planning_period_id,
+                    result,
+                }),
+                Ok(None) => debug!(
+                    "Planning period {} does not re-optimize automatically",
+                    planning_period_id
+                ),
+                Err(e) => warn!(
+                    "Automatic re-optimization of planning period {} failed: {}",
+                    planning_period_id, e
+                ),
+            }
+        });
+    }
+}
+
+static AUTO_OPTIMIZER: OnceLock<Arc<AutoOptimizer>> = OnceLock::new();
+
+/// Start re-optimizing planning periods in the background when their inputs change
+///
+/// The listener receives the result of every automatic run. Until this is called,
+/// changes are only tracked for staleness.
+pub fn start_auto_optimizer(
+    pool: DbPool,
+    listener: impl Fn(&AutoOptimizationEvent) + Send + Sync + 'static,
+) {
+    let optimizer = AutoOptimizer::new(pool, AUTO_OPTIMIZATION_DEBOUNCE, listener);
+    if AUTO_OPTIMIZER.set(optimizer).is_err() {
+        warn!("Automatic re-optimization was already started");
+    }
+}
+
+/// Re-optimize a planning period once its inputs stop changing, if it opted in
+pub fn schedule_auto_optimization(planning_period_id: i64) {
+    if let Some(optimizer) = AUTO_OPTIMIZER.get() {
+        optimizer.schedule(planning_period_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+    use crate::capacity::{
+        list_stored_optimization_runs, load_calculation_staleness, record_input_change,
+        save_auto_optimization_setting, ChangedInput, InputChangeAction, PROPORTIONAL_STRATEGY,
+    };
+    use serde_json::Value;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+    use tokio::time::timeout;
+
+    #[test]
+    fn test_change_debouncer_keeps_only_last_change_of_burst() {
+        let mut debouncer = ChangeDebouncer::default();
+        let first = debouncer.bump(1);
+        let second = debouncer.bump(1);
+        let other_period = debouncer.bump(2);
+
+        assert!(!debouncer.is_latest(1, first));
+        assert!(debouncer.is_latest(1, second));
+        assert!(debouncer.is_latest(2, other_period));
+        assert!(!debouncer.is_latest(3, 1));
+    }
+
+    const TEST_DEBOUNCE: Duration = Duration::from_millis(50);
+
+    /// Optimizer with a short debounce that sends its events serialized, as they are emitted
+    fn test_optimizer(pool: &DbPool) -> (Arc<AutoOptimizer>, UnboundedReceiver<Value>) {
+        let (sender, receiver) = unbounded_channel();
+        let optimizer = AutoOptimizer::new(pool.clone(), TEST_DEBOUNCE, move |event| {
+            let _ = sender.send(serde_json::to_value(event).unwrap());
+        });
+        (optimizer, receiver)
+    }
+
+    /// Record an absence of the seeded period and notify the optimizer, as the app does
+    async fn change_inputs(optimizer: &Arc<AutoOptimizer>, pool: &DbPool) {
+        record_input_change(
+            &[1],
+            ChangedInput::Absence,
+            InputChangeAction::Added,
+            None,
+            pool,
+        )
+        .await
+        .unwrap();
+        assert!(ChangedInput::Absence.triggers_auto_optimization());
+        optimizer.schedule(1);
+    }
+
+    #[tokio::test]
+    async fn test_enabled_period_re_optimizes_once_after_burst_of_changes() {
+        let pool = seeded_database().await;
+        save_auto_optimization_setting(
+            1,
+            true,
+            Some(PROPORTIONAL_STRATEGY),
+            OptimizationOptions::default(),
+            &pool,
+        )
+        .await
+        .unwrap();
+        let (optimizer, mut events) = test_optimizer(&pool);
+
+        for _ in 0..3 {
+            change_inputs(&optimizer, &pool).await;
+        }
+        assert!(load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("No automatic run finished")
+            .unwrap();
+        assert_eq!(event["planning_period_id"], 1);
+        assert_eq!(event["result"]["strategy"], PROPORTIONAL_STRATEGY);
+        assert_eq!(event["result"]["calculations"].as_array().unwrap().len(), 2);
+        assert!(!load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+
+        // The earlier changes of the burst start no runs of their own
+        tokio::time::sleep(TEST_DEBOUNCE * 4).await;
+        assert!(events.try_recv().is_err());
+        let runs = list_stored_optimization_runs(1, &pool).await.unwrap();
+        assert_eq!(runs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_period_is_not_re_optimized() {
+        let pool = seeded_database().await;
+        let setting =
+            save_auto_optimization_setting(1, false, None, OptimizationOptions::default(), &pool)
+                .await
+                .unwrap();
+        assert!(!setting.enabled);
+        let (optimizer, mut events) = test_optimizer(&pool);
+
+        change_inputs(&optimizer, &pool).await;
+
+        tokio::time::sleep(TEST_DEBOUNCE * 4).await;
+        assert!(events.try_recv().is_err());
+        assert!(load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+        assert!(list_stored_optimization_runs(1, &pool)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
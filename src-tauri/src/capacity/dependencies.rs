@@ -0,0 +1,411 @@
+use super::{
+    calculate_assignment_effective_hours, parse_date, planning_period_bounds,
+    AssignmentCalculation, OptimizationResult, OptimizationStrategy, PeriodCapacityData,
+};
+use crate::models::{Assignment, ProjectDependency, ProjectRequirement};
+use chrono::{Duration, NaiveDate};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Rounds after which the start dates of dependent projects are left as they are
+const MAX_DEPENDENCY_ROUNDS: usize = 100;
+
+/// Missing hours below which a project counts as complete
+const COMPLETION_TOLERANCE: f64 = 1e-6;
+
+/// A project that cannot start at the beginning of the planning period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedProject {
+    pub project_id: i64,
+    pub project_name: String,
+    pub predecessor_ids: Vec<i64>,           // Projects it waits for
+    pub earliest_start_date: Option<String>, // None when it cannot start in this period
+}
+
+/// Whether making a project depend on another would close a cycle
+pub fn creates_dependency_cycle(
+    dependencies: &[ProjectDependency],
+    project_id: i64,
+    depends_on_project_id: i64,
+) -> bool {
+    // Reaching the project from the new predecessor's own predecessors means a cycle
+    let mut stack = vec![depends_on_project_id];
+    let mut seen = HashSet::new();
+    while let Some(current) = stack.pop() {
+        if current == project_id {
+            return true;
+        }
+        if seen.insert(current) {
+            stack.extend(
+                dependencies
+                    .iter()
+                    .filter(|dependency| dependency.project_id == current)
+                    .map(|dependency| dependency.depends_on_project_id),
+            );
+        }
+    }
+    false
+}
+
+/// Date on which a project's assignments reach its required hours
+///
+/// Effective hours accrue with the available hours of each assignment window. Returns the
+/// day before the period when nothing is required, and None when the project falls short.
+pub fn forecast_completion(
+    data: &PeriodCapacityData,
+    assignments: &[&Assignment],
+    calculations: &HashMap<i64, &AssignmentCalculation>,
+    required_hours: f64,
+) -> Result<Option<NaiveDate>, String> {
+    let (period_start, period_end) = planning_period_bounds(&data.planning_period)?;
+    if required_hours <= COMPLETION_TOLERANCE {
+        return Ok(Some(period_start - Duration::days(1)));
+    }
+
+    let effective_until = |date: NaiveDate| -> Result<f64, String> {
+        let mut total = 0.0;
+        for assignment in assignments {
+            let (Some(calc), Some(person)) = (
+                calculations.get(&assignment.id),
+                data.person(assignment.person_id),
+            ) else {
+                continue;
+            };
+            if let Some(breakdown) =
+                data.assignment_breakdown_in_range(person, assignment, period_start, date)?
+            {
+                total += calculate_assignment_effective_hours(
+                    breakdown.available_hours,
+                    calc.calculated_allocation_percentage,
+                    assignment.productivity_factor,
+                );
+            }
+        }
+        Ok(total)
+    };
+
+    if effective_until(period_end)? < required_hours - COMPLETION_TOLERANCE {
+        return Ok(None);
+    }
+
+    // First day on which the accrued hours reach the requirement
+    let (mut low, mut high) = (0, (period_end - period_start).num_days());
+    while low < high {
+        let middle = (low + high) / 2;
+        if effective_until(period_start + Duration::days(middle))?
+            >= required_hours - COMPLETION_TOLERANCE
+        {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Ok(Some(period_start + Duration::days(low)))
+}
+
+/// Runs another strategy without giving a project hours before its predecessors complete
+///
+/// Dependent projects start out blocked. Every round re-runs the strategy and, once all
+/// predecessors of a dependent project are settled, moves its start to the day after their
+/// forecast completion plus the lag. Settled starts only ever move later, so the rounds end
+/// when nothing changes. Only dependencies between projects required in the period count;
+/// a predecessor without requirement is taken as complete.
+pub struct SequencedStrategy<'a> {
+    pub inner: &'a dyn OptimizationStrategy,
+    pub dependencies: &'a [ProjectDependency],
+}
+
+impl OptimizationStrategy for SequencedStrategy<'_> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn optimize(
+        &self,
+        data: &PeriodCapacityData,
+        assignments: &[Assignment],
+        requirements: &[ProjectRequirement],
+        project_names: &HashMap<i64, String>,
+    ) -> Result<OptimizationResult, String> {
+        // Starts stored by an earlier run are results, not inputs
+        let mut trial_assignments: Vec<Assignment> = assignments
+            .iter()
+            .cloned()
+            .map(|mut assignment| {
+                assignment.calculated_start_date = None;
+                assignment
+            })
+            .collect();
+
+        let requirements_map: HashMap<i64, &ProjectRequirement> = requirements
+            .iter()
+            .map(|requirement| (requirement.project_id, requirement))
+            .collect();
+        let mut predecessors: BTreeMap<i64, Vec<&ProjectDependency>> = BTreeMap::new();
+        for dependency in self.dependencies {
+            if requirements_map.contains_key(&dependency.project_id)
+                && requirements_map.contains_key(&dependency.depends_on_project_id)
+            {
+                predecessors
+                    .entry(dependency.project_id)
+                    .or_default()
+                    .push(dependency);
+            }
+        }
+        if predecessors.is_empty() {
+            return self
+                .inner
+                .optimize(data, &trial_assignments, requirements, project_names);
+        }
+
+        let (period_start, period_end) = planning_period_bounds(&data.planning_period)?;
+        let blocked_start = period_end + Duration::days(1);
+        let mut starts: HashMap<i64, NaiveDate> = HashMap::new();
+        let mut rounds = 0;
+
+        let mut result = loop {
+            for assignment in &mut trial_assignments {
+                if predecessors.contains_key(&assignment.project_id) {
+                    let start = starts
+                        .get(&assignment.project_id)
+                        .copied()
+                        .unwrap_or(blocked_start);
+                    assignment.calculated_start_date = Some(start.to_string());
+                }
+            }
+            let result =
+                self.inner
+                    .optimize(data, &trial_assignments, requirements, project_names)?;
+            rounds += 1;
+
+            let calculations: HashMap<i64, &AssignmentCalculation> = result
+                .calculations
+                .iter()
+                .chain(&result.pinned_calculations)
+                .map(|calc| (calc.assignment_id, calc))
+                .collect();
+            let mut completions: HashMap<i64, Option<NaiveDate>> = HashMap::new();
+            // Starts settled in this round only count once the strategy ran with them
+            let settled: HashSet<i64> = starts.keys().copied().collect();
+            let mut changed = false;
+
+            for (project_id, links) in &predecessors {
+                // Wait until every predecessor has a start of its own
+                let waiting = links.iter().any(|link| {
+                    predecessors.contains_key(&link.depends_on_project_id)
+                        && !settled.contains(&link.depends_on_project_id)
+                });
+                if waiting {
+                    continue;
+                }
+
+                let mut start = period_start;
+                for link in links {
+                    let predecessor_id = link.depends_on_project_id;
+                    let completion = match completions.get(&predecessor_id) {
+                        Some(completion) => *completion,
+                        None => {
+                            let project_assignments: Vec<&Assignment> = trial_assignments
+                                .iter()
+                                .filter(|assignment| assignment.project_id == predecessor_id)
+                                .collect();
+                            let completion = forecast_completion(
+                                data,
+                                &project_assignments,
+                                &calculations,
+                                requirements_map[&predecessor_id].required_hours,
+                            )?;
+                            completions.insert(predecessor_id, completion);
+                            completion
+                        }
+                    };
+                    let link_start = completion.map_or(blocked_start, |date| {
+                        (date + Duration::days(link.lag_days.max(0) + 1)).min(blocked_start)
+                    });
+                    start = start.max(link_start);
+                }
+
+                let later = match starts.get(project_id) {
+                    Some(previous) => start > *previous,
+                    None => true,
+                };
+                if later {
+                    starts.insert(*project_id, start);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break result;
+            }
+            if rounds >= MAX_DEPENDENCY_ROUNDS {
+                warn!(
+                    "Start dates of dependent projects still changed after {} rounds",
+                    rounds
+                );
+                break result;
+            }
+        };
+        debug!("Settled dependent project starts in {} rounds", rounds);
+
+        // Record the delayed starts on the calculations
+        let mut delayed_starts: HashMap<i64, String> = HashMap::new();
+        for assignment in &trial_assignments {
+            let Some(start) = starts.get(&assignment.project_id) else {
+                continue;
+            };
+            let own_start = parse_date(&assignment.start_date, "assignment start date")?;
+            if *start > own_start.max(period_start) {
+                delayed_starts.insert(assignment.id, start.to_string());
+            }
+        }
+        for calc in result
+            .calculations
+            .iter_mut()
+            .chain(result.pinned_calculations.iter_mut())
+        {
+            if let Some(start) = delayed_starts.get(&calc.assignment_id) {
+                calc.calculated_start_date = Some(start.clone());
+            }
+        }
+
+        for (project_id, links) in &predecessors {
+            let Some(start) = starts.get(project_id).copied() else {
+                continue;
+            };
+            if start <= period_start {
+                continue;
+            }
+            let earliest_start_date = (start <= period_end).then(|| start.to_string());
+            let project_name = project_names
+                .get(project_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Project {}", project_id));
+            match &earliest_start_date {
+                Some(date) => result.warnings.push(format!(
+                    "{} cannot start before {} because of its dependencies",
+                    project_name, date
+                )),
+                None => result.warnings.push(format!(
+                    "{} cannot start in this planning period because of its dependencies",
+                    project_name
+                )),
+            }
+            result.blocked_projects.push(BlockedProject {
+                project_id: *project_id,
+                project_name,
+                predecessor_ids: links
+                    .iter()
+                    .map(|link| link.depends_on_project_id)
+                    .collect(),
+                earliest_start_date,
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+    use crate::capacity::ProportionalStrategy;
+
+    fn dependency(project_id: i64, depends_on_project_id: i64, lag_days: i64) -> ProjectDependency {
+        ProjectDependency {
+            id: 0,
+            project_id,
+            depends_on_project_id,
+            lag_days,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_creates_dependency_cycle() {
+        // Project 2 waits for project 1, project 3 for project 2
+        let dependencies = vec![dependency(2, 1, 0), dependency(3, 2, 0)];
+        assert!(creates_dependency_cycle(&dependencies, 1, 3));
+        assert!(creates_dependency_cycle(&dependencies, 1, 1));
+        assert!(!creates_dependency_cycle(&dependencies, 3, 1));
+        assert!(!creates_dependency_cycle(&dependencies, 4, 3));
+    }
+
+    #[test]
+    fn test_sequenced_strategy_delays_dependent_project() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-15"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 80.0, 20), requirement(2, 40.0, 10)];
+        let dependencies = vec![dependency(2, 1, 2)];
+        let project_names = HashMap::from([(2, "Beta".to_string())]);
+
+        let result = SequencedStrategy {
+            inner: &ProportionalStrategy,
+            dependencies: &dependencies,
+        }
+        .optimize(&data, &assignments, &requirements, &project_names)
+        .unwrap();
+
+        // Project 1 takes the first two weeks, project 2 starts two days after it completes
+        let dependent = result
+            .calculations
+            .iter()
+            .find(|c| c.assignment_id == 2)
+            .unwrap();
+        assert_eq!(
+            dependent.calculated_start_date.as_deref(),
+            Some("2024-03-18")
+        );
+        assert!((dependent.calculated_effective_hours - 40.0).abs() < 0.001);
+        assert!(result.infeasible_projects.is_empty());
+
+        assert_eq!(result.blocked_projects.len(), 1);
+        let blocked = &result.blocked_projects[0];
+        assert_eq!(blocked.project_id, 2);
+        assert_eq!(blocked.predecessor_ids, vec![1]);
+        assert_eq!(blocked.earliest_start_date.as_deref(), Some("2024-03-18"));
+        assert!(result.warnings.contains(
+            &"Beta cannot start before 2024-03-18 because of its dependencies".to_string()
+        ));
+    }
+
+    #[test]
+    fn test_sequenced_strategy_blocks_dependent_of_unfinished_project() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 200.0, 10), requirement(2, 40.0, 20)];
+        let dependencies = vec![dependency(2, 1, 0)];
+
+        let result = SequencedStrategy {
+            inner: &ProportionalStrategy,
+            dependencies: &dependencies,
+        }
+        .optimize(&data, &assignments, &requirements, &HashMap::new())
+        .unwrap();
+
+        // Project 1 cannot complete, so project 2 gets nothing despite its higher priority
+        let predecessor = result
+            .calculations
+            .iter()
+            .find(|c| c.assignment_id == 1)
+            .unwrap();
+        assert!((predecessor.calculated_effective_hours - 160.0).abs() < 0.001);
+        let shortfall = result
+            .infeasible_projects
+            .iter()
+            .find(|s| s.project_id == 2)
+            .unwrap();
+        assert!((shortfall.shortfall - 40.0).abs() < 0.001);
+
+        assert_eq!(result.blocked_projects.len(), 1);
+        assert_eq!(result.blocked_projects[0].earliest_start_date, None);
+    }
+}
@@ -0,0 +1,358 @@
+use super::{
+    allocation_bounds, calculate_assignment_effective_hours, empty_optimization_result,
+    explain_shortfalls, prepare_optimization, project_shortfall, skipped_assignment_ids,
+    unsatisfied_constraints, Allocations, AssignmentWindow, OptimizationOptions,
+    OptimizationResult, PeriodCapacityData, LINEAR_STRATEGY,
+};
+use crate::models::{Assignment, ProjectRequirement};
+use good_lp::{minilp, variable, Expression, ProblemVariables, Solution, SolverModel, Variable};
+use log::debug;
+use std::collections::{BTreeMap, HashMap};
+
+/// Objective weight added per priority level when no step is given
+/// (low=1, medium=2, high=3, blocker=4)
+const DEFAULT_PRIORITY_WEIGHT_STEP: f64 = 1.0;
+
+/// Objective weight of a project's staffed hours, from its priority
+fn priority_weight(priority: i64, step: f64) -> f64 {
+    1.0 + step * priority.max(0) as f64 / 10.0
+}
+
+/// An assignment the solver allocates
+struct LinearAssignment<'a> {
+    assignment: &'a Assignment,
+    window: &'a AssignmentWindow,
+    share: Variable, // Share of the window's available hours on top of the reserved minimum
+}
+
+impl LinearAssignment<'_> {
+    /// Effective hours one full share of the window is worth
+    fn hours_per_share(&self) -> f64 {
+        self.window.available_hours * self.assignment.productivity_factor
+    }
+}
+
+/// Linear-programming optimization algorithm
+///
+/// Maximizes the staffed effective hours, weighted by project priority, subject to each
+/// person's daily capacity and each project's required hours. Unlike the proportional
+/// algorithm, a lower-priority project can still be staffed when a different split of
+/// the shared people covers both. Pinned assignments keep their allocation and minimum
+/// allocations are reserved before solving; maximums cap the solver's shares.
+///
+/// `priority_weight_step` sets how much more a staffed hour of each higher priority level
+/// is worth; zero ignores priority altogether.
+pub fn optimize_linear(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+    options: &OptimizationOptions,
+) -> Result<OptimizationResult, String> {
+    let step = options
+        .priority_weight_step
+        .unwrap_or(DEFAULT_PRIORITY_WEIGHT_STEP);
+    let options = OptimizationOptions {
+        priority_weight_step: Some(step),
+    };
+
+    if assignments.is_empty() {
+        return Ok(empty_optimization_result(LINEAR_STRATEGY, options));
+    }
+
+    let mut setup = prepare_optimization(data, assignments, requirements)?;
+    let project_ids = setup.required_project_ids();
+    let mut allocations = Allocations::with_minimums(&setup);
+
+    // One variable per assignment, capped by what the person's pins and minimums leave
+    // free and by the room left below the assignment's maximum
+    let mut variables = ProblemVariables::new();
+    let mut entries: Vec<LinearAssignment> = Vec::new();
+    for project_id in &project_ids {
+        for (assignment, window) in setup.free_assignments(*project_id) {
+            let (_, max_pct) = allocation_bounds(assignment);
+            let remaining_pct = setup.person_states[&assignment.person_id]
+                .remaining_in(window)
+                .min((max_pct - allocations.allocated(assignment.id)).max(0.0));
+            entries.push(LinearAssignment {
+                assignment,
+                window,
+                share: variables.add(variable().min(0.0).max(remaining_pct / 100.0)),
+            });
+        }
+    }
+
+    let mut objective = Expression::default();
+    let mut project_constraints: Vec<(Expression, f64)> = Vec::new();
+    for project_id in &project_ids {
+        let requirement = setup.requirements_map[project_id];
+        let weight = priority_weight(requirement.priority, step);
+
+        let mut staffed = Expression::default();
+        for entry in entries
+            .iter()
+            .filter(|entry| entry.assignment.project_id == *project_id)
+        {
+            staffed += entry.hours_per_share() * entry.share;
+            objective += weight * entry.hours_per_share() * entry.share;
+        }
+
+        // Don't staff beyond the requirement (pinned and minimum hours count towards it)
+        let open_hours =
+            (requirement.required_hours - setup.reserved_effective(*project_id)).max(0.0);
+        project_constraints.push((staffed, open_hours));
+    }
+
+    // Assignments active on the same day share the person's remaining capacity of that
+    // day. Days with the same set of active assignments need a single constraint.
+    let mut capacity_constraints: BTreeMap<Vec<usize>, f64> = BTreeMap::new();
+    for (person_id, state) in &setup.person_states {
+        let person_entries: Vec<usize> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.assignment.person_id == *person_id)
+            .map(|(index, _)| index)
+            .collect();
+        if person_entries.len() < 2 {
+            continue;
+        }
+
+        for (day, remaining_pct) in state.remaining_percentage.iter().enumerate() {
+            let active: Vec<usize> = person_entries
+                .iter()
+                .copied()
+                .filter(|index| {
+                    let window = entries[*index].window;
+                    window.first_day <= day && day < window.last_day
+                })
+                .collect();
+            if active.len() < 2 {
+                continue;
+            }
+            let capacity = capacity_constraints.entry(active).or_insert(1.0);
+            *capacity = capacity.min(remaining_pct / 100.0);
+        }
+    }
+
+    debug!(
+        "Linear optimization: {} variables, {} project and {} capacity constraints",
+        entries.len(),
+        project_constraints.len(),
+        capacity_constraints.len()
+    );
+
+    let shares: Vec<f64> = if entries.is_empty() {
+        vec![]
+    } else {
+        let mut problem = variables.maximise(objective).using(minilp);
+        for (staffed, open_hours) in project_constraints {
+            problem = problem.with(staffed.leq(open_hours));
+        }
+        for (active, capacity) in &capacity_constraints {
+            let mut total_share = Expression::default();
+            for index in active {
+                total_share += entries[*index].share;
+            }
+            problem = problem.with(total_share.leq(*capacity));
+        }
+
+        let solution = problem
+            .solve()
+            .map_err(|e| format!("Linear optimization failed: {}", e))?;
+        entries
+            .iter()
+            .map(|entry| solution.value(entry.share).max(0.0))
+            .collect()
+    };
+
+    let mut effective_by_project: HashMap<i64, f64> = HashMap::new();
+    for (entry, share) in entries.iter().zip(shares) {
+        let allocation_pct = (share * 100.0).min(100.0);
+        let effective_hours = calculate_assignment_effective_hours(
+            entry.window.available_hours,
+            allocation_pct,
+            entry.assignment.productivity_factor,
+        );
+        *effective_by_project
+            .entry(entry.assignment.project_id)
+            .or_default() += effective_hours;
+
+        allocations.add(entry.assignment.id, allocation_pct, effective_hours);
+    }
+    let calculations = allocations.calculations;
+
+    let mut infeasible_projects: Vec<_> = project_ids
+        .iter()
+        .filter_map(|project_id| {
+            let total_effective = setup.reserved_effective(*project_id)
+                + effective_by_project.get(project_id).copied().unwrap_or(0.0);
+            project_shortfall(
+                setup.requirements_map[project_id],
+                total_effective,
+                project_names,
+            )
+        })
+        .collect();
+
+    explain_shortfalls(
+        data,
+        &setup,
+        &calculations,
+        project_names,
+        &mut infeasible_projects,
+    )?;
+    let unsatisfied_constraints = unsatisfied_constraints(
+        assignments,
+        &calculations,
+        &setup.pinned_calculations,
+        &mut setup.warnings,
+    );
+
+    Ok(OptimizationResult {
+        success: true,
+        skipped_assignment_ids: skipped_assignment_ids(
+            assignments,
+            &calculations,
+            &setup.pinned_calculations,
+        ),
+        calculations,
+        pinned_calculations: setup.pinned_calculations,
+        infeasible_projects,
+        warnings: setup.warnings,
+        strategy: LINEAR_STRATEGY.to_string(),
+        options,
+        unsatisfied_constraints,
+        blocked_projects: vec![],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::optimize_proportional;
+    use crate::capacity::test_support::*;
+
+    #[test]
+    fn test_optimize_linear_finds_split_that_staffs_both_projects() {
+        // Person 1 can work on both projects, person 2 only on project 1
+        let data = two_person_data();
+        let assignments = vec![
+            person_assignment(1, 1, 1),
+            person_assignment(2, 1, 2),
+            person_assignment(3, 2, 1),
+        ];
+        let requirements = vec![requirement(1, 160.0, 10), requirement(2, 160.0, 10)];
+
+        let proportional =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+        assert_eq!(proportional.infeasible_projects.len(), 1);
+
+        let linear = optimize_linear(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &OptimizationOptions::default(),
+        )
+        .unwrap();
+        assert!(linear.infeasible_projects.is_empty());
+        let allocation = |id: i64| {
+            linear
+                .calculations
+                .iter()
+                .find(|c| c.assignment_id == id)
+                .unwrap()
+                .calculated_allocation_percentage
+        };
+        assert!((allocation(1) - 0.0).abs() < 0.001);
+        assert!((allocation(2) - 100.0).abs() < 0.001);
+        assert!((allocation(3) - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_optimize_linear_prefers_higher_priority() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 160.0, 0), requirement(2, 100.0, 20)];
+
+        let result = optimize_linear(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &OptimizationOptions::default(),
+        )
+        .unwrap();
+
+        let high = result
+            .calculations
+            .iter()
+            .find(|c| c.assignment_id == 2)
+            .unwrap();
+        let low = result
+            .calculations
+            .iter()
+            .find(|c| c.assignment_id == 1)
+            .unwrap();
+        assert!((high.calculated_effective_hours - 100.0).abs() < 0.001);
+        assert!((low.calculated_effective_hours - 60.0).abs() < 0.001);
+        assert_eq!(result.infeasible_projects.len(), 1);
+        assert_eq!(result.infeasible_projects[0].project_id, 1);
+    }
+
+    #[test]
+    fn test_optimize_linear_respects_overlapping_windows() {
+        // The second half of the month is shared by both assignments
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-18", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 160.0, 10), requirement(2, 80.0, 10)];
+
+        let result = optimize_linear(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &OptimizationOptions::default(),
+        )
+        .unwrap();
+
+        let total_pct: f64 = result
+            .calculations
+            .iter()
+            .map(|c| c.calculated_allocation_percentage)
+            .sum();
+        assert!(total_pct <= 100.0 + 0.001);
+    }
+
+    #[test]
+    fn test_optimize_linear_keeps_pinned_allocation() {
+        let data = single_person_data();
+        let assignments = vec![
+            pinned(project_assignment(1, 1, "2024-03-04", "2024-03-31"), 25.0),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 40.0, 0), requirement(2, 160.0, 30)];
+
+        let result = optimize_linear(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &OptimizationOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.pinned_calculations.len(), 1);
+        assert!(
+            (result.pinned_calculations[0].calculated_allocation_percentage - 25.0).abs() < 0.001
+        );
+        let other = &result.calculations[0];
+        assert!((other.calculated_allocation_percentage - 75.0).abs() < 0.001);
+    }
+}
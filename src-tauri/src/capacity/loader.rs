@@ -0,0 +1,1844 @@
+use super::{
+    absence_days, absence_deduction, calculation_staleness, dated_change_counts,
+    optimization_fingerprint, optimization_inputs_summary, optimization_preview,
+    optimization_run_calculations, optimization_run_comparison, optimization_strategy,
+    optimize_period_sequence, order_period_sequence, recommend_staffing, same_calculations,
+    schedule_auto_optimization, simulate_proportional, unique_ids, AbsenceDeduction,
+    AutoOptimizationSetting, CalculationStaleness, CapacitySimulation, ChangedInput,
+    InputChangeAction, MultiPeriodOptimizationResult, OptimizationInputsSummary,
+    OptimizationOptions, OptimizationPreview, OptimizationResult, OptimizationRun,
+    OptimizationRunCalculation, OptimizationRunComparison, OptimizationRunSummary,
+    OptimizationStrategy, PeriodCapacityData, PeriodSequenceInputs, ProposedAssignment,
+    RestoredOptimizationRun, SequencedStrategy, SkillData, StaffingRecommendations,
+    PROPORTIONAL_STRATEGY,
+};
+use crate::db::DbPool;
+use crate::models::{
+    Absence as ModelAbsence, AppSettings, Assignment, Holiday, JobOverheadTask, Person,
+    PersonJobAssignment, PersonSkill, PlanningPeriod, PlanningPeriodInputChange, ProjectDependency,
+    ProjectRequirement, ProjectSkillRequirement, Skill,
+};
+use log::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::OwnedMutexGuard;
+
+/// Load a country's holidays that overlap start..=end
+async fn load_holidays(
+    country_id: Option<i64>,
+    start: &str,
+    end: &str,
+    pool: &DbPool,
+) -> Result<Vec<Holiday>, String> {
+    // People without a country have no holidays
+    let Some(country_id) = country_id else {
+        return Ok(vec![]);
+    };
+
+    sqlx::query_as::<_, Holiday>(
+        "SELECT * FROM holidays
+         WHERE country_id = ?
+         AND start_date <= ?
+         AND end_date >= ?",
+    )
+    .bind(country_id)
+    .bind(end)
+    .bind(start)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch holidays: {}", e))
+}
+
+/// Count the days an absence takes out of a person's schedule
+///
+/// Only the person's working days between the dates count, and full-day public holidays of
+/// the person's country are skipped since they are deducted as holidays.
+pub async fn calculate_absence_days(
+    person: &Person,
+    start_date: &str,
+    end_date: &str,
+    pool: &DbPool,
+) -> Result<i64, String> {
+    let holidays = load_holidays(person.country_id, start_date, end_date, pool).await?;
+    absence_days(person, start_date, end_date, &holidays)
+}
+
+/// Calculate the days and hours an absence deducts, including partial-day absences
+///
+/// Public holidays inside the absence are skipped since they are deducted as holidays.
+pub async fn calculate_absence_deduction(
+    person: &Person,
+    absence: &ModelAbsence,
+    pool: &DbPool,
+) -> Result<AbsenceDeduction, String> {
+    let holidays = load_holidays(
+        person.country_id,
+        &absence.start_date,
+        &absence.end_date,
+        pool,
+    )
+    .await?;
+    absence_deduction(person, absence, &holidays)
+}
+
+/// Load the app-wide settings
+pub async fn load_app_settings(pool: &DbPool) -> Result<AppSettings, String> {
+    sqlx::query_as::<_, AppSettings>(
+        "SELECT default_max_utilization_percentage FROM app_settings WHERE id = 1",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch app settings: {}", e))
+}
+
+impl PeriodCapacityData {
+    /// Load the capacity inputs of a planning period
+    ///
+    /// People, absences, holidays, job assignments and overhead tasks are loaded in a
+    /// handful of queries. People without a utilization cap get the app-wide default.
+    pub async fn load(planning_period: PlanningPeriod, pool: &DbPool) -> Result<Self, String> {
+        let mut people = sqlx::query_as::<_, Person>("SELECT * FROM people ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch people: {}", e))?;
+
+        let settings = load_app_settings(pool).await?;
+        for person in &mut people {
+            person
+                .max_utilization_percentage
+                .get_or_insert(settings.default_max_utilization_percentage);
+        }
+
+        // Absences and holidays that overlap the period
+        let absences = sqlx::query_as::<_, ModelAbsence>(
+            "SELECT * FROM absences WHERE start_date <= ? AND end_date >= ? ORDER BY id",
+        )
+        .bind(&planning_period.end_date)
+        .bind(&planning_period.start_date)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch absences: {}", e))?;
+
+        let holidays = sqlx::query_as::<_, Holiday>(
+            "SELECT * FROM holidays WHERE start_date <= ? AND end_date >= ? ORDER BY id",
+        )
+        .bind(&planning_period.end_date)
+        .bind(&planning_period.start_date)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch holidays: {}", e))?;
+
+        let job_assignments = sqlx::query_as::<_, PersonJobAssignment>(
+            "SELECT * FROM person_job_assignments WHERE planning_period_id = ? ORDER BY id",
+        )
+        .bind(planning_period.id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch job assignments: {}", e))?;
+
+        let overhead_tasks = sqlx::query_as::<_, JobOverheadTask>(
+            "SELECT * FROM job_overhead_tasks WHERE job_id IN (
+                SELECT job_id FROM person_job_assignments WHERE planning_period_id = ?
+             )
+             ORDER BY id",
+        )
+        .bind(planning_period.id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch job overhead tasks: {}", e))?;
+
+        debug!(
+            "Loaded capacity data for planning period {}: {} people",
+            planning_period.id,
+            people.len()
+        );
+
+        Self::new(
+            planning_period,
+            people,
+            absences,
+            holidays,
+            job_assignments,
+            overhead_tasks,
+        )
+    }
+}
+
+impl SkillData {
+    /// Load the skills, proficiencies and skill requirements of a planning period
+    pub async fn load(planning_period_id: i64, pool: &DbPool) -> Result<Self, String> {
+        let skills = sqlx::query_as::<_, Skill>("SELECT * FROM skills")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch skills: {}", e))?;
+
+        let person_skills = sqlx::query_as::<_, PersonSkill>("SELECT * FROM person_skills")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch person skills: {}", e))?;
+
+        let requirements = sqlx::query_as::<_, ProjectSkillRequirement>(
+            "SELECT * FROM project_skill_requirements WHERE planning_period_id = ?",
+        )
+        .bind(planning_period_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch project skill requirements: {}", e))?;
+
+        Ok(SkillData {
+            skill_names: skills
+                .into_iter()
+                .map(|skill| (skill.id, skill.name))
+                .collect(),
+            person_skills,
+            requirements,
+        })
+    }
+}
+
+/// Wait until no other optimization reads or writes the planning period
+///
+/// Optimizing, applying a preview and restoring a run hold the guard from loading the
+/// inputs until the results are committed, so concurrent calls on one period run in turn.
+pub async fn lock_planning_period(planning_period_id: i64) -> OwnedMutexGuard<()> {
+    static LOCKS: OnceLock<Mutex<HashMap<i64, Arc<tokio::sync::Mutex<()>>>>> = OnceLock::new();
+
+    let lock = LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(planning_period_id)
+        .or_default()
+        .clone();
+    lock.lock_owned().await
+}
+
+/// Inputs of an optimization run for one planning period
+struct OptimizationInputs {
+    data: PeriodCapacityData,
+    assignments: Vec<Assignment>,
+    requirements: Vec<ProjectRequirement>,
+    project_names: HashMap<i64, String>,
+    dependencies: Vec<ProjectDependency>,
+    input_change_id: i64, // Latest input change the inputs include
+}
+
+impl OptimizationInputs {
+    /// Fingerprint of the inputs, see `optimization_fingerprint`
+    fn fingerprint(&self) -> Result<String, String> {
+        optimization_fingerprint(
+            &self.data,
+            &self.assignments,
+            &self.requirements,
+            &self.dependencies,
+        )
+    }
+
+    /// Summary of the inputs for the run history
+    fn summary(&self) -> Result<OptimizationInputsSummary, String> {
+        optimization_inputs_summary(
+            &self.data,
+            &self.assignments,
+            &self.requirements,
+            &self.dependencies,
+        )
+    }
+
+    /// Wrap a strategy so it respects the project dependencies
+    fn sequenced<'a>(&'a self, strategy: &'a dyn OptimizationStrategy) -> SequencedStrategy<'a> {
+        SequencedStrategy {
+            inner: strategy,
+            dependencies: &self.dependencies,
+        }
+    }
+}
+
+/// Load the planning period, its assignments, requirements and capacity data
+async fn load_optimization_inputs(
+    planning_period_id: i64,
+    pool: &DbPool,
+) -> Result<OptimizationInputs, String> {
+    // Read first, so a change made while loading keeps the period stale
+    let input_change_id = latest_input_change_id(planning_period_id, pool).await?;
+
+    // Load planning period
+    let planning_period =
+        sqlx::query_as::<_, PlanningPeriod>("SELECT * FROM planning_periods WHERE id = ?")
+            .bind(planning_period_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch planning period: {}", e))?;
+
+    // Load all assignments for this planning period
+    let assignments = sqlx::query_as::<_, Assignment>(
+        "SELECT * FROM assignments WHERE planning_period_id = ? ORDER BY id",
+    )
+    .bind(planning_period_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch assignments: {}", e))?;
+
+    // Load project requirements
+    let requirements = sqlx::query_as::<_, ProjectRequirement>(
+        "SELECT * FROM project_requirements WHERE planning_period_id = ? ORDER BY id",
+    )
+    .bind(planning_period_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch project requirements: {}", e))?;
+
+    // Load project names for shortfall reporting
+    let project_names: HashMap<i64, String> =
+        sqlx::query_as::<_, (i64, String)>("SELECT id, name FROM projects")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch projects: {}", e))?
+            .into_iter()
+            .collect();
+
+    // Load the dependencies between projects, they hold back dependent projects
+    let dependencies =
+        sqlx::query_as::<_, ProjectDependency>("SELECT * FROM project_dependencies ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch project dependencies: {}", e))?;
+
+    // Load people, absences, holidays and overhead for the whole period at once
+    let data = PeriodCapacityData::load(planning_period, pool).await?;
+
+    Ok(OptimizationInputs {
+        data,
+        assignments,
+        requirements,
+        project_names,
+        dependencies,
+        input_change_id,
+    })
+}
+
+/// Store the calculated allocation and effective hours on each assignment
+///
+/// The result is also recorded in the run history; returns the ID of the run.
+async fn save_calculations(
+    inputs: &OptimizationInputs,
+    inputs_summary: &OptimizationInputsSummary,
+    result: &OptimizationResult,
+    pool: &DbPool,
+) -> Result<i64, String> {
+    // All or nothing: a failure must not leave a mix of new and stale allocations
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let run_id = store_calculations(&mut tx, inputs, inputs_summary, result).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit optimization results: {}", e))?;
+
+    log_optimization_result(result);
+    Ok(run_id)
+}
+
+/// Write a result onto the assignments of its planning period and record the run
+///
+/// Runs on the connection of the transaction that stores the result.
+async fn store_calculations(
+    conn: &mut SqliteConnection,
+    inputs: &OptimizationInputs,
+    inputs_summary: &OptimizationInputsSummary,
+    result: &OptimizationResult,
+) -> Result<i64, String> {
+    let planning_period_id = inputs.data.planning_period.id;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    for calc in result
+        .calculations
+        .iter()
+        .chain(&result.pinned_calculations)
+    {
+        sqlx::query(
+            "UPDATE assignments
+             SET calculated_allocation_percentage = ?,
+                 calculated_effective_hours = ?,
+                 calculated_start_date = ?,
+                 last_calculated_at = ?
+             WHERE id = ? AND planning_period_id = ?",
+        )
+        .bind(calc.calculated_allocation_percentage)
+        .bind(calc.calculated_effective_hours)
+        .bind(&calc.calculated_start_date)
+        .bind(&now)
+        .bind(calc.assignment_id)
+        .bind(planning_period_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to update assignment {}: {}", calc.assignment_id, e))?;
+    }
+
+    // Clear the numbers of skipped assignments, they would be stale otherwise
+    for assignment_id in &result.skipped_assignment_ids {
+        reset_calculation(&mut *conn, *assignment_id, planning_period_id, &now).await?;
+    }
+
+    let run_id = record_optimization_run(
+        &mut *conn,
+        planning_period_id,
+        inputs_summary,
+        result,
+        &optimization_run_calculations(result, &inputs.assignments),
+        &now,
+    )
+    .await?;
+
+    // The calculations now reflect every change the inputs were loaded with
+    clear_input_changes(&mut *conn, planning_period_id, inputs.input_change_id).await?;
+    Ok(run_id)
+}
+
+/// Clear the stored calculation of an assignment
+async fn reset_calculation(
+    conn: &mut SqliteConnection,
+    assignment_id: i64,
+    planning_period_id: i64,
+    now: &str,
+) -> Result<(), String> {
+    sqlx::query(
+        "UPDATE assignments
+         SET calculated_allocation_percentage = NULL,
+             calculated_effective_hours = NULL,
+             calculated_start_date = NULL,
+             last_calculated_at = ?
+         WHERE id = ? AND planning_period_id = ?",
+    )
+    .bind(now)
+    .bind(assignment_id)
+    .bind(planning_period_id)
+    .execute(conn)
+    .await
+    .map_err(|e| format!("Failed to reset assignment {}: {}", assignment_id, e))?;
+    Ok(())
+}
+
+/// Log the shortfalls and totals of a stored result
+fn log_optimization_result(result: &OptimizationResult) {
+    for shortfall in &result.infeasible_projects {
+        warn!(
+            "Project {} is under-staffed by {:.1}h ({:.1}%)",
+            shortfall.project_id, shortfall.shortfall, shortfall.shortfall_percentage
+        );
+    }
+
+    info!(
+        "Optimization complete: {} calculations, {} pinned, {} skipped, {} infeasible projects, {} warnings",
+        result.calculations.len(),
+        result.pinned_calculations.len(),
+        result.skipped_assignment_ids.len(),
+        result.infeasible_projects.len(),
+        result.warnings.len()
+    );
+}
+
+/// Run an optimization strategy for a planning period
+///
+/// Loads the planning period, runs the strategy and stores the calculated allocation and
+/// effective hours on each assignment.
+pub async fn optimize_assignments_with_strategy(
+    planning_period_id: i64,
+    strategy: &dyn OptimizationStrategy,
+    pool: &DbPool,
+) -> Result<OptimizationResult, String> {
+    info!(
+        "Starting {} optimization for planning period {}",
+        strategy.name(),
+        planning_period_id
+    );
+
+    let _guard = lock_planning_period(planning_period_id).await;
+    let inputs = load_optimization_inputs(planning_period_id, pool).await?;
+    let result = inputs.sequenced(strategy).optimize(
+        &inputs.data,
+        &inputs.assignments,
+        &inputs.requirements,
+        &inputs.project_names,
+    )?;
+
+    let inputs_summary = inputs.summary()?;
+    save_calculations(&inputs, &inputs_summary, &result, pool).await?;
+    Ok(result)
+}
+
+/// Run an optimization strategy over consecutive planning periods
+///
+/// Unmet demand of each period is added to the next period's requirements. The results of
+/// all periods are stored and recorded in their run histories in one transaction.
+pub async fn optimize_planning_period_sequence(
+    planning_period_ids: &[i64],
+    strategy: &dyn OptimizationStrategy,
+    pool: &DbPool,
+) -> Result<MultiPeriodOptimizationResult, String> {
+    let mut ids = planning_period_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() != planning_period_ids.len() {
+        return Err("A planning period is listed more than once".to_string());
+    }
+
+    info!(
+        "Starting {} optimization for {} consecutive planning periods",
+        strategy.name(),
+        ids.len()
+    );
+
+    // Locked in ID order so overlapping sequences cannot deadlock
+    let mut _guards = Vec::with_capacity(ids.len());
+    for id in &ids {
+        _guards.push(lock_planning_period(*id).await);
+    }
+
+    let mut period_inputs = Vec::with_capacity(ids.len());
+    for id in &ids {
+        period_inputs.push(load_optimization_inputs(*id, pool).await?);
+    }
+    period_inputs.sort_by(|a, b| {
+        a.data
+            .planning_period
+            .start_date
+            .cmp(&b.data.planning_period.start_date)
+    });
+
+    let mut sequence: Vec<PeriodSequenceInputs> = period_inputs
+        .iter()
+        .map(|inputs| PeriodSequenceInputs {
+            data: &inputs.data,
+            assignments: &inputs.assignments,
+            requirements: &inputs.requirements,
+        })
+        .collect();
+    order_period_sequence(&mut sequence)?;
+    let project_names = period_inputs
+        .first()
+        .map(|inputs| inputs.project_names.clone())
+        .unwrap_or_default();
+    let dependencies = period_inputs
+        .first()
+        .map(|inputs| inputs.dependencies.clone())
+        .unwrap_or_default();
+    let strategy = SequencedStrategy {
+        inner: strategy,
+        dependencies: &dependencies,
+    };
+    let result = optimize_period_sequence(&sequence, &project_names, &strategy)?;
+
+    // All periods or none, the carried-over demand ties their results together
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for (inputs, period) in period_inputs.iter().zip(&result.periods) {
+        store_calculations(&mut tx, inputs, &inputs.summary()?, &period.result).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit optimization results: {}", e))?;
+
+    for period in &result.periods {
+        log_optimization_result(&period.result);
+    }
+    Ok(result)
+}
+
+/// Run an optimization strategy without storing the result
+///
+/// The preview lists the changes against the currently stored calculations and carries a
+/// fingerprint of the inputs, so it can be applied later if nothing changed in between.
+pub async fn preview_optimization(
+    planning_period_id: i64,
+    strategy: &dyn OptimizationStrategy,
+    pool: &DbPool,
+) -> Result<OptimizationPreview, String> {
+    info!(
+        "Previewing {} optimization for planning period {}",
+        strategy.name(),
+        planning_period_id
+    );
+
+    // Keeps a concurrent run from committing while the inputs are read
+    let _guard = lock_planning_period(planning_period_id).await;
+    let inputs = load_optimization_inputs(planning_period_id, pool).await?;
+    let input_fingerprint = inputs.fingerprint()?;
+    let result = inputs.sequenced(strategy).optimize(
+        &inputs.data,
+        &inputs.assignments,
+        &inputs.requirements,
+        &inputs.project_names,
+    )?;
+
+    Ok(optimization_preview(
+        planning_period_id,
+        input_fingerprint,
+        &inputs.assignments,
+        &inputs.project_names,
+        result,
+    ))
+}
+
+/// Store the result of a preview
+///
+/// Refuses when the planning period's data changed since the preview was calculated. The
+/// previewed strategy runs again and only its own result is stored, so a preview that was
+/// altered on the way back cannot write allocations the optimizer didn't calculate.
+pub async fn apply_optimization_preview(
+    preview: &OptimizationPreview,
+    pool: &DbPool,
+) -> Result<OptimizationResult, String> {
+    let strategy = optimization_strategy(
+        Some(&preview.result.strategy),
+        preview.result.options.clone(),
+    )?;
+
+    let _guard = lock_planning_period(preview.planning_period_id).await;
+    let inputs = load_optimization_inputs(preview.planning_period_id, pool).await?;
+    let inputs_summary = inputs.summary()?;
+    if inputs_summary.input_fingerprint != preview.input_fingerprint {
+        return Err(
+            "Planning period data changed since the optimization was previewed, preview it again"
+                .to_string(),
+        );
+    }
+
+    let result = inputs.sequenced(strategy.as_ref()).optimize(
+        &inputs.data,
+        &inputs.assignments,
+        &inputs.requirements,
+        &inputs.project_names,
+    )?;
+    if !same_calculations(&result, &preview.result) {
+        return Err(format!(
+            "The preview does not match the {} optimization of the planning period, preview it again",
+            result.strategy
+        ));
+    }
+
+    info!(
+        "Applying previewed {} optimization for planning period {}",
+        result.strategy, preview.planning_period_id
+    );
+    save_calculations(&inputs, &inputs_summary, &result, pool).await?;
+    Ok(result)
+}
+
+/// Monte Carlo simulation of optional overhead for a planning period
+///
+/// Nothing is written to the database. Passing a seed makes the runs reproducible.
+pub async fn simulate_capacity_monte_carlo(
+    planning_period_id: i64,
+    runs: usize,
+    seed: Option<u64>,
+    pool: &DbPool,
+) -> Result<CapacitySimulation, String> {
+    info!(
+        "Starting {} simulation runs for planning period {}",
+        runs, planning_period_id
+    );
+
+    let inputs = load_optimization_inputs(planning_period_id, pool).await?;
+
+    // Each run re-runs the optimizer, so keep them off the async runtime's threads
+    tokio::task::spawn_blocking(move || {
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        simulate_proportional(
+            &inputs.data,
+            &inputs.assignments,
+            &inputs.requirements,
+            &inputs.project_names,
+            &inputs.dependencies,
+            runs,
+            &mut rng,
+        )
+    })
+    .await
+    .map_err(|e| format!("Simulation failed: {}", e))?
+}
+
+/// Recommend new assignments for the infeasible projects of a planning period
+///
+/// Nothing is written to the database; accepted proposals are stored with
+/// `create_proposed_assignments`.
+pub async fn recommend_staffing_for_period(
+    planning_period_id: i64,
+    strategy: &dyn OptimizationStrategy,
+    productivity_factor: f64,
+    pool: &DbPool,
+) -> Result<StaffingRecommendations, String> {
+    info!(
+        "Recommending staffing for planning period {} with {} optimization",
+        planning_period_id,
+        strategy.name()
+    );
+
+    let inputs = load_optimization_inputs(planning_period_id, pool).await?;
+    recommend_staffing(
+        &inputs.data,
+        &inputs.assignments,
+        &inputs.requirements,
+        &inputs.project_names,
+        &inputs.sequenced(strategy),
+        productivity_factor,
+    )
+}
+
+/// Create the assignments of accepted staffing proposals
+///
+/// All proposals must belong to the planning period and must not duplicate an assignment of
+/// the same person to the same project; either all of them are created or none.
+pub async fn create_proposed_assignments(
+    planning_period_id: i64,
+    proposals: &[ProposedAssignment],
+    pool: &DbPool,
+) -> Result<Vec<Assignment>, String> {
+    let planning_period =
+        sqlx::query_as::<_, PlanningPeriod>("SELECT * FROM planning_periods WHERE id = ?")
+            .bind(planning_period_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch planning period: {}", e))?;
+
+    for proposal in proposals {
+        if proposal.planning_period_id != planning_period_id {
+            return Err(format!(
+                "Proposed assignment of person {} belongs to another planning period",
+                proposal.person_id
+            ));
+        }
+        if !(proposal.productivity_factor > 0.0 && proposal.productivity_factor <= 1.0) {
+            return Err("Productivity factor must be greater than 0 and at most 1".to_string());
+        }
+        if proposal.start_date < planning_period.start_date
+            || proposal.end_date > planning_period.end_date
+            || proposal.start_date > proposal.end_date
+        {
+            return Err(format!(
+                "Proposed assignment of person {} must lie within the planning period",
+                proposal.person_id
+            ));
+        }
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut created = Vec::with_capacity(proposals.len());
+    for proposal in proposals {
+        let requirement_exists = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM project_requirements
+             WHERE project_id = ? AND planning_period_id = ?",
+        )
+        .bind(proposal.project_id)
+        .bind(planning_period_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to check project requirement: {}", e))?;
+        if requirement_exists == 0 {
+            return Err(format!(
+                "Project {} has no requirement in this planning period",
+                proposal.project_id
+            ));
+        }
+
+        // Repeated proposals are caught too, the earlier copy is already inserted
+        let assignment_exists = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM assignments
+             WHERE person_id = ? AND project_id = ? AND planning_period_id = ?",
+        )
+        .bind(proposal.person_id)
+        .bind(proposal.project_id)
+        .bind(planning_period_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to check existing assignments: {}", e))?;
+        if assignment_exists > 0 {
+            return Err(format!(
+                "Person {} is already assigned to project {} in this planning period",
+                proposal.person_id, proposal.project_id
+            ));
+        }
+
+        let id = sqlx::query(
+            "INSERT INTO assignments
+             (person_id, project_id, planning_period_id, productivity_factor, start_date, end_date)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(proposal.person_id)
+        .bind(proposal.project_id)
+        .bind(planning_period_id)
+        .bind(proposal.productivity_factor)
+        .bind(&proposal.start_date)
+        .bind(&proposal.end_date)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to create proposed assignment: {}", e))?
+        .last_insert_rowid();
+
+        let assignment = sqlx::query_as::<_, Assignment>("SELECT * FROM assignments WHERE id = ?")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to fetch created assignment: {}", e))?;
+        created.push(assignment);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit proposed assignments: {}", e))?;
+
+    for assignment in &created {
+        let project_name = input_subject_name("projects", assignment.project_id, pool).await?;
+        record_input_change(
+            &[planning_period_id],
+            ChangedInput::Assignment,
+            InputChangeAction::Added,
+            project_name.as_deref(),
+            pool,
+        )
+        .await?;
+    }
+
+    info!(
+        "Created {} proposed assignments in planning period {}",
+        created.len(),
+        planning_period_id
+    );
+    Ok(created)
+}
+
+/// Record that an input of the given planning periods changed
+pub async fn record_input_change(
+    planning_period_ids: &[i64],
+    input: ChangedInput,
+    action: InputChangeAction,
+    subject: Option<&str>,
+    pool: &DbPool,
+) -> Result<(), String> {
+    for planning_period_id in unique_ids(planning_period_ids) {
+        insert_input_change(planning_period_id, input, action, subject, 1, pool).await?;
+    }
+    Ok(())
+}
+
+/// Record changes of dated inputs, counted per planning period their dates overlap
+///
+/// Periods none of the changed date ranges overlap are left alone.
+pub async fn record_dated_input_changes(
+    planning_period_ids: &[i64],
+    changed_ranges: &[Vec<(String, String)>],
+    input: ChangedInput,
+    action: InputChangeAction,
+    pool: &DbPool,
+) -> Result<(), String> {
+    let candidates = unique_ids(planning_period_ids);
+    if candidates.is_empty() || changed_ranges.is_empty() {
+        return Ok(());
+    }
+
+    let mut periods = sqlx::query_as::<_, PlanningPeriod>("SELECT * FROM planning_periods")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch planning periods: {}", e))?;
+    periods.retain(|period| candidates.contains(&period.id));
+
+    for (planning_period_id, overlapping) in dated_change_counts(&periods, changed_ranges) {
+        insert_input_change(planning_period_id, input, action, None, overlapping, pool).await?;
+    }
+    Ok(())
+}
+
+/// Record changes of holidays in the planning periods of the country's people
+pub async fn record_holiday_changes(
+    country_id: i64,
+    changed_ranges: &[Vec<(String, String)>],
+    action: InputChangeAction,
+    pool: &DbPool,
+) -> Result<(), String> {
+    let planning_period_ids = country_planning_periods(country_id, pool).await?;
+    record_dated_input_changes(
+        &planning_period_ids,
+        changed_ranges,
+        ChangedInput::Holiday,
+        action,
+        pool,
+    )
+    .await
+}
+
+async fn insert_input_change(
+    planning_period_id: i64,
+    input: ChangedInput,
+    action: InputChangeAction,
+    subject: Option<&str>,
+    change_count: i64,
+    pool: &DbPool,
+) -> Result<(), String> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire connection: {}", e))?;
+    store_input_change(
+        &mut conn,
+        planning_period_id,
+        input,
+        action,
+        subject,
+        change_count,
+    )
+    .await?;
+
+    if input.triggers_auto_optimization() {
+        schedule_auto_optimization(planning_period_id);
+    }
+    Ok(())
+}
+
+/// Store an input change without re-optimizing, on the connection of a transaction
+async fn store_input_change(
+    conn: &mut SqliteConnection,
+    planning_period_id: i64,
+    input: ChangedInput,
+    action: InputChangeAction,
+    subject: Option<&str>,
+    change_count: i64,
+) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO planning_period_input_changes
+         (planning_period_id, input, action, subject, change_count)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(planning_period_id)
+    .bind(input.as_str())
+    .bind(action.as_str())
+    .bind(subject)
+    .bind(change_count)
+    .execute(conn)
+    .await
+    .map_err(|e| format!("Failed to record input change: {}", e))?;
+
+    debug!(
+        "Planning period {} is stale: {} {} {}",
+        planning_period_id,
+        change_count,
+        input.as_str(),
+        action.as_str()
+    );
+    Ok(())
+}
+
+/// Planning periods in which a person is assigned
+pub async fn person_planning_periods(person_id: i64, pool: &DbPool) -> Result<Vec<i64>, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT DISTINCT planning_period_id FROM assignments WHERE person_id = ?",
+    )
+    .bind(person_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch planning periods of person: {}", e))
+}
+
+/// Planning periods in which people of a country are assigned
+pub async fn country_planning_periods(country_id: i64, pool: &DbPool) -> Result<Vec<i64>, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT DISTINCT a.planning_period_id FROM assignments a
+         JOIN people p ON p.id = a.person_id
+         WHERE p.country_id = ?",
+    )
+    .bind(country_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch planning periods of country: {}", e))
+}
+
+/// Planning periods in which a project is required or staffed
+pub async fn project_planning_periods(project_id: i64, pool: &DbPool) -> Result<Vec<i64>, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT planning_period_id FROM project_requirements WHERE project_id = ?
+         UNION
+         SELECT planning_period_id FROM assignments WHERE project_id = ?",
+    )
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch planning periods of project: {}", e))
+}
+
+/// Planning periods in which someone holds a job
+pub async fn job_planning_periods(job_id: i64, pool: &DbPool) -> Result<Vec<i64>, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT DISTINCT planning_period_id FROM person_job_assignments WHERE job_id = ?",
+    )
+    .bind(job_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch planning periods of job: {}", e))
+}
+
+/// Planning periods with at least one assignment
+pub async fn staffed_planning_periods(pool: &DbPool) -> Result<Vec<i64>, String> {
+    sqlx::query_scalar::<_, i64>("SELECT DISTINCT planning_period_id FROM assignments")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch staffed planning periods: {}", e))
+}
+
+/// Name of a person, project or job to describe a change with
+pub async fn input_subject_name(
+    table: &'static str,
+    id: i64,
+    pool: &DbPool,
+) -> Result<Option<String>, String> {
+    sqlx::query_scalar::<_, String>(&format!("SELECT name FROM {} WHERE id = ?", table))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch name from {}: {}", table, e))
+}
+
+/// Staleness of one planning period's calculations
+pub async fn load_calculation_staleness(
+    planning_period_id: i64,
+    pool: &DbPool,
+) -> Result<CalculationStaleness, String> {
+    let changes = sqlx::query_as::<_, PlanningPeriodInputChange>(
+        "SELECT * FROM planning_period_input_changes WHERE planning_period_id = ? ORDER BY id",
+    )
+    .bind(planning_period_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch input changes: {}", e))?;
+
+    Ok(calculation_staleness(&changes))
+}
+
+/// Staleness of every planning period with recorded input changes
+pub async fn load_all_calculation_staleness(
+    pool: &DbPool,
+) -> Result<HashMap<i64, CalculationStaleness>, String> {
+    let changes = sqlx::query_as::<_, PlanningPeriodInputChange>(
+        "SELECT * FROM planning_period_input_changes ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch input changes: {}", e))?;
+
+    let mut by_period: HashMap<i64, Vec<PlanningPeriodInputChange>> = HashMap::new();
+    for change in changes {
+        by_period
+            .entry(change.planning_period_id)
+            .or_default()
+            .push(change);
+    }
+    Ok(by_period
+        .into_iter()
+        .map(|(planning_period_id, changes)| (planning_period_id, calculation_staleness(&changes)))
+        .collect())
+}
+
+/// ID of the latest input change of a planning period, 0 when there is none
+pub async fn latest_input_change_id(planning_period_id: i64, pool: &DbPool) -> Result<i64, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(MAX(id), 0) FROM planning_period_input_changes
+         WHERE planning_period_id = ?",
+    )
+    .bind(planning_period_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch input changes: {}", e))
+}
+
+/// Forget the input changes an optimization has taken into account
+///
+/// Changes recorded after its inputs were loaded stay, so the period remains stale.
+pub async fn clear_input_changes(
+    conn: &mut SqliteConnection,
+    planning_period_id: i64,
+    up_to_change_id: i64,
+) -> Result<(), String> {
+    sqlx::query(
+        "DELETE FROM planning_period_input_changes WHERE planning_period_id = ? AND id <= ?",
+    )
+    .bind(planning_period_id)
+    .bind(up_to_change_id)
+    .execute(conn)
+    .await
+    .map_err(|e| format!("Failed to clear input changes: {}", e))?;
+    Ok(())
+}
+
+/// Row of the optimization_runs table; the JSON columns are parsed by `into_run`
+#[derive(sqlx::FromRow)]
+struct OptimizationRunRow {
+    id: i64,
+    planning_period_id: i64,
+    strategy: String,
+    options: String,
+    inputs_summary: String,
+    infeasible_projects: String,
+    warnings: String,
+    created_at: String,
+}
+
+impl OptimizationRunRow {
+    fn into_run(
+        self,
+        calculations: Vec<OptimizationRunCalculation>,
+    ) -> Result<OptimizationRun, String> {
+        Ok(OptimizationRun {
+            id: self.id,
+            planning_period_id: self.planning_period_id,
+            strategy: self.strategy,
+            options: from_json(&self.options, "run options")?,
+            inputs: from_json(&self.inputs_summary, "run inputs")?,
+            calculations,
+            infeasible_projects: from_json(&self.infeasible_projects, "run shortfalls")?,
+            warnings: from_json(&self.warnings, "run warnings")?,
+            created_at: self.created_at,
+        })
+    }
+}
+
+fn to_json<T: Serialize>(value: &T, what: &str) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("Failed to serialize {}: {}", what, e))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str, what: &str) -> Result<T, String> {
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse {}: {}", what, e))
+}
+
+/// Store an optimization result in the run history
+///
+/// Runs on the connection of the transaction that stores the result itself.
+pub async fn record_optimization_run(
+    conn: &mut SqliteConnection,
+    planning_period_id: i64,
+    inputs: &OptimizationInputsSummary,
+    result: &OptimizationResult,
+    calculations: &[OptimizationRunCalculation],
+    created_at: &str,
+) -> Result<i64, String> {
+    let run_id = sqlx::query(
+        "INSERT INTO optimization_runs
+         (planning_period_id, strategy, options, inputs_summary, infeasible_projects, warnings, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(planning_period_id)
+    .bind(&result.strategy)
+    .bind(to_json(&result.options, "run options")?)
+    .bind(to_json(inputs, "run inputs")?)
+    .bind(to_json(&result.infeasible_projects, "run shortfalls")?)
+    .bind(to_json(&result.warnings, "run warnings")?)
+    .bind(created_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to create optimization run: {}", e))?
+    .last_insert_rowid();
+
+    for calc in calculations {
+        sqlx::query(
+            "INSERT INTO optimization_run_calculations
+             (run_id, assignment_id, person_id, project_id, allocation_percentage, effective_hours, is_pinned, calculated_start_date)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(run_id)
+        .bind(calc.assignment_id)
+        .bind(calc.person_id)
+        .bind(calc.project_id)
+        .bind(calc.allocation_percentage)
+        .bind(calc.effective_hours)
+        .bind(calc.is_pinned)
+        .bind(&calc.calculated_start_date)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to store optimization run calculation: {}", e))?;
+    }
+
+    info!(
+        "Recorded optimization run {} for planning period {}",
+        run_id, planning_period_id
+    );
+    Ok(run_id)
+}
+
+/// List the stored runs of a planning period, newest first
+pub async fn list_stored_optimization_runs(
+    planning_period_id: i64,
+    pool: &DbPool,
+) -> Result<Vec<OptimizationRunSummary>, String> {
+    let rows = sqlx::query_as::<_, OptimizationRunRow>(
+        "SELECT * FROM optimization_runs WHERE planning_period_id = ? ORDER BY id DESC",
+    )
+    .bind(planning_period_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch optimization runs: {}", e))?;
+
+    rows.into_iter()
+        .map(|row| {
+            let run = row.into_run(vec![])?;
+            Ok(OptimizationRunSummary {
+                id: run.id,
+                planning_period_id: run.planning_period_id,
+                strategy: run.strategy,
+                inputs: run.inputs,
+                infeasible_projects: run.infeasible_projects.len(),
+                warnings: run.warnings.len(),
+                created_at: run.created_at,
+            })
+        })
+        .collect()
+}
+
+/// Load a stored run with its calculations
+pub async fn load_stored_optimization_run(
+    run_id: i64,
+    pool: &DbPool,
+) -> Result<OptimizationRun, String> {
+    let row =
+        sqlx::query_as::<_, OptimizationRunRow>("SELECT * FROM optimization_runs WHERE id = ?")
+            .bind(run_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch optimization run: {}", e))?;
+
+    let calculations = sqlx::query_as::<_, OptimizationRunCalculation>(
+        "SELECT assignment_id, person_id, project_id, allocation_percentage, effective_hours, is_pinned,
+                calculated_start_date
+         FROM optimization_run_calculations WHERE run_id = ? ORDER BY assignment_id",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch optimization run calculations: {}", e))?;
+
+    row.into_run(calculations)
+}
+
+/// Compare the allocations of two stored runs
+pub async fn compare_stored_optimization_runs(
+    base_run_id: i64,
+    other_run_id: i64,
+    pool: &DbPool,
+) -> Result<OptimizationRunComparison, String> {
+    let base = load_stored_optimization_run(base_run_id, pool).await?;
+    let other = load_stored_optimization_run(other_run_id, pool).await?;
+
+    let project_names: HashMap<i64, String> =
+        sqlx::query_as::<_, (i64, String)>("SELECT id, name FROM projects")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch projects: {}", e))?
+            .into_iter()
+            .collect();
+
+    Ok(optimization_run_comparison(&base, &other, &project_names))
+}
+
+/// Write a stored run's allocations back onto the assignments
+///
+/// Assignments the run has no calculation for are cleared, as when the run was stored.
+/// Restoring an older run than the latest one marks the planning period stale, since the
+/// input changes made after it were forgotten by the later runs. Restoring the latest run
+/// leaves the changes made since it.
+pub async fn restore_stored_optimization_run(
+    run_id: i64,
+    pool: &DbPool,
+) -> Result<RestoredOptimizationRun, String> {
+    let run = load_stored_optimization_run(run_id, pool).await?;
+    let planning_period_id = run.planning_period_id;
+    let _guard = lock_planning_period(planning_period_id).await;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut restored_assignments = 0;
+    let mut missing_assignment_ids = Vec::new();
+    for calc in &run.calculations {
+        let updated = sqlx::query(
+            "UPDATE assignments
+             SET calculated_allocation_percentage = ?,
+                 calculated_effective_hours = ?,
+                 calculated_start_date = ?,
+                 last_calculated_at = ?
+             WHERE id = ? AND planning_period_id = ?",
+        )
+        .bind(calc.allocation_percentage)
+        .bind(calc.effective_hours)
+        .bind(&calc.calculated_start_date)
+        .bind(&now)
+        .bind(calc.assignment_id)
+        .bind(planning_period_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to update assignment {}: {}", calc.assignment_id, e))?
+        .rows_affected();
+
+        if updated == 0 {
+            missing_assignment_ids.push(calc.assignment_id);
+        } else {
+            restored_assignments += 1;
+        }
+    }
+
+    // Numbers the run didn't calculate would not belong to the restored result
+    let restored_ids: HashSet<i64> = run.calculations.iter().map(|c| c.assignment_id).collect();
+    let assignment_ids: Vec<i64> =
+        sqlx::query_scalar("SELECT id FROM assignments WHERE planning_period_id = ?")
+            .bind(planning_period_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to fetch assignments: {}", e))?;
+    let mut reset_assignments = 0;
+    for assignment_id in assignment_ids
+        .into_iter()
+        .filter(|id| !restored_ids.contains(id))
+    {
+        reset_calculation(&mut tx, assignment_id, planning_period_id, &now).await?;
+        reset_assignments += 1;
+    }
+
+    // Changes since the latest run are still recorded, those before it are not
+    let latest_run_id: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(id), 0) FROM optimization_runs WHERE planning_period_id = ?",
+    )
+    .bind(planning_period_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch optimization runs: {}", e))?;
+    if run_id == latest_run_id {
+        // Back to the latest result, earlier restores no longer make it stale
+        sqlx::query(
+            "DELETE FROM planning_period_input_changes WHERE planning_period_id = ? AND input = ?",
+        )
+        .bind(planning_period_id)
+        .bind(ChangedInput::OptimizationRun.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to clear input changes: {}", e))?;
+    } else {
+        store_input_change(
+            &mut tx,
+            planning_period_id,
+            ChangedInput::OptimizationRun,
+            InputChangeAction::Restored,
+            Some(&format!("#{}", run_id)),
+            1,
+        )
+        .await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit restored allocations: {}", e))?;
+
+    if !missing_assignment_ids.is_empty() {
+        warn!(
+            "Optimization run {} references {} deleted assignments",
+            run_id,
+            missing_assignment_ids.len()
+        );
+    }
+    info!(
+        "Restored optimization run {} onto {} assignments, cleared {}",
+        run_id, restored_assignments, reset_assignments
+    );
+
+    Ok(RestoredOptimizationRun {
+        run_id,
+        restored_assignments,
+        missing_assignment_ids,
+    })
+}
+
+/// Run the optimization of a planning period with its automatic setting, if enabled
+///
+/// Returns None when the period does not re-optimize automatically.
+pub async fn auto_optimize(
+    planning_period_id: i64,
+    pool: &DbPool,
+) -> Result<Option<OptimizationResult>, String> {
+    let setting = load_auto_optimization_setting(planning_period_id, pool).await?;
+    if !setting.enabled {
+        return Ok(None);
+    }
+
+    let strategy = optimization_strategy(Some(&setting.strategy), setting.options)?;
+    info!(
+        "Re-optimizing planning period ID {} with {} after input changes",
+        planning_period_id,
+        strategy.name()
+    );
+    optimize_assignments_with_strategy(planning_period_id, strategy.as_ref(), pool)
+        .await
+        .map(Some)
+}
+
+/// Row of the planning_period_auto_optimization table
+#[derive(sqlx::FromRow)]
+struct AutoOptimizationRow {
+    strategy: String,
+    options: String,
+}
+
+/// Automatic re-optimization setting of a planning period; disabled when never set
+pub async fn load_auto_optimization_setting(
+    planning_period_id: i64,
+    pool: &DbPool,
+) -> Result<AutoOptimizationSetting, String> {
+    let row = sqlx::query_as::<_, AutoOptimizationRow>(
+        "SELECT strategy, options FROM planning_period_auto_optimization
+         WHERE planning_period_id = ?",
+    )
+    .bind(planning_period_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to fetch auto-optimization setting: {}", e))?;
+
+    match row {
+        Some(row) => Ok(AutoOptimizationSetting {
+            planning_period_id,
+            enabled: true,
+            strategy: row.strategy,
+            options: serde_json::from_str(&row.options)
+                .map_err(|e| format!("Failed to parse auto-optimization options: {}", e))?,
+        }),
+        None => Ok(AutoOptimizationSetting {
+            planning_period_id,
+            enabled: false,
+            strategy: PROPORTIONAL_STRATEGY.to_string(),
+            options: OptimizationOptions::default(),
+        }),
+    }
+}
+
+/// IDs of the planning periods that re-optimize automatically
+pub async fn load_auto_optimized_planning_periods(pool: &DbPool) -> Result<HashSet<i64>, String> {
+    let ids: Vec<i64> =
+        sqlx::query_scalar("SELECT planning_period_id FROM planning_period_auto_optimization")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to fetch auto-optimization settings: {}", e))?;
+    Ok(ids.into_iter().collect())
+}
+
+/// Turn automatic re-optimization of a planning period on or off
+///
+/// Enabling it re-optimizes right away when the period's inputs already changed.
+pub async fn save_auto_optimization_setting(
+    planning_period_id: i64,
+    enabled: bool,
+    strategy: Option<&str>,
+    options: OptimizationOptions,
+    pool: &DbPool,
+) -> Result<AutoOptimizationSetting, String> {
+    if !enabled {
+        sqlx::query("DELETE FROM planning_period_auto_optimization WHERE planning_period_id = ?")
+            .bind(planning_period_id)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to disable auto-optimization: {}", e))?;
+        info!(
+            "Disabled automatic re-optimization of planning period ID {}",
+            planning_period_id
+        );
+        return load_auto_optimization_setting(planning_period_id, pool).await;
+    }
+
+    // Validates the strategy and options before they are stored
+    let strategy_name = optimization_strategy(strategy, options.clone())?.name();
+    let options_json = serde_json::to_string(&options)
+        .map_err(|e| format!("Failed to serialize auto-optimization options: {}", e))?;
+    sqlx::query(
+        "INSERT INTO planning_period_auto_optimization (planning_period_id, strategy, options)
+         VALUES (?, ?, ?)
+         ON CONFLICT(planning_period_id)
+         DO UPDATE SET strategy = excluded.strategy, options = excluded.options",
+    )
+    .bind(planning_period_id)
+    .bind(strategy_name)
+    .bind(options_json)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to enable auto-optimization: {}", e))?;
+    info!(
+        "Enabled automatic {} re-optimization of planning period ID {}",
+        strategy_name, planning_period_id
+    );
+
+    if load_calculation_staleness(planning_period_id, pool)
+        .await?
+        .is_stale
+    {
+        schedule_auto_optimization(planning_period_id);
+    }
+    load_auto_optimization_setting(planning_period_id, pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+    use crate::capacity::ProportionalStrategy;
+
+    #[tokio::test]
+    async fn test_apply_preview_rejects_changed_dependencies() {
+        let pool = seeded_database().await;
+
+        let preview = preview_optimization(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO project_dependencies (project_id, depends_on_project_id) VALUES (2, 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let error = apply_optimization_preview(&preview, &pool)
+            .await
+            .unwrap_err();
+        assert!(error.contains("changed since the optimization was previewed"));
+
+        // A preview of the current inputs applies
+        let preview = preview_optimization(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        apply_optimization_preview(&preview, &pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_apply_preview_rejects_altered_result() {
+        let pool = seeded_database().await;
+        let stored_allocations = || async {
+            sqlx::query_scalar::<_, Option<f64>>(
+                "SELECT calculated_allocation_percentage FROM assignments ORDER BY id",
+            )
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+
+        let mut preview = preview_optimization(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        preview.result.calculations[0].calculated_allocation_percentage = 100.0;
+        let error = apply_optimization_preview(&preview, &pool)
+            .await
+            .unwrap_err();
+        assert!(error.contains("does not match the proportional optimization"));
+
+        // The recorded strategy has to be one the server can re-run
+        let mut preview = preview_optimization(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        preview.result.strategy = "handpicked".to_string();
+        let error = apply_optimization_preview(&preview, &pool)
+            .await
+            .unwrap_err();
+        assert!(error.contains("Unknown optimization strategy"));
+        assert_eq!(stored_allocations().await, vec![None, None]);
+
+        let preview = preview_optimization(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        let applied = apply_optimization_preview(&preview, &pool).await.unwrap();
+        assert!(same_calculations(&applied, &preview.result));
+        assert!(stored_allocations().await.iter().all(Option::is_some));
+    }
+
+    #[tokio::test]
+    async fn test_period_sequence_is_stored_together() {
+        let pool = seeded_database().await;
+        sqlx::query(
+            "INSERT INTO planning_periods (id, start_date, end_date)
+             VALUES (2, '2024-04-01', '2024-04-28'), (3, '2024-05-06', '2024-05-31')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let run_count = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM optimization_runs")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        // April and May leave a gap, so nothing is stored
+        let error = optimize_planning_period_sequence(&[1, 2, 3], &ProportionalStrategy, &pool)
+            .await
+            .unwrap_err();
+        assert!(error.contains("not consecutive"));
+        assert_eq!(run_count().await, 0);
+
+        let result = optimize_planning_period_sequence(&[1, 2], &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        assert_eq!(result.periods.len(), 2);
+        assert_eq!(run_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_proposed_assignments_rejects_duplicates() {
+        let pool = seeded_database().await;
+        sqlx::query(
+            "INSERT INTO people (id, name, email, available_hours_per_week)
+             VALUES (2, 'Person 2', 'person2@example.com', 40.0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let proposal = |person_id: i64| ProposedAssignment {
+            person_id,
+            person_name: format!("Person {}", person_id),
+            project_id: 1,
+            planning_period_id: 1,
+            productivity_factor: 0.5,
+            start_date: "2024-03-04".to_string(),
+            end_date: "2024-03-31".to_string(),
+            allocation_percentage: 25.0,
+            effective_hours: 20.0,
+        };
+        let assignment_count = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM assignments")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        // Person 1 already works on project 1
+        let error = create_proposed_assignments(1, &[proposal(2), proposal(1)], &pool)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "Person 1 is already assigned to project 1 in this planning period"
+        );
+        assert_eq!(assignment_count().await, 2);
+
+        // The same proposal twice in one batch
+        assert!(
+            create_proposed_assignments(1, &[proposal(2), proposal(2)], &pool)
+                .await
+                .is_err()
+        );
+        assert_eq!(assignment_count().await, 2);
+
+        let created = create_proposed_assignments(1, &[proposal(2)], &pool)
+            .await
+            .unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].person_id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_restore_run_resets_other_assignments_and_tracks_staleness() {
+        let pool = seeded_database().await;
+        let first = optimize_assignments_with_strategy(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        assert_eq!(first.calculations.len(), 2);
+
+        // Person 2 joins project 1, and a second run includes the new assignment
+        sqlx::query(
+            "INSERT INTO people (id, name, email, available_hours_per_week)
+             VALUES (2, 'Person 2', 'person2@example.com', 40.0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO assignments
+             (id, person_id, project_id, planning_period_id, productivity_factor, start_date, end_date)
+             VALUES (3, 2, 1, 1, 1.0, '2024-03-04', '2024-03-31')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        record_input_change(
+            &[1],
+            ChangedInput::Assignment,
+            InputChangeAction::Added,
+            None,
+            &pool,
+        )
+        .await
+        .unwrap();
+        optimize_assignments_with_strategy(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        assert!(!load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+
+        let runs = list_stored_optimization_runs(1, &pool).await.unwrap();
+        let (latest_run, first_run) = (runs[0].id, runs[1].id);
+        let restored = restore_stored_optimization_run(first_run, &pool)
+            .await
+            .unwrap();
+        assert_eq!(restored.restored_assignments, 2);
+
+        let calculated: Vec<(i64, Option<f64>)> = sqlx::query_as(
+            "SELECT id, calculated_allocation_percentage FROM assignments ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert!(calculated[0].1.is_some());
+        assert!(calculated[1].1.is_some());
+        assert_eq!(calculated[2], (3, None));
+
+        let staleness = load_calculation_staleness(1, &pool).await.unwrap();
+        assert_eq!(staleness.reasons, vec!["optimization run #1 restored"]);
+
+        // Restoring the latest run makes the period fresh again
+        restore_stored_optimization_run(latest_run, &pool)
+            .await
+            .unwrap();
+        assert!(!load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+    }
+
+    /// Stale planning periods with their reasons, by ID
+    async fn stale_periods(pool: &DbPool) -> Vec<(i64, Vec<String>)> {
+        let mut stale: Vec<(i64, Vec<String>)> = load_all_calculation_staleness(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|(_, staleness)| staleness.is_stale)
+            .map(|(id, staleness)| (id, staleness.reasons))
+            .collect();
+        stale.sort();
+        stale
+    }
+
+    #[tokio::test]
+    async fn test_dated_changes_mark_only_overlapping_periods() {
+        let pool = seeded_database().await;
+        for statement in [
+            "INSERT INTO planning_periods (id, start_date, end_date)
+             VALUES (2, '2024-04-01', '2024-04-28'), (3, '2024-04-29', '2024-05-26')",
+            "INSERT INTO countries (id, iso_code, name) VALUES (1, 'DE', 'Germany')",
+            "UPDATE people SET country_id = 1 WHERE id = 1",
+            "INSERT INTO assignments
+             (person_id, project_id, planning_period_id, productivity_factor, start_date, end_date)
+             VALUES (1, 1, 2, 1.0, '2024-04-01', '2024-04-28'),
+                    (1, 1, 3, 1.0, '2024-04-29', '2024-05-26')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        let range = |start: &str, end: &str| vec![(start.to_string(), end.to_string())];
+
+        // An absence outside every period marks none
+        let person_periods = person_planning_periods(1, &pool).await.unwrap();
+        record_dated_input_changes(
+            &person_periods,
+            &[range("2024-06-03", "2024-06-07")],
+            ChangedInput::Absence,
+            InputChangeAction::Added,
+            &pool,
+        )
+        .await
+        .unwrap();
+        assert!(stale_periods(&pool).await.is_empty());
+
+        // An absence across the turn of the month marks March and April
+        record_dated_input_changes(
+            &person_periods,
+            &[range("2024-03-28", "2024-04-02")],
+            ChangedInput::Absence,
+            InputChangeAction::Added,
+            &pool,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            stale_periods(&pool).await,
+            vec![
+                (1, vec!["absence added".to_string()]),
+                (2, vec!["absence added".to_string()]),
+            ]
+        );
+
+        // A holiday of the person's country marks the period it falls in
+        record_holiday_changes(
+            1,
+            &[range("2024-05-01", "2024-05-01")],
+            InputChangeAction::Added,
+            &pool,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            stale_periods(&pool).await[2],
+            (3, vec!["holiday added".to_string()])
+        );
+
+        // Holidays of other countries mark nothing
+        record_holiday_changes(
+            2,
+            &[range("2024-03-04", "2024-05-26")],
+            InputChangeAction::Added,
+            &pool,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stale_periods(&pool).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_saved_optimization_clears_the_changes_it_includes() {
+        let pool = seeded_database().await;
+        let record_absence = || {
+            record_input_change(
+                &[1],
+                ChangedInput::Absence,
+                InputChangeAction::Added,
+                None,
+                &pool,
+            )
+        };
+
+        record_absence().await.unwrap();
+        assert!(load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+        optimize_assignments_with_strategy(1, &ProportionalStrategy, &pool)
+            .await
+            .unwrap();
+        assert!(!load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+
+        // A change recorded after the inputs were loaded keeps the period stale
+        record_absence().await.unwrap();
+        let loaded_up_to = latest_input_change_id(1, &pool).await.unwrap();
+        record_absence().await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        clear_input_changes(&mut conn, 1, loaded_up_to)
+            .await
+            .unwrap();
+        drop(conn);
+        let staleness = load_calculation_staleness(1, &pool).await.unwrap();
+        assert_eq!(staleness.reasons, vec!["absence added"]);
+
+        let latest = latest_input_change_id(1, &pool).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        clear_input_changes(&mut conn, 1, latest).await.unwrap();
+        drop(conn);
+        assert!(!load_calculation_staleness(1, &pool).await.unwrap().is_stale);
+    }
+}
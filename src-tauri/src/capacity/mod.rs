@@ -1,12 +1,34 @@
-use crate::db::DbPool;
+mod auto_optimize;
+mod dependencies;
+mod linear;
+mod loader;
+mod sequence;
+mod skills;
+mod staffing;
+mod staleness;
+mod strategy;
+#[cfg(test)]
+mod test_support;
+
+pub use auto_optimize::*;
+pub use dependencies::*;
+pub use linear::*;
+pub use loader::*;
+pub use sequence::*;
+pub use skills::*;
+pub use staffing::*;
+pub use staleness::*;
+pub use strategy::*;
+
 use crate::models::{
     Absence as ModelAbsence, Assignment, Holiday, JobOverheadTask, Person, PersonJobAssignment,
-    PlanningPeriod, ProjectRequirement,
+    PlanningPeriod, ProjectDependency, ProjectRequirement,
 };
 use chrono::{Datelike, NaiveDate, Weekday};
-use log::{debug, info, warn};
+use log::debug;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 // Helper functions for working days
 
@@ -41,13 +63,151 @@ fn is_working_day(date: &NaiveDate, working_days_set: &[Weekday]) -> bool {
     working_days_set.contains(&weekday)
 }
 
+/// Count the dates between start and end (inclusive) that fall on one of the person's working days
+fn count_working_days(start: NaiveDate, end: NaiveDate, working_days_set: &[Weekday]) -> i64 {
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .filter(|date| is_working_day(date, working_days_set))
+        .count() as i64
+}
+
 // Analytics types
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OptimizationResult {
     pub success: bool,
     pub calculations: Vec<AssignmentCalculation>,
+    pub pinned_calculations: Vec<AssignmentCalculation>, // Assignments locked at their pinned allocation
+    pub infeasible_projects: Vec<ProjectShortfall>,
+    pub warnings: Vec<String>,
+    pub skipped_assignment_ids: Vec<i64>, // Not calculated, e.g. projects without requirement
+    pub strategy: String,                 // Name of the strategy that produced the result
+    pub options: OptimizationOptions,     // Options the strategy ran with, defaults filled in
+    pub unsatisfied_constraints: Vec<UnsatisfiedConstraint>, // Allocation bounds that could not be met
+    pub blocked_projects: Vec<BlockedProject>,               // Held back by project dependencies
+}
+
+/// Which allocation bound of an assignment was not met
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationBound {
+    Minimum,
+    Maximum,
+}
+
+/// An assignment whose allocation ended up outside its minimum or maximum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsatisfiedConstraint {
+    pub assignment_id: i64,
+    pub person_id: i64,
+    pub project_id: i64,
+    pub bound: AllocationBound,
+    pub bound_percentage: f64,
+    pub allocation_percentage: f64, // What the assignment got instead
+}
+
+/// Change an optimization would make to one assignment
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignmentDiff {
+    pub assignment_id: i64,
+    pub person_id: i64,
+    pub project_id: i64,
+    pub old_allocation_percentage: Option<f64>, // None if not calculated
+    pub new_allocation_percentage: Option<f64>, // None if not calculated or cleared
+    pub old_effective_hours: Option<f64>,
+    pub new_effective_hours: Option<f64>,
+}
+
+/// Change an optimization would make to a project's effective hours
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectStaffingDelta {
+    pub project_id: i64,
+    pub project_name: String,
+    pub old_effective_hours: f64,
+    pub new_effective_hours: f64,
+    pub delta_hours: f64,
+}
+
+/// Optimization result that has not been stored yet, with its changes to the current state
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OptimizationPreview {
+    pub planning_period_id: i64,
+    pub input_fingerprint: String, // Identifies the data the preview was calculated from
+    pub result: OptimizationResult,
+    pub assignment_diffs: Vec<AssignmentDiff>,
+    pub project_deltas: Vec<ProjectStaffingDelta>, // Sorted by project name
+}
+
+/// What an optimization run started from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationInputsSummary {
+    pub input_fingerprint: String,
+    pub people: usize,
+    pub assignments: usize,
+    pub pinned_assignments: usize,
+    pub required_projects: usize,
+    pub required_hours: f64,
+}
+
+/// Calculated allocation of one assignment in a stored run
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OptimizationRunCalculation {
+    pub assignment_id: i64,
+    pub person_id: i64,
+    pub project_id: i64,
+    pub allocation_percentage: f64,
+    pub effective_hours: f64,
+    pub is_pinned: bool,
+    pub calculated_start_date: Option<String>, // Set when project dependencies delayed the start
+}
+
+/// A stored optimization run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OptimizationRun {
+    pub id: i64,
+    pub planning_period_id: i64,
+    pub strategy: String,
+    pub options: OptimizationOptions,
+    pub inputs: OptimizationInputsSummary,
+    pub calculations: Vec<OptimizationRunCalculation>,
     pub infeasible_projects: Vec<ProjectShortfall>,
     pub warnings: Vec<String>,
+    pub created_at: String,
+}
+
+/// A stored optimization run without its calculations, for listing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OptimizationRunSummary {
+    pub id: i64,
+    pub planning_period_id: i64,
+    pub strategy: String,
+    pub inputs: OptimizationInputsSummary,
+    pub infeasible_projects: usize,
+    pub warnings: usize,
+    pub created_at: String,
+}
+
+/// Differences between the allocations of two stored runs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OptimizationRunComparison {
+    pub base_run_id: i64,
+    pub other_run_id: i64,
+    pub assignment_diffs: Vec<AssignmentDiff>, // Old values from the base run
+    pub project_deltas: Vec<ProjectStaffingDelta>, // Sorted by project name
+}
+
+/// Outcome of restoring a stored run onto the assignments
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoredOptimizationRun {
+    pub run_id: i64,
+    pub restored_assignments: usize,
+    pub missing_assignment_ids: Vec<i64>, // Assignments deleted since the run
+}
+
+/// Tuning options of the optimization strategies; a strategy rejects options it doesn't use
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OptimizationOptions {
+    pub priority_weight_step: Option<f64>, // Linear: objective weight added per priority level
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,6 +215,8 @@ pub struct AssignmentCalculation {
     pub assignment_id: i64,
     pub calculated_allocation_percentage: f64,
     pub calculated_effective_hours: f64,
+    #[serde(default)]
+    pub calculated_start_date: Option<String>, // Set when project dependencies delay the start
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -65,6 +227,33 @@ pub struct ProjectShortfall {
     pub available_effective_hours: f64,
     pub shortfall: f64,
     pub shortfall_percentage: f64,
+    #[serde(default)] // Missing from runs stored before explanations existed
+    pub explanation: Vec<ShortfallPersonExplanation>, // One entry per assigned person
+}
+
+/// What an assigned person's capacity went to while their project fell short
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortfallPersonExplanation {
+    pub assignment_id: i64,
+    pub person_id: i64,
+    pub person_name: String,
+    pub available_hours: f64, // Inside the assignment window, after the deductions below
+    pub absence_hours: f64,
+    pub holiday_hours: f64,
+    pub overhead_hours: f64,
+    pub optional_overhead_hours: f64,
+    pub allocation_percentage: f64, // Share the short project got
+    pub effective_hours: f64,
+    pub competing_projects: Vec<CompetingProjectHours>, // Higher- or equal-priority projects
+}
+
+/// Hours a person spent on another project during a short project's assignment window
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompetingProjectHours {
+    pub project_id: i64,
+    pub project_name: String,
+    pub priority: i64,
+    pub allocated_hours: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,9 +261,11 @@ pub struct CapacityOverview {
     pub total_people: usize,
     pub total_projects: usize,
     pub over_committed_people: usize,
+    pub over_utilization_cap_people: usize, // Planned above their cap, including over-committed
     pub under_staffed_projects: usize,
     pub people_capacity: Vec<PersonCapacity>,
     pub project_staffing: Vec<ProjectStaffing>,
+    pub staleness: CalculationStaleness, // Whether the allocations shown are out of date
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -82,15 +273,18 @@ pub struct PersonCapacity {
     pub person_id: i64,
     pub person_name: String,
     pub person_email: String,
+    pub working_days: i64,
     pub total_available_hours: f64,
     pub total_allocated_hours: f64,
     pub total_effective_hours: f64,
     pub utilization_percentage: f64,
-    pub is_over_committed: bool,
+    pub is_over_committed: bool, // Planned above 100% of available hours
+    pub max_utilization_percentage: f64, // Planned utilization cap
+    pub is_over_utilization_cap: bool, // Planned above the cap
     pub assignments: Vec<AssignmentSummary>,
-    pub absence_days: i64,
+    pub absence_days: f64,
     pub absence_hours: f64,
-    pub holiday_days: i64,
+    pub holiday_days: f64,
     pub holiday_hours: f64,
     pub base_available_hours: f64,
     pub overhead_hours: f64,          // Required overhead tasks only
@@ -108,6 +302,7 @@ pub struct ProjectStaffing {
     pub is_viable: bool,
     pub shortfall: f64,
     pub assigned_people: Vec<PersonAssignmentSummary>,
+    pub skill_staffing: Vec<SkillStaffing>, // Empty when the project requires no skills
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,21 +320,92 @@ pub struct PersonAssignmentSummary {
     pub allocation_percentage: f64,
     pub productivity_factor: f64,
     pub effective_hours: f64,
-    pub absence_days: i64,
+    pub absence_days: f64,
     pub absence_hours: f64,
-    pub holiday_days: i64,
+    pub holiday_days: f64,
     pub holiday_hours: f64,
     pub overhead_hours: f64,          // Required overhead tasks only
     pub optional_overhead_hours: f64, // Optional overhead tasks (weighted at 50%)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CapacityTimeline {
+    pub planning_period_id: i64,
+    pub granularity: String, // "week" (ISO weeks) or "month"
+    pub buckets: Vec<TimelineBucket>,
+    pub people: Vec<PersonTimeline>,
+    pub projects: Vec<ProjectTimeline>,
+    pub staleness: CalculationStaleness, // Whether the allocations shown are out of date
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    pub label: String,      // e.g. "2024-W05" or "2024-01"
+    pub start_date: String, // Clipped to the planning period
+    pub end_date: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonTimeline {
+    pub person_id: i64,
+    pub person_name: String,
+    pub points: Vec<TimelinePoint>, // One point per bucket, in bucket order
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectTimeline {
+    pub project_id: i64,
+    pub project_name: String,
+    pub required_hours: f64,
+    pub points: Vec<TimelinePoint>, // One point per bucket, in bucket order
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimelinePoint {
+    pub available_hours: f64,
+    pub allocated_hours: f64,
+    pub effective_hours: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CapacitySimulation {
+    pub planning_period_id: i64,
+    pub runs: usize,
+    pub people: Vec<PersonSimulation>,
+    pub projects: Vec<ProjectSimulation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonSimulation {
+    pub person_id: i64,
+    pub person_name: String,
+    pub available_hours: SimulationDistribution,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectSimulation {
+    pub project_id: i64,
+    pub project_name: String,
+    pub required_hours: f64,
+    pub staffing_percentage: SimulationDistribution,
+    pub viable_probability: f64, // Share of runs in which the project is fully staffed (0.0 to 1.0)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationDistribution {
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+#[derive(Debug, PartialEq)]
 pub struct PersonAvailableHoursBreakdown {
     pub available_hours: f64,
     pub base_hours: f64,
-    pub absence_days: i64,
+    pub working_days: i64, // Calendar dates in the period that match the person's working days
+    pub absence_days: f64,
     pub absence_hours: f64,
-    pub holiday_days: i64,
+    pub holiday_days: f64,
     pub holiday_hours: f64,
     pub overhead_hours: f64,          // Required overhead tasks only
     pub optional_overhead_hours: f64, // Optional overhead tasks (weighted at 50%)
@@ -147,165 +413,540 @@ pub struct PersonAvailableHoursBreakdown {
 
 // Core calculation functions
 
-/// Calculate available hours for a person in a planning period, accounting for absences
-pub async fn calculate_person_available_hours(
-    person: &Person,
+/// Parse an ISO 8601 date (YYYY-MM-DD), labelling the error with what the date belongs to
+fn parse_date(date: &str, label: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid {}: {}", label, e))
+}
+
+/// Parse the start and end date of a planning period
+pub fn planning_period_bounds(
     planning_period: &PlanningPeriod,
-    pool: &DbPool,
-) -> Result<PersonAvailableHoursBreakdown, String> {
-    // Parse dates
-    let start = NaiveDate::parse_from_str(&planning_period.start_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid start date: {}", e))?;
-    let end = NaiveDate::parse_from_str(&planning_period.end_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid end date: {}", e))?;
-
-    // Calculate total days in period
-    let total_days = (end - start).num_days() + 1;
-
-    // Parse person's working days configuration
-    let working_days_count = parse_working_days_count(&person.working_days) as f64;
-    let working_days_set = parse_working_days_set(&person.working_days);
+) -> Result<(NaiveDate, NaiveDate), String> {
+    Ok((
+        parse_date(&planning_period.start_date, "start date")?,
+        parse_date(&planning_period.end_date, "end date")?,
+    ))
+}
+
+/// Parse an assignment's date window, clipped to the planning period
+///
+/// A start calculated from project dependencies moves the window's start later; the window
+/// is empty when it lies past the end.
+fn assignment_window(
+    assignment: &Assignment,
+    planning_period: &PlanningPeriod,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    let (period_start, period_end) = planning_period_bounds(planning_period)?;
+    let mut start = parse_date(&assignment.start_date, "assignment start date")?;
+    if let Some(calculated_start) = &assignment.calculated_start_date {
+        start = start.max(parse_date(
+            calculated_start,
+            "assignment calculated start date",
+        )?);
+    }
+    let end = parse_date(&assignment.end_date, "assignment end date")?;
+    Ok((start.max(period_start), end.min(period_end)))
+}
+
+/// Collect the working dates between start and end (inclusive) covered by any of the date ranges
+///
+/// Overlapping ranges are merged, so each date is counted once.
+fn working_dates_in_ranges(
+    ranges: &[(NaiveDate, NaiveDate)],
+    start: NaiveDate,
+    end: NaiveDate,
+    working_days_set: &[Weekday],
+) -> BTreeSet<NaiveDate> {
+    ranges
+        .iter()
+        .flat_map(|(range_start, range_end)| {
+            let range_end = (*range_end).min(end);
+            (*range_start)
+                .max(start)
+                .iter_days()
+                .take_while(move |date| *date <= range_end)
+        })
+        .filter(|date| is_working_day(date, working_days_set))
+        .collect()
+}
+
+/// Split start..=end into consecutive ISO weeks ("week") or calendar months ("month")
+///
+/// The first and last bucket are clipped to start and end. Each bucket is returned with
+/// its label, e.g. "2024-W05" or "2024-01".
+pub fn timeline_ranges(
+    start: NaiveDate,
+    end: NaiveDate,
+    granularity: &str,
+) -> Result<Vec<(String, NaiveDate, NaiveDate)>, String> {
+    let mut ranges = Vec::new();
+    let mut bucket_start = start;
+
+    while bucket_start <= end {
+        let (label, bucket_end) = match granularity {
+            "week" => {
+                let iso_week = bucket_start.iso_week();
+                let sunday = bucket_start
+                    + chrono::Duration::days(
+                        6 - bucket_start.weekday().num_days_from_monday() as i64,
+                    );
+                (
+                    format!("{}-W{:02}", iso_week.year(), iso_week.week()),
+                    sunday,
+                )
+            }
+            "month" => {
+                let next_month = if bucket_start.month() == 12 {
+                    NaiveDate::from_ymd_opt(bucket_start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(bucket_start.year(), bucket_start.month() + 1, 1)
+                }
+                .ok_or_else(|| "Invalid month boundary".to_string())?;
+                (
+                    bucket_start.format("%Y-%m").to_string(),
+                    next_month - chrono::Duration::days(1),
+                )
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid granularity '{}': expected 'week' or 'month'",
+                    granularity
+                ))
+            }
+        };
+
+        let bucket_end = bucket_end.min(end);
+        ranges.push((label, bucket_start, bucket_end));
+        bucket_start = bucket_end + chrono::Duration::days(1);
+    }
 
-    // Calculate working days based on person's schedule
-    let total_weeks = total_days as f64 / 7.0;
-    let working_days = total_weeks * working_days_count;
+    Ok(ranges)
+}
 
-    // Calculate hours per day based on person's working days
-    let hours_per_day = if working_days_count > 0.0 {
+/// Hours a person works on each of their working days
+fn person_hours_per_day(person: &Person) -> f64 {
+    let working_days_count = parse_working_days_count(&person.working_days) as f64;
+    if working_days_count > 0.0 {
         person.available_hours_per_week / working_days_count
     } else {
         0.0
-    };
+    }
+}
 
-    // Calculate base hours (before absences)
-    let base_hours = working_days * hours_per_day;
-
-    // Get absences for this person within the planning period
-    let absences = sqlx::query_as::<_, ModelAbsence>(
-        "SELECT * FROM absences 
-             WHERE person_id = ? 
-             AND (
-                (start_date >= ? AND start_date <= ?) OR
-                (end_date >= ? AND end_date <= ?) OR
-                (start_date <= ? AND end_date >= ?)
-             )",
-    )
-    .bind(person.id)
-    .bind(&planning_period.start_date)
-    .bind(&planning_period.end_date)
-    .bind(&planning_period.start_date)
-    .bind(&planning_period.end_date)
-    .bind(&planning_period.start_date)
-    .bind(&planning_period.end_date)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch absences: {}", e))?;
-
-    let total_absence_days: i64 = absences.iter().map(|a| a.days).sum();
-    let absence_hours = total_absence_days as f64 * hours_per_day;
-
-    // Get holidays for this person's country within the planning period
-    let (total_holiday_days, holiday_hours) = if let Some(country_id) = person.country_id {
-        let holidays = sqlx::query_as::<_, Holiday>(
-            "SELECT * FROM holidays 
-             WHERE country_id = ? 
-             AND start_date <= ? 
-             AND end_date >= ?",
-        )
-        .bind(country_id)
-        .bind(&planning_period.end_date)
-        .bind(&planning_period.start_date)
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch holidays: {}", e))?;
-
-        // Calculate total holiday days, accounting for partial overlaps
-        let mut total_holiday_days = 0i64;
+/// Fraction of a working day taken out by an entry that may cover only part of the day
+///
+/// A fixed number of hours is converted with the person's hours per day, an explicit
+/// fraction is used as-is, and neither means a full day.
+fn partial_day_fraction(hours: Option<f64>, day_fraction: Option<f64>, hours_per_day: f64) -> f64 {
+    if let Some(hours) = hours {
+        if hours_per_day > 0.0 {
+            (hours / hours_per_day).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    } else if let Some(day_fraction) = day_fraction {
+        day_fraction.clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+/// Fraction of a working day an absence takes out (1.0 for a full-day absence)
+fn absence_day_fraction(absence: &ModelAbsence, hours_per_day: f64) -> f64 {
+    partial_day_fraction(absence.hours, absence.day_fraction, hours_per_day)
+}
+
+/// Fraction of a working day a holiday takes out (1.0 for a full-day holiday)
+fn holiday_day_fraction(holiday: &Holiday, hours_per_day: f64) -> f64 {
+    partial_day_fraction(holiday.hours, holiday.day_fraction, hours_per_day)
+}
+
+/// Map each working date between start and end (inclusive) covered by the ranges to the
+/// fraction of the day taken out
+///
+/// When ranges overlap on a date, the largest fraction wins so the day is counted once.
+fn day_fractions_by_date(
+    ranges: &[(NaiveDate, NaiveDate, f64)],
+    start: NaiveDate,
+    end: NaiveDate,
+    working_days_set: &[Weekday],
+) -> BTreeMap<NaiveDate, f64> {
+    let mut fractions: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for (range_start, range_end, fraction) in ranges {
+        for date in
+            working_dates_in_ranges(&[(*range_start, *range_end)], start, end, working_days_set)
+        {
+            let entry = fractions.entry(date).or_insert(0.0);
+            *entry = entry.max(*fraction);
+        }
+    }
+    fractions
+}
+
+/// Map each working date between start and end (inclusive) to the fraction of the day
+/// the person is absent
+///
+/// When absences overlap on a date, the largest fraction wins so the day is counted once.
+fn absence_fractions_by_date(
+    absences: &[ModelAbsence],
+    start: NaiveDate,
+    end: NaiveDate,
+    working_days_set: &[Weekday],
+    hours_per_day: f64,
+) -> Result<BTreeMap<NaiveDate, f64>, String> {
+    let ranges = absences
+        .iter()
+        .map(|absence| {
+            Ok((
+                parse_date(&absence.start_date, "absence start date")?,
+                parse_date(&absence.end_date, "absence end date")?,
+                absence_day_fraction(absence, hours_per_day),
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(day_fractions_by_date(&ranges, start, end, working_days_set))
+}
+
+/// Map each working date between start and end (inclusive) to the fraction of the day
+/// that is a public holiday
+///
+/// Half-day holidays such as Christmas Eve only take out their fraction or hours.
+fn holiday_fractions_by_date(
+    holidays: &[Holiday],
+    start: NaiveDate,
+    end: NaiveDate,
+    working_days_set: &[Weekday],
+    hours_per_day: f64,
+) -> Result<BTreeMap<NaiveDate, f64>, String> {
+    let ranges = holidays
+        .iter()
+        .map(|holiday| {
+            Ok((
+                parse_date(&holiday.start_date, "holiday start date")?,
+                parse_date(&holiday.end_date, "holiday end date")?,
+                holiday_day_fraction(holiday, hours_per_day),
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(day_fractions_by_date(&ranges, start, end, working_days_set))
+}
+
+/// Sum the absence fractions that are not already covered by a holiday
+///
+/// Holidays take precedence: on a half-day holiday an absence can only take out the
+/// other half of the day.
+fn absence_days_outside_holidays(
+    absence_fractions: &BTreeMap<NaiveDate, f64>,
+    holiday_fractions: &BTreeMap<NaiveDate, f64>,
+) -> f64 {
+    absence_fractions
+        .iter()
+        .map(|(date, fraction)| {
+            let holiday_fraction = holiday_fractions.get(date).copied().unwrap_or(0.0);
+            fraction.min(1.0 - holiday_fraction).max(0.0)
+        })
+        .sum()
+}
+
+/// Count the days an absence between two dates takes out of a person's schedule
+///
+/// Only the person's working days between the dates count, and full-day public holidays of
+/// the person's country are skipped since they are deducted as holidays.
+pub fn absence_days(
+    person: &Person,
+    start_date: &str,
+    end_date: &str,
+    holidays: &[Holiday],
+) -> Result<i64, String> {
+    let start = parse_date(start_date, "absence start date")?;
+    let end = parse_date(end_date, "absence end date")?;
+    let working_days_set = parse_working_days_set(&person.working_days);
+
+    let holiday_fractions = holiday_fractions_by_date(
+        holidays,
+        start,
+        end,
+        &working_days_set,
+        person_hours_per_day(person),
+    )?;
+    let absence_dates = working_dates_in_ranges(&[(start, end)], start, end, &working_days_set);
+
+    Ok(absence_dates
+        .iter()
+        .filter(|date| holiday_fractions.get(date).copied().unwrap_or(0.0) < 1.0)
+        .count() as i64)
+}
+
+/// Days and hours a single absence takes out of a person's schedule
+#[derive(Debug)]
+pub struct AbsenceDeduction {
+    pub days: f64,
+    pub hours: f64,
+}
+
+/// Calculate the days and hours an absence deducts, including partial-day absences
+///
+/// Public holidays inside the absence are skipped since they are deducted as holidays.
+pub fn absence_deduction(
+    person: &Person,
+    absence: &ModelAbsence,
+    holidays: &[Holiday],
+) -> Result<AbsenceDeduction, String> {
+    let start = parse_date(&absence.start_date, "absence start date")?;
+    let end = parse_date(&absence.end_date, "absence end date")?;
+    let working_days_set = parse_working_days_set(&person.working_days);
+    let hours_per_day = person_hours_per_day(person);
+
+    let holiday_fractions =
+        holiday_fractions_by_date(holidays, start, end, &working_days_set, hours_per_day)?;
+    let absence_fractions = absence_fractions_by_date(
+        std::slice::from_ref(absence),
+        start,
+        end,
+        &working_days_set,
+        hours_per_day,
+    )?;
+
+    let days = absence_days_outside_holidays(&absence_fractions, &holiday_fractions);
+
+    Ok(AbsenceDeduction {
+        days,
+        hours: days * hours_per_day,
+    })
+}
+
+/// Everything the capacity calculations need for one planning period
+///
+/// People, absences, holidays and overhead tasks are grouped up front, so computing
+/// breakdowns for every person and assignment of the period is a pure in-memory
+/// calculation.
+#[derive(Debug, Clone)]
+pub struct PeriodCapacityData {
+    pub planning_period: PlanningPeriod,
+    pub people: Vec<Person>, // Ordered by name
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    person_index: HashMap<i64, usize>,
+    absences_by_person: HashMap<i64, Vec<ModelAbsence>>,
+    holidays_by_country: HashMap<i64, Vec<Holiday>>,
+    overhead_tasks_by_person: HashMap<i64, Vec<JobOverheadTask>>,
+    input_fingerprint: u64, // Hash of the records the data was built from
+}
+
+impl PeriodCapacityData {
+    /// Build the capacity inputs of a planning period from in-memory records
+    ///
+    /// Absences, holidays and overhead tasks outside the period are ignored by the
+    /// calculations, so callers may pass more than the period needs.
+    pub fn new(
+        planning_period: PlanningPeriod,
+        people: Vec<Person>,
+        absences: Vec<ModelAbsence>,
+        holidays: Vec<Holiday>,
+        job_assignments: Vec<PersonJobAssignment>,
+        overhead_tasks: Vec<JobOverheadTask>,
+    ) -> Result<Self, String> {
+        let (period_start, period_end) = planning_period_bounds(&planning_period)?;
+        let input_fingerprint = hash_records(&(
+            &planning_period,
+            &people,
+            &absences,
+            &holidays,
+            &job_assignments,
+            &overhead_tasks,
+        ))?;
+
+        let person_index = people
+            .iter()
+            .enumerate()
+            .map(|(index, person)| (person.id, index))
+            .collect();
+
+        let mut absences_by_person: HashMap<i64, Vec<ModelAbsence>> = HashMap::new();
+        for absence in absences {
+            absences_by_person
+                .entry(absence.person_id)
+                .or_default()
+                .push(absence);
+        }
+
+        let mut holidays_by_country: HashMap<i64, Vec<Holiday>> = HashMap::new();
         for holiday in holidays {
-            let holiday_start = NaiveDate::parse_from_str(&holiday.start_date, "%Y-%m-%d")
-                .map_err(|e| format!("Invalid holiday start date: {}", e))?;
-            let holiday_end = NaiveDate::parse_from_str(&holiday.end_date, "%Y-%m-%d")
-                .map_err(|e| format!("Invalid holiday end date: {}", e))?;
-
-            // Calculate the overlap between holiday and planning period
-            let overlap_start = holiday_start.max(start);
-            let overlap_end = holiday_end.min(end);
-
-            if overlap_start <= overlap_end {
-                // Count only holiday days that fall on person's working days
-                let mut working_holiday_days = 0i64;
-                let mut current_date = overlap_start;
-
-                while current_date <= overlap_end {
-                    // Check if this date is a working day for this person
-                    if is_working_day(&current_date, &working_days_set) {
-                        // Check if this date overlaps with any absence
-                        let mut is_absent = false;
-                        for absence in &absences {
-                            let absence_start =
-                                NaiveDate::parse_from_str(&absence.start_date, "%Y-%m-%d")
-                                    .map_err(|e| format!("Invalid absence start date: {}", e))?;
-                            let absence_end =
-                                NaiveDate::parse_from_str(&absence.end_date, "%Y-%m-%d")
-                                    .map_err(|e| format!("Invalid absence end date: {}", e))?;
-
-                            if current_date >= absence_start && current_date <= absence_end {
-                                is_absent = true;
-                                break;
-                            }
-                        }
-
-                        // Only count if not already covered by absence
-                        if !is_absent {
-                            working_holiday_days += 1;
-                        }
-                    }
-                    current_date = current_date.succ_opt().unwrap();
-                }
+            holidays_by_country
+                .entry(holiday.country_id)
+                .or_default()
+                .push(holiday);
+        }
+
+        let mut tasks_by_job: HashMap<i64, Vec<JobOverheadTask>> = HashMap::new();
+        for task in overhead_tasks {
+            tasks_by_job.entry(task.job_id).or_default().push(task);
+        }
 
-                total_holiday_days += working_holiday_days;
+        // Each job assignment contributes all overhead tasks of its job
+        let mut overhead_tasks_by_person: HashMap<i64, Vec<JobOverheadTask>> = HashMap::new();
+        for job_assignment in job_assignments {
+            if let Some(tasks) = tasks_by_job.get(&job_assignment.job_id) {
+                overhead_tasks_by_person
+                    .entry(job_assignment.person_id)
+                    .or_default()
+                    .extend(tasks.iter().cloned());
             }
         }
 
-        let holiday_hours = total_holiday_days as f64 * hours_per_day;
-        (total_holiday_days, holiday_hours)
-    } else {
-        // Person has no country assigned, so no holidays
-        (0, 0.0)
-    };
+        Ok(Self {
+            planning_period,
+            people,
+            period_start,
+            period_end,
+            person_index,
+            absences_by_person,
+            holidays_by_country,
+            overhead_tasks_by_person,
+            input_fingerprint,
+        })
+    }
 
-    // Get job assignments for this person within the planning period
-    let job_assignments = sqlx::query_as::<_, PersonJobAssignment>(
-        "SELECT * FROM person_job_assignments 
-         WHERE person_id = ? AND planning_period_id = ?",
-    )
-    .bind(person.id)
-    .bind(planning_period.id)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch job assignments: {}", e))?;
-
-    // Calculate overhead hours from all assigned jobs' overhead tasks
-    // Split into required (is_optional = false) and optional (is_optional = true)
-    // For optional tasks, we apply the per-task weight and track both raw and weighted hours
-    let mut overhead_hours = 0.0;
-    let mut optional_overhead_hours = 0.0; // Raw optional hours (unweighted, for display)
-    let mut weighted_optional_overhead = 0.0; // Weighted optional hours (for calculation)
-    for job_assignment in job_assignments {
-        // Get all overhead tasks for this job
-        let overhead_tasks = sqlx::query_as::<_, JobOverheadTask>(
-            "SELECT * FROM job_overhead_tasks WHERE job_id = ?",
-        )
-        .bind(job_assignment.job_id)
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch job overhead tasks: {}", e))?;
+    /// Look up a person of the period by ID
+    pub fn person(&self, person_id: i64) -> Option<&Person> {
+        self.person_index
+            .get(&person_id)
+            .map(|index| &self.people[*index])
+    }
+
+    /// Copy of the data in which every optional overhead task either happens in full or not
+    /// at all
+    ///
+    /// Each optional task of each person is drawn once, using its optional_weight as the
+    /// probability that it happens during the period.
+    pub fn sample_optional_overhead<R: Rng>(&self, rng: &mut R) -> Self {
+        let mut sampled = self.clone();
+        // Draw in name order so a seeded generator gives reproducible runs
+        for person in &self.people {
+            let Some(tasks) = sampled.overhead_tasks_by_person.get_mut(&person.id) else {
+                continue;
+            };
+            for task in tasks.iter_mut().filter(|task| task.is_optional) {
+                let happens = rng.gen_bool(task.optional_weight.clamp(0.0, 1.0));
+                task.optional_weight = if happens { 1.0 } else { 0.0 };
+            }
+        }
+        sampled
+    }
+
+    /// Available hours of a person over the whole planning period
+    pub fn person_breakdown(
+        &self,
+        person: &Person,
+    ) -> Result<PersonAvailableHoursBreakdown, String> {
+        self.breakdown_in_range(person, self.period_start, self.period_end)
+    }
+
+    /// Available hours of a person within an assignment's date window
+    pub fn assignment_breakdown(
+        &self,
+        person: &Person,
+        assignment: &Assignment,
+    ) -> Result<PersonAvailableHoursBreakdown, String> {
+        let (start, end) = assignment_window(assignment, &self.planning_period)?;
+        self.breakdown_in_range(person, start, end)
+    }
+
+    /// Available hours of a person within the part of an assignment's date window that
+    /// falls between two dates
+    ///
+    /// Returns None when the window and the range do not overlap.
+    pub fn assignment_breakdown_in_range(
+        &self,
+        person: &Person,
+        assignment: &Assignment,
+        range_start: NaiveDate,
+        range_end: NaiveDate,
+    ) -> Result<Option<PersonAvailableHoursBreakdown>, String> {
+        let (window_start, window_end) = assignment_window(assignment, &self.planning_period)?;
+        let start = window_start.max(range_start);
+        let end = window_end.min(range_end);
+        if start > end {
+            return Ok(None);
+        }
+        self.breakdown_in_range(person, start, end).map(Some)
+    }
+
+    /// Available hours of a person between two dates of the planning period
+    ///
+    /// The range is clipped to the planning period. Absences and holidays are only counted
+    /// inside the range, and overhead tasks are prorated to the working days and weeks it
+    /// covers.
+    pub fn breakdown_in_range(
+        &self,
+        person: &Person,
+        range_start: NaiveDate,
+        range_end: NaiveDate,
+    ) -> Result<PersonAvailableHoursBreakdown, String> {
+        let start = range_start.max(self.period_start);
+        let end = range_end.min(self.period_end);
+
+        // Calculate total days in range (empty when the range lies outside the period)
+        let total_days = ((end - start).num_days() + 1).max(0);
+
+        // Parse person's working days configuration
+        let working_days_set = parse_working_days_set(&person.working_days);
+
+        // Walk the calendar and count the dates that fall on the person's working days
+        let total_weeks = total_days as f64 / 7.0;
+        let working_days = count_working_days(start, end, &working_days_set);
 
+        // Calculate hours per day based on person's working days
+        let hours_per_day = person_hours_per_day(person);
+
+        // Calculate base hours (before absences)
+        let base_hours = working_days as f64 * hours_per_day;
+
+        let absences = self
+            .absences_by_person
+            .get(&person.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        // People without a country have no holidays
+        let holidays = person
+            .country_id
+            .and_then(|country_id| self.holidays_by_country.get(&country_id))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let overhead_tasks = self
+            .overhead_tasks_by_person
+            .get(&person.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        // Holidays take precedence: a holiday (or the holiday part of a half-day holiday) is
+        // never also deducted as an absence
+        let holiday_fractions =
+            holiday_fractions_by_date(holidays, start, end, &working_days_set, hours_per_day)?;
+
+        // Absence days are derived from the calendar: clipped to the range, working days only,
+        // overlapping absences merged per date. Partial-day absences count as a fraction of a day.
+        let absence_fractions =
+            absence_fractions_by_date(absences, start, end, &working_days_set, hours_per_day)?;
+
+        let total_absence_days =
+            absence_days_outside_holidays(&absence_fractions, &holiday_fractions);
+        let absence_hours = total_absence_days * hours_per_day;
+
+        let total_holiday_days: f64 = holiday_fractions.values().sum();
+        let holiday_hours = total_holiday_days * hours_per_day;
+
+        // Calculate overhead hours from all assigned jobs' overhead tasks
+        // Split into required (is_optional = false) and optional (is_optional = true)
+        // For optional tasks, we apply the per-task weight and track both raw and weighted hours
+        let mut overhead_hours = 0.0;
+        let mut optional_overhead_hours = 0.0; // Raw optional hours (unweighted, for display)
+        let mut weighted_optional_overhead = 0.0; // Weighted optional hours (for calculation)
         for task in overhead_tasks {
             let task_hours = if task.effort_period == "weekly" {
                 task.effort_hours * total_weeks
             } else if task.effort_period == "daily" {
-                task.effort_hours * working_days
+                task.effort_hours * working_days as f64
             } else {
                 0.0
             };
@@ -318,29 +959,33 @@ pub async fn calculate_person_available_hours(
                 overhead_hours += task_hours;
             }
         }
-    }
 
-    // Calculate available working days and hours after absences, holidays, and overhead
-    // Note: We use pre-calculated weighted_optional_overhead instead of applying a global weight
-    let available_hours =
-        (base_hours - absence_hours - holiday_hours - overhead_hours - weighted_optional_overhead)
+        // Calculate available working days and hours after absences, holidays, and overhead
+        // Note: We use pre-calculated weighted_optional_overhead instead of applying a global weight
+        let available_hours = (base_hours
+            - absence_hours
+            - holiday_hours
+            - overhead_hours
+            - weighted_optional_overhead)
             .max(0.0);
 
-    debug!(
-        "Person {} available hours: {} (base: {}, working days: {}, absence days: {}, absence hours: {}, holiday days: {}, holiday hours: {}, overhead hours: {}, optional overhead hours: {} (weighted: {}), hours/day: {})",
-        person.name, available_hours, base_hours, working_days, total_absence_days, absence_hours, total_holiday_days, holiday_hours, overhead_hours, optional_overhead_hours, weighted_optional_overhead, hours_per_day
-    );
+        debug!(
+            "Person {} available hours: {} (base: {}, working days: {}, absence days: {}, absence hours: {}, holiday days: {}, holiday hours: {}, overhead hours: {}, optional overhead hours: {} (weighted: {}), hours/day: {})",
+            person.name, available_hours, base_hours, working_days, total_absence_days, absence_hours, total_holiday_days, holiday_hours, overhead_hours, optional_overhead_hours, weighted_optional_overhead, hours_per_day
+        );
 
-    Ok(PersonAvailableHoursBreakdown {
-        available_hours,
-        base_hours,
-        absence_days: total_absence_days,
-        absence_hours,
-        holiday_days: total_holiday_days,
-        holiday_hours,
-        overhead_hours,
-        optional_overhead_hours,
-    })
+        Ok(PersonAvailableHoursBreakdown {
+            available_hours,
+            base_hours,
+            working_days,
+            absence_days: total_absence_days,
+            absence_hours,
+            holiday_days: total_holiday_days,
+            holiday_hours,
+            overhead_hours,
+            optional_overhead_hours,
+        })
+    }
 }
 
 /// Calculate effective hours for an assignment
@@ -382,106 +1027,874 @@ pub fn calculate_available_hours(
         .max(0.0)
 }
 
-/// Proportional optimization algorithm
-pub async fn optimize_assignments_proportional(
-    planning_period_id: i64,
-    pool: &DbPool,
-) -> Result<OptimizationResult, String> {
-    info!(
-        "Starting optimization for planning period {}",
-        planning_period_id
-    );
+/// Available hours and day range of an assignment's date window within the planning period
+#[derive(Debug, Clone)]
+struct AssignmentWindow {
+    available_hours: f64,
+    first_day: usize, // Offset of the first window day from the period start
+    last_day: usize,  // Offset one past the last window day
+}
 
-    // Load planning period
-    let planning_period =
-        sqlx::query_as::<_, PlanningPeriod>("SELECT * FROM planning_periods WHERE id = ?")
-            .bind(planning_period_id)
-            .fetch_one(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch planning period: {}", e))?;
-
-    // Load all assignments for this planning period
-    let assignments =
-        sqlx::query_as::<_, Assignment>("SELECT * FROM assignments WHERE planning_period_id = ?")
-            .bind(planning_period_id)
-            .fetch_all(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch assignments: {}", e))?;
+/// Planned utilization cap of a person, 100% when none is set
+///
+/// People loaded for a planning period already carry the app-wide default.
+pub fn max_utilization(person: &Person) -> f64 {
+    person
+        .max_utilization_percentage
+        .map_or(100.0, |pct| pct.clamp(0.0, 100.0))
+}
 
-    if assignments.is_empty() {
-        return Ok(OptimizationResult {
-            success: true,
-            calculations: vec![],
-            infeasible_projects: vec![],
-            warnings: vec!["No assignments found for this planning period".to_string()],
-        });
-    }
+/// Whether a utilization is above a cap (with tolerance for floating-point noise)
+pub fn is_over_utilization(utilization_percentage: f64, cap_percentage: f64) -> bool {
+    utilization_percentage > cap_percentage + 0.05
+}
 
-    // Load all people
-    let people = sqlx::query_as::<_, Person>("SELECT * FROM people")
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch people: {}", e))?;
+/// Remaining allocation percentage of a person for every day of the planning period
+#[derive(Debug)]
+struct PersonState {
+    remaining_percentage: Vec<f64>, // Starts at the person's cap per day, decreases as we allocate
+}
 
-    let people_map: HashMap<i64, Person> = people.into_iter().map(|p| (p.id, p)).collect();
+impl PersonState {
+    fn new(period_days: usize, max_utilization: f64) -> Self {
+        Self {
+            remaining_percentage: vec![max_utilization; period_days],
+        }
+    }
 
-    // Calculate available hours for each person
-    let mut person_available_hours: HashMap<i64, f64> = HashMap::new();
-    for (person_id, person) in &people_map {
-        let breakdown = calculate_person_available_hours(person, &planning_period, pool).await?;
-        person_available_hours.insert(*person_id, breakdown.available_hours);
+    /// Percentage still free on every day of the window
+    fn remaining_in(&self, window: &AssignmentWindow) -> f64 {
+        self.remaining_percentage
+            .get(window.first_day..window.last_day)
+            .and_then(|days| days.iter().copied().reduce(f64::min))
+            .unwrap_or(0.0)
     }
 
-    // Load project requirements
-    let requirements = sqlx::query_as::<_, ProjectRequirement>(
-        "SELECT * FROM project_requirements WHERE planning_period_id = ?",
-    )
-    .bind(planning_period_id)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| format!("Failed to fetch project requirements: {}", e))?;
+    /// Consume a percentage of the person's time on every day of the window
+    fn allocate(&mut self, window: &AssignmentWindow, percentage: f64) {
+        if let Some(days) = self
+            .remaining_percentage
+            .get_mut(window.first_day..window.last_day)
+        {
+            for remaining in days {
+                *remaining = (*remaining - percentage).max(0.0);
+            }
+        }
+    }
+}
 
-    let requirements_map: HashMap<i64, ProjectRequirement> = requirements
-        .into_iter()
-        .map(|r| (r.project_id, r))
-        .collect();
+/// Allocation percentage an assignment is pinned at, if any
+fn pinned_allocation(assignment: &Assignment) -> Option<f64> {
+    if assignment.is_pinned {
+        assignment
+            .pinned_allocation_percentage
+            .map(|pct| pct.clamp(0.0, 100.0))
+    } else {
+        None
+    }
+}
+
+/// Minimum and maximum allocation percentage of an assignment, clamped to 0-100%
+fn allocation_bounds(assignment: &Assignment) -> (f64, f64) {
+    let min_pct = assignment
+        .min_allocation_percentage
+        .map_or(0.0, |pct| pct.clamp(0.0, 100.0));
+    let max_pct = assignment
+        .max_allocation_percentage
+        .map_or(100.0, |pct| pct.clamp(0.0, 100.0));
+    (min_pct, max_pct)
+}
+
+/// Starting point shared by the optimization algorithms
+///
+/// Holds the available hours of every assignment window and each person's capacity left
+/// after the pinned allocations.
+struct OptimizationSetup<'a> {
+    assignment_windows: HashMap<i64, AssignmentWindow>,
+    person_states: HashMap<i64, PersonState>,
+    requirements_map: HashMap<i64, &'a ProjectRequirement>,
+    assignments_by_project: HashMap<i64, Vec<&'a Assignment>>,
+    pinned_calculations: Vec<AssignmentCalculation>,
+    pinned_effective_by_project: HashMap<i64, f64>,
+    minimum_calculations: Vec<AssignmentCalculation>, // Minimums reserved for free assignments
+    minimum_effective_by_project: HashMap<i64, f64>,
+    warnings: Vec<String>,
+}
+
+impl OptimizationSetup<'_> {
+    /// Effective hours the pinned assignments contribute to a project
+    fn pinned_effective(&self, project_id: i64) -> f64 {
+        self.pinned_effective_by_project
+            .get(&project_id)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Effective hours a project has before any distribution: pins and reserved minimums
+    fn reserved_effective(&self, project_id: i64) -> f64 {
+        self.pinned_effective(project_id)
+            + self
+                .minimum_effective_by_project
+                .get(&project_id)
+                .copied()
+                .unwrap_or(0.0)
+    }
+
+    /// Projects that have a requirement, by ascending ID
+    fn required_project_ids(&self) -> Vec<i64> {
+        let mut project_ids: Vec<i64> = self
+            .assignments_by_project
+            .keys()
+            .copied()
+            .filter(|project_id| self.requirements_map.contains_key(project_id))
+            .collect();
+        project_ids.sort_unstable();
+        project_ids
+    }
+
+    /// Assignments the optimizer may allocate: known people, not pinned
+    fn free_assignments(&self, project_id: i64) -> Vec<(&Assignment, &AssignmentWindow)> {
+        self.assignments_by_project
+            .get(&project_id)
+            .into_iter()
+            .flatten()
+            .filter(|assignment| pinned_allocation(assignment).is_none())
+            .filter_map(|assignment| {
+                self.assignment_windows
+                    .get(&assignment.id)
+                    .map(|window| (*assignment, window))
+            })
+            .collect()
+    }
+}
+
+/// Calculate assignment windows, reserve pinned and minimum allocations and collect warnings
+fn prepare_optimization<'a>(
+    data: &PeriodCapacityData,
+    assignments: &'a [Assignment],
+    requirements: &'a [ProjectRequirement],
+) -> Result<OptimizationSetup<'a>, String> {
+    let period_start = data.period_start;
+    let period_days = ((data.period_end - period_start).num_days() + 1).max(0) as usize;
+    let mut warnings = Vec::new();
+
+    // Calculate available hours inside each assignment's date window
+    let mut assignment_windows: HashMap<i64, AssignmentWindow> = HashMap::new();
+    for assignment in assignments {
+        let person = match data.person(assignment.person_id) {
+            Some(person) => person,
+            None => {
+                warnings.push(format!(
+                    "Assignment ID {} references unknown person ID {}",
+                    assignment.id, assignment.person_id
+                ));
+                continue;
+            }
+        };
+        let (window_start, window_end) = assignment_window(assignment, &data.planning_period)?;
+        let breakdown = data.breakdown_in_range(person, window_start, window_end)?;
+
+        assignment_windows.insert(
+            assignment.id,
+            AssignmentWindow {
+                available_hours: breakdown.available_hours,
+                first_day: (window_start - period_start).num_days().max(0) as usize,
+                last_day: ((window_end - period_start).num_days() + 1).max(0) as usize,
+            },
+        );
+    }
+
+    let requirements_map: HashMap<i64, &ProjectRequirement> =
+        requirements.iter().map(|r| (r.project_id, r)).collect();
 
     // Group assignments by project
     let mut assignments_by_project: HashMap<i64, Vec<&Assignment>> = HashMap::new();
-    for assignment in &assignments {
+    for assignment in assignments {
         assignments_by_project
             .entry(assignment.project_id)
             .or_default()
             .push(assignment);
     }
 
-    let mut calculations = Vec::new();
-    let mut infeasible_projects = Vec::new();
-    let mut warnings = Vec::new();
+    let mut unrequired_projects: Vec<i64> = assignments_by_project
+        .keys()
+        .copied()
+        .filter(|project_id| !requirements_map.contains_key(project_id))
+        .collect();
+    unrequired_projects.sort_unstable();
+    for project_id in unrequired_projects {
+        warnings.push(format!(
+            "Project ID {} has assignments but no requirement defined",
+            project_id
+        ));
+    }
 
-    // ========================================================================
-    // PASS 1: Calculate per-person remaining capacity
-    // ========================================================================
+    // Remaining capacity is tracked per day of the period, so assignments with
+    // disjoint date windows don't compete for the same share of a person's time
+    let mut person_states: HashMap<i64, PersonState> = HashMap::new();
+    for person in &data.people {
+        person_states.insert(
+            person.id,
+            PersonState::new(period_days, max_utilization(person)),
+        );
+    }
 
-    #[derive(Debug)]
-    struct PersonState {
-        available_hours: f64,
-        remaining_percentage: f64, // Starts at 100.0, decreases as we allocate
+    // Reserve pinned allocations before distributing the remaining capacity
+    let mut pinned_calculations = Vec::new();
+    let mut pinned_effective_by_project: HashMap<i64, f64> = HashMap::new();
+    let mut pinned_by_person: HashMap<i64, Vec<f64>> = HashMap::new(); // Pinned percentage per day
+    for assignment in assignments {
+        let Some(pinned_pct) = pinned_allocation(assignment) else {
+            continue;
+        };
+        let (Some(state), Some(window)) = (
+            person_states.get_mut(&assignment.person_id),
+            assignment_windows.get(&assignment.id),
+        ) else {
+            continue;
+        };
+
+        state.allocate(window, pinned_pct);
+        if let Some(days) = pinned_by_person
+            .entry(assignment.person_id)
+            .or_insert_with(|| vec![0.0; period_days])
+            .get_mut(window.first_day..window.last_day)
+        {
+            for pinned in days {
+                *pinned += pinned_pct;
+            }
+        }
+
+        let effective_hours = calculate_assignment_effective_hours(
+            window.available_hours,
+            pinned_pct,
+            assignment.productivity_factor,
+        );
+        *pinned_effective_by_project
+            .entry(assignment.project_id)
+            .or_default() += effective_hours;
+
+        pinned_calculations.push(AssignmentCalculation {
+            assignment_id: assignment.id,
+            calculated_allocation_percentage: pinned_pct,
+            calculated_effective_hours: effective_hours,
+            calculated_start_date: None,
+        });
     }
 
-    let mut person_states: HashMap<i64, PersonState> = HashMap::new();
+    for person in &data.people {
+        let max_pinned = pinned_by_person
+            .get(&person.id)
+            .and_then(|days| days.iter().copied().reduce(f64::max))
+            .unwrap_or(0.0);
+        let cap = max_utilization(person);
+        if is_over_utilization(max_pinned, 100.0) {
+            warnings.push(format!(
+                "Pinned allocations of {} add up to {:.1}%, more than 100%",
+                person.name, max_pinned
+            ));
+        } else if is_over_utilization(max_pinned, cap) {
+            warnings.push(format!(
+                "Pinned allocations of {} add up to {:.1}%, above their utilization cap of {:.1}%",
+                person.name, max_pinned, cap
+            ));
+        }
+    }
 
-    // Initialize person states
-    for (person_id, available_hours) in &person_available_hours {
-        person_states.insert(
-            *person_id,
-            PersonState {
-                available_hours: *available_hours,
-                remaining_percentage: 100.0,
+    // Reserve the minimum allocations of free assignments from what the pins leave,
+    // highest priority project first. Minimums that don't fit are reported afterwards.
+    // Projects without a requirement are skipped, so their minimums reserve nothing.
+    let mut minimum_assignments: Vec<&Assignment> = assignments
+        .iter()
+        .filter(|assignment| {
+            pinned_allocation(assignment).is_none()
+                && allocation_bounds(assignment).0 > 0.0
+                && requirements_map.contains_key(&assignment.project_id)
+        })
+        .collect();
+    minimum_assignments.sort_by_key(|assignment| {
+        (
+            std::cmp::Reverse(
+                requirements_map
+                    .get(&assignment.project_id)
+                    .map(|requirement| requirement.priority),
+            ),
+            assignment.id,
+        )
+    });
+
+    let mut minimum_calculations = Vec::new();
+    let mut minimum_effective_by_project: HashMap<i64, f64> = HashMap::new();
+    for assignment in minimum_assignments {
+        let (Some(state), Some(window)) = (
+            person_states.get_mut(&assignment.person_id),
+            assignment_windows.get(&assignment.id),
+        ) else {
+            continue;
+        };
+
+        let (min_pct, max_pct) = allocation_bounds(assignment);
+        let reserved_pct = min_pct.min(max_pct).min(state.remaining_in(window));
+        state.allocate(window, reserved_pct);
+
+        let effective_hours = calculate_assignment_effective_hours(
+            window.available_hours,
+            reserved_pct,
+            assignment.productivity_factor,
+        );
+        *minimum_effective_by_project
+            .entry(assignment.project_id)
+            .or_default() += effective_hours;
+
+        minimum_calculations.push(AssignmentCalculation {
+            assignment_id: assignment.id,
+            calculated_allocation_percentage: reserved_pct,
+            calculated_effective_hours: effective_hours,
+            calculated_start_date: None,
+        });
+    }
+
+    Ok(OptimizationSetup {
+        assignment_windows,
+        person_states,
+        requirements_map,
+        assignments_by_project,
+        pinned_calculations,
+        pinned_effective_by_project,
+        minimum_calculations,
+        minimum_effective_by_project,
+        warnings,
+    })
+}
+
+/// Shortfall of a project that ends up with fewer effective hours than required
+fn project_shortfall(
+    requirement: &ProjectRequirement,
+    total_effective_hours: f64,
+    project_names: &HashMap<i64, String>,
+) -> Option<ProjectShortfall> {
+    // Ignore rounding noise, e.g. from the linear solver
+    if total_effective_hours >= requirement.required_hours - 1e-6 {
+        return None;
+    }
+
+    let shortfall = requirement.required_hours - total_effective_hours;
+    let project_name = project_names
+        .get(&requirement.project_id)
+        .cloned()
+        .unwrap_or_else(|| format!("Project {}", requirement.project_id));
+
+    Some(ProjectShortfall {
+        project_id: requirement.project_id,
+        project_name,
+        required_hours: requirement.required_hours,
+        available_effective_hours: total_effective_hours,
+        shortfall,
+        shortfall_percentage: (shortfall / requirement.required_hours) * 100.0,
+        explanation: vec![],
+    })
+}
+
+/// Explain each shortfall with what happened to the capacity of the project's people
+///
+/// For every assignment on a short project this lists the person's deductions inside the
+/// assignment window and the hours of that window their higher- or equal-priority projects
+/// took, including pinned ones.
+fn explain_shortfalls(
+    data: &PeriodCapacityData,
+    setup: &OptimizationSetup,
+    calculations: &[AssignmentCalculation],
+    project_names: &HashMap<i64, String>,
+    shortfalls: &mut [ProjectShortfall],
+) -> Result<(), String> {
+    let allocated: HashMap<i64, &AssignmentCalculation> = calculations
+        .iter()
+        .chain(&setup.pinned_calculations)
+        .map(|calc| (calc.assignment_id, calc))
+        .collect();
+
+    let mut assignments_by_person: HashMap<i64, Vec<&Assignment>> = HashMap::new();
+    for assignment in setup.assignments_by_project.values().flatten() {
+        assignments_by_person
+            .entry(assignment.person_id)
+            .or_default()
+            .push(assignment);
+    }
+
+    for shortfall in shortfalls {
+        let priority = setup.requirements_map[&shortfall.project_id].priority;
+        let mut project_assignments = setup.assignments_by_project[&shortfall.project_id].clone();
+        project_assignments.sort_by_key(|assignment| assignment.id);
+
+        for assignment in project_assignments {
+            let Some(person) = data.person(assignment.person_id) else {
+                continue;
+            };
+            let breakdown = data.assignment_breakdown(person, assignment)?;
+            let (window_start, window_end) = assignment_window(assignment, &data.planning_period)?;
+
+            // Other projects of the person that were staffed first or alongside
+            let mut competing: BTreeMap<(std::cmp::Reverse<i64>, i64), f64> = BTreeMap::new();
+            for other in &assignments_by_person[&assignment.person_id] {
+                let Some(other_priority) = setup
+                    .requirements_map
+                    .get(&other.project_id)
+                    .map(|requirement| requirement.priority)
+                else {
+                    continue;
+                };
+                let Some(calc) = allocated.get(&other.id) else {
+                    continue;
+                };
+                if other.project_id == shortfall.project_id
+                    || other_priority < priority
+                    || calc.calculated_allocation_percentage <= 0.0
+                {
+                    continue;
+                }
+
+                let Some(overlap) =
+                    data.assignment_breakdown_in_range(person, other, window_start, window_end)?
+                else {
+                    continue;
+                };
+                *competing
+                    .entry((std::cmp::Reverse(other_priority), other.project_id))
+                    .or_default() +=
+                    overlap.available_hours * calc.calculated_allocation_percentage / 100.0;
+            }
+
+            let calc = allocated.get(&assignment.id);
+            shortfall.explanation.push(ShortfallPersonExplanation {
+                assignment_id: assignment.id,
+                person_id: person.id,
+                person_name: person.name.clone(),
+                available_hours: breakdown.available_hours,
+                absence_hours: breakdown.absence_hours,
+                holiday_hours: breakdown.holiday_hours,
+                overhead_hours: breakdown.overhead_hours,
+                optional_overhead_hours: breakdown.optional_overhead_hours,
+                allocation_percentage: calc.map_or(0.0, |c| c.calculated_allocation_percentage),
+                effective_hours: calc.map_or(0.0, |c| c.calculated_effective_hours),
+                competing_projects: competing
+                    .into_iter()
+                    .map(
+                        |((other_priority, project_id), allocated_hours)| CompetingProjectHours {
+                            project_id,
+                            project_name: project_names
+                                .get(&project_id)
+                                .cloned()
+                                .unwrap_or_else(|| format!("Project {}", project_id)),
+                            priority: other_priority.0,
+                            allocated_hours,
+                        },
+                    )
+                    .collect(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Assignments an optimization left without a calculation, by ascending ID
+fn skipped_assignment_ids(
+    assignments: &[Assignment],
+    calculations: &[AssignmentCalculation],
+    pinned_calculations: &[AssignmentCalculation],
+) -> Vec<i64> {
+    let calculated: BTreeSet<i64> = calculations
+        .iter()
+        .chain(pinned_calculations)
+        .map(|calc| calc.assignment_id)
+        .collect();
+    let mut skipped: Vec<i64> = assignments
+        .iter()
+        .map(|assignment| assignment.id)
+        .filter(|id| !calculated.contains(id))
+        .collect();
+    skipped.sort_unstable();
+    skipped
+}
+
+/// Assignments whose final allocation is outside their bounds, by ascending ID
+///
+/// Each one is also reported as a warning.
+fn unsatisfied_constraints(
+    assignments: &[Assignment],
+    calculations: &[AssignmentCalculation],
+    pinned_calculations: &[AssignmentCalculation],
+    warnings: &mut Vec<String>,
+) -> Vec<UnsatisfiedConstraint> {
+    let allocated: HashMap<i64, f64> = calculations
+        .iter()
+        .chain(pinned_calculations)
+        .map(|calc| (calc.assignment_id, calc.calculated_allocation_percentage))
+        .collect();
+
+    let mut sorted: Vec<&Assignment> = assignments.iter().collect();
+    sorted.sort_by_key(|assignment| assignment.id);
+
+    let mut unsatisfied = Vec::new();
+    for assignment in sorted {
+        let Some(&allocation_pct) = allocated.get(&assignment.id) else {
+            continue;
+        };
+        let (min_pct, max_pct) = allocation_bounds(assignment);
+
+        // Use tolerance for floating-point comparison
+        let violated = if allocation_pct < min_pct - 1e-6 {
+            Some((AllocationBound::Minimum, min_pct))
+        } else if allocation_pct > max_pct + 1e-6 {
+            Some((AllocationBound::Maximum, max_pct))
+        } else {
+            None
+        };
+        let Some((bound, bound_pct)) = violated else {
+            continue;
+        };
+
+        let relation = match bound {
+            AllocationBound::Minimum => "below its minimum",
+            AllocationBound::Maximum => "above its maximum",
+        };
+        warnings.push(format!(
+            "Assignment ID {} is allocated {:.1}%, {} of {:.1}%",
+            assignment.id, allocation_pct, relation, bound_pct
+        ));
+        unsatisfied.push(UnsatisfiedConstraint {
+            assignment_id: assignment.id,
+            person_id: assignment.person_id,
+            project_id: assignment.project_id,
+            bound,
+            bound_percentage: bound_pct,
+            allocation_percentage: allocation_pct,
+        });
+    }
+    unsatisfied
+}
+
+/// Result for a planning period without assignments
+fn empty_optimization_result(strategy: &str, options: OptimizationOptions) -> OptimizationResult {
+    OptimizationResult {
+        success: true,
+        calculations: vec![],
+        pinned_calculations: vec![],
+        infeasible_projects: vec![],
+        warnings: vec!["No assignments found for this planning period".to_string()],
+        skipped_assignment_ids: vec![],
+        strategy: strategy.to_string(),
+        options,
+        unsatisfied_constraints: vec![],
+        blocked_projects: vec![],
+    }
+}
+
+/// How the priority-ordered algorithms share capacity between the projects of a tier
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PriorityAllocation {
+    /// Projects compete for shared people in proportion to the hours they still need
+    Proportional,
+    /// Projects in turn, most productive assignments first
+    PriorityFirst,
+    /// Each person's capacity is first split evenly between their projects of the tier
+    EvenSpread,
+}
+
+/// Capacity a free assignment has left for one project
+#[derive(Debug)]
+struct AssignmentCapacity {
+    assignment_id: i64,
+    person_id: i64,
+    window: AssignmentWindow,
+    remaining_capacity_pct: f64,
+    productivity_factor: f64,
+    max_contribution_hours: f64,
+}
+
+/// Calculations of the free assignments, merged when an assignment is allocated twice
+#[derive(Default)]
+struct Allocations {
+    calculations: Vec<AssignmentCalculation>,
+    index: HashMap<i64, usize>,
+}
+
+impl Allocations {
+    /// Start from the minimums reserved for the free assignments
+    fn with_minimums(setup: &OptimizationSetup) -> Self {
+        let mut allocations = Self::default();
+        for calc in &setup.minimum_calculations {
+            allocations.add(
+                calc.assignment_id,
+                calc.calculated_allocation_percentage,
+                calc.calculated_effective_hours,
+            );
+        }
+        allocations
+    }
+
+    /// Percentage allocated to an assignment so far
+    fn allocated(&self, assignment_id: i64) -> f64 {
+        self.index.get(&assignment_id).map_or(0.0, |&i| {
+            self.calculations[i].calculated_allocation_percentage
+        })
+    }
+
+    fn add(&mut self, assignment_id: i64, allocation_pct: f64, effective_hours: f64) {
+        match self.index.get(&assignment_id) {
+            Some(&i) => {
+                let calc = &mut self.calculations[i];
+                calc.calculated_allocation_percentage += allocation_pct;
+                calc.calculated_effective_hours += effective_hours;
+            }
+            None => {
+                self.index.insert(assignment_id, self.calculations.len());
+                self.calculations.push(AssignmentCalculation {
+                    assignment_id,
+                    calculated_allocation_percentage: allocation_pct,
+                    calculated_effective_hours: effective_hours,
+                    calculated_start_date: None,
+                });
+            }
+        }
+    }
+}
+
+/// Allocate up to `open_hours` of a project to its free assignments
+///
+/// `share_limits` caps the percentage an assignment may take in this round (by assignment
+/// ID); assignments without a limit may use all of their person's remaining capacity, up
+/// to their maximum allocation. Returns the effective hours allocated.
+fn allocate_project(
+    setup: &mut OptimizationSetup,
+    project_id: i64,
+    open_hours: f64,
+    most_productive_first: bool,
+    share_limits: &HashMap<i64, f64>,
+    allocations: &mut Allocations,
+) -> f64 {
+    // Calculate available capacity from all assignments for this project
+    let mut assignment_capacities = Vec::new();
+    let mut total_available_hours = 0.0;
+
+    for (assignment, window) in setup.free_assignments(project_id) {
+        let mut remaining_pct = setup.person_states[&assignment.person_id].remaining_in(window);
+        if let Some(limit) = share_limits.get(&assignment.id) {
+            remaining_pct = remaining_pct.min(*limit);
+        }
+        let (_, max_pct) = allocation_bounds(assignment);
+        remaining_pct =
+            remaining_pct.min((max_pct - allocations.allocated(assignment.id)).max(0.0));
+
+        // Max this person can contribute to THIS project within the assignment window
+        let max_hours =
+            window.available_hours * (remaining_pct / 100.0) * assignment.productivity_factor;
+
+        total_available_hours += max_hours;
+        assignment_capacities.push(AssignmentCapacity {
+            assignment_id: assignment.id,
+            person_id: assignment.person_id,
+            window: window.clone(),
+            remaining_capacity_pct: remaining_pct,
+            productivity_factor: assignment.productivity_factor,
+            max_contribution_hours: max_hours,
+        });
+    }
+
+    debug!("  Total available capacity: {:.1}h", total_available_hours);
+
+    if total_available_hours <= 0.0 {
+        return 0.0;
+    }
+
+    // Distribute capped by available capacity
+    let hours_to_distribute = open_hours.max(0.0).min(total_available_hours);
+    let mut hours_left = hours_to_distribute;
+    let mut allocated_effective = 0.0;
+
+    if most_productive_first {
+        // Stable sort keeps assignment order between equally productive people
+        assignment_capacities
+            .sort_by(|a, b| b.productivity_factor.total_cmp(&a.productivity_factor));
+    }
+
+    for cap in assignment_capacities {
+        let allocated_hours = if most_productive_first {
+            cap.max_contribution_hours.min(hours_left)
+        } else {
+            // Proportional share based on max contribution
+            cap.max_contribution_hours / total_available_hours * hours_to_distribute
+        };
+
+        // Convert back to allocation percentage of the window's available hours
+        let allocation_pct = if cap.window.available_hours > 0.0 && cap.productivity_factor > 0.0 {
+            ((allocated_hours / cap.productivity_factor) / cap.window.available_hours * 100.0)
+                .min(cap.remaining_capacity_pct) // Cap at remaining capacity
+        } else {
+            0.0
+        };
+
+        let effective_hours = calculate_assignment_effective_hours(
+            cap.window.available_hours,
+            allocation_pct,
+            cap.productivity_factor,
+        );
+
+        hours_left = (hours_left - effective_hours).max(0.0);
+        allocated_effective += effective_hours;
+
+        // Update person's remaining capacity for the days of the window
+        let state = setup.person_states.get_mut(&cap.person_id).unwrap();
+        state.allocate(&cap.window, allocation_pct);
+
+        allocations.add(cap.assignment_id, allocation_pct, effective_hours);
+
+        debug!(
+            "    Assignment {}: {:.1}% allocation, {:.1}h effective, {:.1}% remaining capacity",
+            cap.assignment_id,
+            allocation_pct,
+            effective_hours,
+            state.remaining_in(&cap.window)
+        );
+    }
+
+    allocated_effective
+}
+
+/// Per-assignment limits that split each person's remaining capacity between their free
+/// assignments on the given projects, in proportion to the weight of each project
+///
+/// Projects weighted zero and assignments already at their maximum get no share.
+fn weighted_share_limits(
+    setup: &OptimizationSetup,
+    project_ids: &[i64],
+    weight: impl Fn(i64) -> f64,
+    allocations: &Allocations,
+) -> HashMap<i64, f64> {
+    let tier_assignments: Vec<(&Assignment, &AssignmentWindow, f64)> = project_ids
+        .iter()
+        .map(|project_id| (*project_id, weight(*project_id)))
+        .filter(|(_, project_weight)| *project_weight > 0.0)
+        .flat_map(|(project_id, project_weight)| {
+            setup
+                .free_assignments(project_id)
+                .into_iter()
+                .map(move |(assignment, window)| (assignment, window, project_weight))
+        })
+        .filter(|(assignment, _, _)| {
+            allocation_bounds(assignment).1 - allocations.allocated(assignment.id) > 1e-6
+        })
+        .collect();
+
+    let mut weight_per_person: HashMap<i64, f64> = HashMap::new();
+    for (assignment, _, project_weight) in &tier_assignments {
+        *weight_per_person.entry(assignment.person_id).or_default() += project_weight;
+    }
+
+    tier_assignments
+        .iter()
+        .map(|(assignment, window, project_weight)| {
+            let remaining_pct = setup.person_states[&assignment.person_id].remaining_in(window);
+            let share = project_weight / weight_per_person[&assignment.person_id];
+            (assignment.id, remaining_pct * share)
+        })
+        .collect()
+}
+
+/// Upper bound on the sharing rounds of a tier; each round usually settles most of the rest
+const MAX_SHARING_ROUNDS: usize = 100;
+
+/// Let the projects of a tier compete for their shared people at the same time
+///
+/// Every round splits each person's remaining capacity between the projects that still
+/// need hours, in proportion to those hours. Capacity a project can't use goes back into
+/// the pool for the next round, until no project gains anything. The outcome doesn't
+/// depend on the order of the projects.
+fn share_tier_by_demand(
+    setup: &mut OptimizationSetup,
+    project_ids: &[i64],
+    staffed: &mut HashMap<i64, f64>,
+    allocations: &mut Allocations,
+) {
+    for round in 0..MAX_SHARING_ROUNDS {
+        let open_hours: HashMap<i64, f64> = project_ids
+            .iter()
+            .map(|project_id| {
+                let required = setup.requirements_map[project_id].required_hours;
+                (*project_id, (required - staffed[project_id]).max(0.0))
+            })
+            .collect();
+        let share_limits = weighted_share_limits(
+            setup,
+            project_ids,
+            |project_id| {
+                let open = open_hours[&project_id];
+                if open > 1e-6 {
+                    open
+                } else {
+                    0.0
+                }
             },
+            allocations,
+        );
+
+        let mut added_hours = 0.0;
+        for project_id in project_ids {
+            if open_hours[project_id] <= 1e-6 {
+                continue;
+            }
+            let added = allocate_project(
+                setup,
+                *project_id,
+                open_hours[project_id],
+                false,
+                &share_limits,
+                allocations,
+            );
+            *staffed.get_mut(project_id).unwrap() += added;
+            added_hours += added;
+        }
+
+        debug!(
+            "  Sharing round {}: {:.1}h allocated",
+            round + 1,
+            added_hours
         );
+        if added_hours <= 1e-6 {
+            break;
+        }
+    }
+}
+
+/// Priority-ordered optimization shared by the heuristic strategies
+///
+/// Pinned assignments keep their allocation and minimum allocations are reserved up front;
+/// only the person's remaining capacity is distributed, highest priority first. Each
+/// assignment's allocation percentage is a share of the person's available hours inside the
+/// assignment's date window. Assignments of people missing from the period data are reported
+/// as warnings.
+fn optimize_by_priority(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+    strategy: &str,
+    allocation: PriorityAllocation,
+) -> Result<OptimizationResult, String> {
+    if assignments.is_empty() {
+        return Ok(empty_optimization_result(
+            strategy,
+            OptimizationOptions::default(),
+        ));
     }
 
+    // ========================================================================
+    // PASS 1: Calculate per-person remaining capacity
+    // ========================================================================
+
+    let mut setup = prepare_optimization(data, assignments, requirements)?;
+    let mut allocations = Allocations::with_minimums(&setup);
+    let mut infeasible_projects = Vec::new();
+
     debug!("Pass 1 complete: Initialized person capacity states");
 
     // ========================================================================
@@ -489,26 +1902,17 @@ pub async fn optimize_assignments_proportional(
     // ========================================================================
 
     // Group assignments by project and priority
-    let mut projects_by_priority: Vec<(i64, i64)> = Vec::new(); // (priority, project_id)
-
-    for project_id in assignments_by_project.keys() {
-        let requirement = match requirements_map.get(project_id) {
-            Some(req) => req,
-            None => {
-                warnings.push(format!(
-                    "Project ID {} has assignments but no requirement defined",
-                    project_id
-                ));
-                continue;
-            }
-        };
-        projects_by_priority.push((requirement.priority, *project_id));
-    }
+    let mut projects_by_priority: Vec<(i64, i64)> = setup
+        .required_project_ids()
+        .into_iter()
+        .map(|project_id| (setup.requirements_map[&project_id].priority, project_id))
+        .collect(); // (priority, project_id)
 
     // Sort by priority DESC (blocker=30, high=20, medium=10, low=0)
+    #[allow(clippy::unnecessary_sort_by)]
     projects_by_priority.sort_by(|a, b| b.0.cmp(&a.0));
 
-    // Group projects by priority level for proportional allocation within same priority
+    // Group projects by priority level
     let mut priority_groups: Vec<Vec<i64>> = Vec::new();
     let mut current_priority: Option<i64> = None;
     let mut current_group = Vec::new();
@@ -527,6 +1931,9 @@ pub async fn optimize_assignments_proportional(
         priority_groups.push(current_group);
     }
 
+    let most_productive_first = allocation == PriorityAllocation::PriorityFirst;
+    let no_limits = HashMap::new();
+
     // Process each priority group
     for priority_group in priority_groups {
         debug!(
@@ -534,160 +1941,578 @@ pub async fn optimize_assignments_proportional(
             priority_group.len()
         );
 
-        // For each project in this priority group, calculate needs
-        for project_id in priority_group {
-            let project_assignments = assignments_by_project.get(&project_id).unwrap();
-            let requirement = requirements_map.get(&project_id).unwrap();
-
-            debug!(
-                "Processing project {} (required: {}h)",
-                project_id, requirement.required_hours
-            );
-
-            // Calculate available capacity from all assignments for this project
-            #[derive(Debug)]
-            struct AssignmentCapacity<'a> {
-                assignment: &'a Assignment,
-                available_hours: f64,
-                remaining_capacity_pct: f64,
-                productivity_factor: f64,
-                max_contribution_hours: f64,
-            }
+        // Pinned assignments and reserved minimums already cover part of each requirement
+        let mut staffed: HashMap<i64, f64> = priority_group
+            .iter()
+            .map(|project_id| (*project_id, setup.reserved_effective(*project_id)))
+            .collect();
 
-            let mut assignment_capacities = Vec::new();
-            let mut total_available_hours = 0.0;
-
-            for assignment in project_assignments {
-                let state = person_states.get(&assignment.person_id).unwrap();
-                let available = state.available_hours;
-                let remaining_pct = state.remaining_percentage;
-
-                // Max this person can contribute to THIS project
-                let max_hours =
-                    available * (remaining_pct / 100.0) * assignment.productivity_factor;
-
-                total_available_hours += max_hours;
-                assignment_capacities.push(AssignmentCapacity {
-                    assignment,
-                    available_hours: available,
-                    remaining_capacity_pct: remaining_pct,
-                    productivity_factor: assignment.productivity_factor,
-                    max_contribution_hours: max_hours,
-                });
+        match allocation {
+            // Proportional: the projects of the tier share their people by demand
+            PriorityAllocation::Proportional => {
+                share_tier_by_demand(&mut setup, &priority_group, &mut staffed, &mut allocations)
             }
-
-            debug!("  Total available capacity: {:.1}h", total_available_hours);
-
-            // Distribute proportionally, capped by available capacity
-            let hours_to_distribute = requirement.required_hours.min(total_available_hours);
-            let mut project_total_effective = 0.0;
-
-            if total_available_hours > 0.0 {
-                for cap in assignment_capacities {
-                    // Proportional share based on max contribution
-                    let proportion = cap.max_contribution_hours / total_available_hours;
-                    let allocated_hours = proportion * hours_to_distribute;
-
-                    // Convert back to allocation percentage
-                    let allocation_pct = if cap.available_hours > 0.0 {
-                        ((allocated_hours / cap.productivity_factor) / cap.available_hours * 100.0)
-                            .min(cap.remaining_capacity_pct) // Cap at remaining capacity
-                    } else {
-                        0.0
-                    };
-
-                    let effective_hours = calculate_assignment_effective_hours(
-                        cap.available_hours,
-                        allocation_pct,
-                        cap.productivity_factor,
+            // Even spread: every project of the tier first gets its even share of each person
+            PriorityAllocation::EvenSpread => {
+                let share_limits =
+                    weighted_share_limits(&setup, &priority_group, |_| 1.0, &allocations);
+                for project_id in &priority_group {
+                    let open_hours =
+                        setup.requirements_map[project_id].required_hours - staffed[project_id];
+                    let added = allocate_project(
+                        &mut setup,
+                        *project_id,
+                        open_hours,
+                        false,
+                        &share_limits,
+                        &mut allocations,
                     );
-
-                    project_total_effective += effective_hours;
-
-                    // Update person's remaining capacity
-                    let state = person_states.get_mut(&cap.assignment.person_id).unwrap();
-                    state.remaining_percentage -= allocation_pct;
-                    state.remaining_percentage = state.remaining_percentage.max(0.0);
-
-                    calculations.push(AssignmentCalculation {
-                        assignment_id: cap.assignment.id,
-                        calculated_allocation_percentage: allocation_pct,
-                        calculated_effective_hours: effective_hours,
-                    });
-
-                    debug!("    Assignment {}: {:.1}% allocation, {:.1}h effective, {:.1}% remaining capacity",
-                           cap.assignment.id, allocation_pct, effective_hours,
-                           state.remaining_percentage);
+                    *staffed.get_mut(project_id).unwrap() += added;
                 }
             }
+            PriorityAllocation::PriorityFirst => {}
+        }
 
-            // Check if project is under-staffed
-            if project_total_effective < requirement.required_hours {
-                let shortfall = requirement.required_hours - project_total_effective;
-                let shortfall_pct = (shortfall / requirement.required_hours) * 100.0;
-
-                let project_name =
-                    sqlx::query_scalar::<_, String>("SELECT name FROM projects WHERE id = ?")
-                        .bind(project_id)
-                        .fetch_one(pool)
-                        .await
-                        .unwrap_or_else(|_| format!("Project {}", project_id));
-
-                infeasible_projects.push(ProjectShortfall {
-                    project_id,
-                    project_name,
-                    required_hours: requirement.required_hours,
-                    available_effective_hours: project_total_effective,
-                    shortfall,
-                    shortfall_percentage: shortfall_pct,
-                });
+        // Projects in turn: the whole allocation for priority first, otherwise whatever the
+        // shared round left (which also records the assignments that got nothing)
+        for project_id in &priority_group {
+            let requirement = setup.requirements_map[project_id];
+
+            debug!(
+                "Processing project {} (required: {}h)",
+                project_id, requirement.required_hours
+            );
 
-                warn!(
-                    "Project {} is under-staffed by {:.1}h ({:.1}%)",
-                    project_id, shortfall, shortfall_pct
+            let open_hours = requirement.required_hours - staffed[project_id];
+            if allocation != PriorityAllocation::EvenSpread || open_hours > 1e-6 {
+                let added = allocate_project(
+                    &mut setup,
+                    *project_id,
+                    open_hours,
+                    most_productive_first,
+                    &no_limits,
+                    &mut allocations,
                 );
+                *staffed.get_mut(project_id).unwrap() += added;
             }
+
+            // Check if project is under-staffed
+            infeasible_projects.extend(project_shortfall(
+                requirement,
+                staffed[project_id],
+                project_names,
+            ));
         }
     }
 
-    // Update database with calculations
-    let now = chrono::Utc::now().to_rfc3339();
-    for calc in &calculations {
-        sqlx::query(
-            "UPDATE assignments 
-             SET calculated_allocation_percentage = ?,
-                 calculated_effective_hours = ?,
-                 last_calculated_at = ?
-             WHERE id = ?",
-        )
-        .bind(calc.calculated_allocation_percentage)
-        .bind(calc.calculated_effective_hours)
-        .bind(&now)
-        .bind(calc.assignment_id)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to update assignment {}: {}", calc.assignment_id, e))?;
-    }
-
-    info!(
-        "Optimization complete: {} calculations, {} infeasible projects, {} warnings",
-        calculations.len(),
-        infeasible_projects.len(),
-        warnings.len()
+    explain_shortfalls(
+        data,
+        &setup,
+        &allocations.calculations,
+        project_names,
+        &mut infeasible_projects,
+    )?;
+    let unsatisfied_constraints = unsatisfied_constraints(
+        assignments,
+        &allocations.calculations,
+        &setup.pinned_calculations,
+        &mut setup.warnings,
     );
 
     Ok(OptimizationResult {
         success: true,
-        calculations,
+        skipped_assignment_ids: skipped_assignment_ids(
+            assignments,
+            &allocations.calculations,
+            &setup.pinned_calculations,
+        ),
+        calculations: allocations.calculations,
+        pinned_calculations: setup.pinned_calculations,
         infeasible_projects,
-        warnings,
+        warnings: setup.warnings,
+        strategy: strategy.to_string(),
+        options: OptimizationOptions::default(),
+        unsatisfied_constraints,
+        blocked_projects: vec![],
     })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::NaiveDate;
+/// Proportional optimization algorithm
+///
+/// Priority tiers are staffed one after another, highest first. Projects of the same
+/// priority compete for their shared people at the same time, each getting a share of a
+/// person in proportion to the hours it still needs, so the result doesn't depend on which
+/// project comes first. A project's hours are split across its people in proportion to
+/// what each can still contribute.
+pub fn optimize_proportional(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+) -> Result<OptimizationResult, String> {
+    optimize_by_priority(
+        data,
+        assignments,
+        requirements,
+        project_names,
+        PROPORTIONAL_STRATEGY,
+        PriorityAllocation::Proportional,
+    )
+}
+
+/// Strict priority-first optimization algorithm
+///
+/// Projects are staffed one after another, highest priority first, drawing on their most
+/// productive people first so urgent work takes as few person-hours as possible.
+pub fn optimize_priority_first(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+) -> Result<OptimizationResult, String> {
+    optimize_by_priority(
+        data,
+        assignments,
+        requirements,
+        project_names,
+        PRIORITY_FIRST_STRATEGY,
+        PriorityAllocation::PriorityFirst,
+    )
+}
+
+/// Even-spread optimization algorithm
+///
+/// Projects of the same priority share their people: each person's remaining capacity is
+/// first split evenly between their projects of the tier, and only the capacity left after
+/// that round goes to projects that are still short, in project order.
+pub fn optimize_even_spread(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+) -> Result<OptimizationResult, String> {
+    optimize_by_priority(
+        data,
+        assignments,
+        requirements,
+        project_names,
+        EVEN_SPREAD_STRATEGY,
+        PriorityAllocation::EvenSpread,
+    )
+}
+
+/// Hash records through their JSON form with 64-bit FNV-1a
+///
+/// Fingerprints are stored with optimization runs, so the hash must not change between
+/// builds, which the standard library's hasher doesn't promise.
+fn hash_records<T: Serialize>(records: &T) -> Result<u64, String> {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    let json =
+        serde_json::to_vec(records).map_err(|e| format!("Failed to serialize records: {}", e))?;
+    Ok(json.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    }))
+}
+
+/// Fingerprint of everything an optimization reads, including the current calculations
+///
+/// A preview can only be applied while the fingerprint of the planning period is unchanged.
+pub fn optimization_fingerprint(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    dependencies: &[ProjectDependency],
+) -> Result<String, String> {
+    let hash = hash_records(&(
+        data.input_fingerprint,
+        assignments,
+        requirements,
+        dependencies,
+    ))?;
+    Ok(format!("{:016x}", hash))
+}
+
+/// Compare an optimization result with the allocations currently stored on the assignments
+///
+/// Stored values of assignments the result skips are cleared when the result is applied.
+pub fn optimization_preview(
+    planning_period_id: i64,
+    input_fingerprint: String,
+    assignments: &[Assignment],
+    project_names: &HashMap<i64, String>,
+    result: OptimizationResult,
+) -> OptimizationPreview {
+    let new_calculations: HashMap<i64, &AssignmentCalculation> = result
+        .calculations
+        .iter()
+        .chain(&result.pinned_calculations)
+        .map(|calc| (calc.assignment_id, calc))
+        .collect();
+
+    let mut assignment_diffs = Vec::new();
+    let mut totals_by_project: BTreeMap<i64, (f64, f64)> = BTreeMap::new(); // (old, new)
+    for assignment in assignments {
+        let new = new_calculations.get(&assignment.id);
+        // Skipped assignments only show up when they have stored values to clear
+        if new.is_some() || assignment.calculated_allocation_percentage.is_some() {
+            assignment_diffs.push(AssignmentDiff {
+                assignment_id: assignment.id,
+                person_id: assignment.person_id,
+                project_id: assignment.project_id,
+                old_allocation_percentage: assignment.calculated_allocation_percentage,
+                new_allocation_percentage: new.map(|calc| calc.calculated_allocation_percentage),
+                old_effective_hours: assignment.calculated_effective_hours,
+                new_effective_hours: new.map(|calc| calc.calculated_effective_hours),
+            });
+        }
+
+        let totals = totals_by_project.entry(assignment.project_id).or_default();
+        totals.0 += assignment.calculated_effective_hours.unwrap_or(0.0);
+        totals.1 += new.map_or(0.0, |calc| calc.calculated_effective_hours);
+    }
+    assignment_diffs.sort_by_key(|diff| diff.assignment_id);
+
+    OptimizationPreview {
+        planning_period_id,
+        input_fingerprint,
+        result,
+        assignment_diffs,
+        project_deltas: project_staffing_deltas(totals_by_project, project_names),
+    }
+}
+
+/// Whether two results store the same calculation on every assignment
+pub fn same_calculations(result: &OptimizationResult, other: &OptimizationResult) -> bool {
+    let by_assignment = |result: &OptimizationResult| -> HashMap<i64, (f64, f64, Option<String>)> {
+        result
+            .calculations
+            .iter()
+            .chain(&result.pinned_calculations)
+            .map(|calc| {
+                (
+                    calc.assignment_id,
+                    (
+                        calc.calculated_allocation_percentage,
+                        calc.calculated_effective_hours,
+                        calc.calculated_start_date.clone(),
+                    ),
+                )
+            })
+            .collect()
+    };
+    let calculations = by_assignment(result);
+    let other_calculations = by_assignment(other);
+
+    calculations.len() == other_calculations.len()
+        && calculations.iter().all(|(id, (allocation, hours, start))| {
+            other_calculations.get(id).is_some_and(
+                |(other_allocation, other_hours, other_start)| {
+                    // Ignore rounding noise, e.g. from the JSON round trip
+                    (allocation - other_allocation).abs() < 1e-6
+                        && (hours - other_hours).abs() < 1e-6
+                        && start == other_start
+                },
+            )
+        })
+}
+
+/// Staffing deltas from old and new effective hours per project, sorted by project name
+fn project_staffing_deltas(
+    totals_by_project: BTreeMap<i64, (f64, f64)>,
+    project_names: &HashMap<i64, String>,
+) -> Vec<ProjectStaffingDelta> {
+    let mut project_deltas: Vec<ProjectStaffingDelta> = totals_by_project
+        .into_iter()
+        .map(
+            |(project_id, (old_effective, new_effective))| ProjectStaffingDelta {
+                project_id,
+                project_name: project_names
+                    .get(&project_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Project {}", project_id)),
+                old_effective_hours: old_effective,
+                new_effective_hours: new_effective,
+                delta_hours: new_effective - old_effective,
+            },
+        )
+        .collect();
+    project_deltas.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+    project_deltas
+}
+
+/// Effective hours of every project in an optimization result
+fn effective_hours_by_project(
+    assignments: &[Assignment],
+    result: &OptimizationResult,
+) -> HashMap<i64, f64> {
+    let project_by_assignment: HashMap<i64, i64> = assignments
+        .iter()
+        .map(|assignment| (assignment.id, assignment.project_id))
+        .collect();
+
+    let mut totals = HashMap::new();
+    for calc in result
+        .calculations
+        .iter()
+        .chain(&result.pinned_calculations)
+    {
+        if let Some(project_id) = project_by_assignment.get(&calc.assignment_id) {
+            *totals.entry(*project_id).or_default() += calc.calculated_effective_hours;
+        }
+    }
+    totals
+}
+
+/// Summarize what an optimization run starts from
+pub fn optimization_inputs_summary(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    dependencies: &[ProjectDependency],
+) -> Result<OptimizationInputsSummary, String> {
+    Ok(OptimizationInputsSummary {
+        input_fingerprint: optimization_fingerprint(data, assignments, requirements, dependencies)?,
+        people: data.people.len(),
+        assignments: assignments.len(),
+        pinned_assignments: assignments
+            .iter()
+            .filter(|assignment| pinned_allocation(assignment).is_some())
+            .count(),
+        required_projects: requirements.len(),
+        required_hours: requirements.iter().map(|r| r.required_hours).sum(),
+    })
+}
+
+/// Per-assignment records of a result, as stored in the run history
+pub fn optimization_run_calculations(
+    result: &OptimizationResult,
+    assignments: &[Assignment],
+) -> Vec<OptimizationRunCalculation> {
+    let assignments_by_id: HashMap<i64, &Assignment> =
+        assignments.iter().map(|a| (a.id, a)).collect();
+
+    let free = result.calculations.iter().map(|calc| (calc, false));
+    let pinned = result.pinned_calculations.iter().map(|calc| (calc, true));
+    free.chain(pinned)
+        .filter_map(|(calc, is_pinned)| {
+            let assignment = assignments_by_id.get(&calc.assignment_id)?;
+            Some(OptimizationRunCalculation {
+                assignment_id: calc.assignment_id,
+                person_id: assignment.person_id,
+                project_id: assignment.project_id,
+                allocation_percentage: calc.calculated_allocation_percentage,
+                effective_hours: calc.calculated_effective_hours,
+                is_pinned,
+                calculated_start_date: calc.calculated_start_date.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Compare the allocations of two stored runs
+///
+/// Assignments missing from a run have no values on that side of the diff.
+pub fn optimization_run_comparison(
+    base: &OptimizationRun,
+    other: &OptimizationRun,
+    project_names: &HashMap<i64, String>,
+) -> OptimizationRunComparison {
+    let base_calculations: HashMap<i64, &OptimizationRunCalculation> = base
+        .calculations
+        .iter()
+        .map(|calc| (calc.assignment_id, calc))
+        .collect();
+    let other_calculations: HashMap<i64, &OptimizationRunCalculation> = other
+        .calculations
+        .iter()
+        .map(|calc| (calc.assignment_id, calc))
+        .collect();
+
+    let assignment_ids: BTreeSet<i64> = base_calculations
+        .keys()
+        .chain(other_calculations.keys())
+        .copied()
+        .collect();
+
+    let mut assignment_diffs = Vec::new();
+    let mut totals_by_project: BTreeMap<i64, (f64, f64)> = BTreeMap::new(); // (base, other)
+    for assignment_id in assignment_ids {
+        let old = base_calculations.get(&assignment_id);
+        let new = other_calculations.get(&assignment_id);
+        let Some(known) = new.or(old) else {
+            continue;
+        };
+
+        assignment_diffs.push(AssignmentDiff {
+            assignment_id,
+            person_id: known.person_id,
+            project_id: known.project_id,
+            old_allocation_percentage: old.map(|calc| calc.allocation_percentage),
+            new_allocation_percentage: new.map(|calc| calc.allocation_percentage),
+            old_effective_hours: old.map(|calc| calc.effective_hours),
+            new_effective_hours: new.map(|calc| calc.effective_hours),
+        });
+
+        let totals = totals_by_project.entry(known.project_id).or_default();
+        totals.0 += old.map_or(0.0, |calc| calc.effective_hours);
+        totals.1 += new.map_or(0.0, |calc| calc.effective_hours);
+    }
+
+    OptimizationRunComparison {
+        base_run_id: base.id,
+        other_run_id: other.id,
+        assignment_diffs,
+        project_deltas: project_staffing_deltas(totals_by_project, project_names),
+    }
+}
+
+/// Default number of runs of a capacity simulation
+pub const DEFAULT_SIMULATION_RUNS: usize = 1000;
+
+/// Most runs a capacity simulation may take
+pub const MAX_SIMULATION_RUNS: usize = 10_000;
+
+/// Summarize samples as their 10th, 50th and 90th percentile (nearest rank)
+fn simulation_distribution(samples: &mut [f64]) -> SimulationDistribution {
+    samples.sort_by(f64::total_cmp);
+    let percentile = |q: f64| {
+        let rank = (q * samples.len() as f64).ceil() as usize;
+        samples
+            .get(rank.clamp(1, samples.len().max(1)) - 1)
+            .copied()
+            .unwrap_or(0.0)
+    };
+    SimulationDistribution {
+        p10: percentile(0.1),
+        p50: percentile(0.5),
+        p90: percentile(0.9),
+    }
+}
+
+/// Monte Carlo simulation of the proportional optimization
+///
+/// Every run samples which optional overhead tasks happen, then recalculates each person's
+/// available hours and re-runs the optimizer. The spread of the results shows how much the
+/// optional overhead puts capacity and staffing at risk.
+pub fn simulate_proportional<R: Rng>(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+    dependencies: &[ProjectDependency],
+    runs: usize,
+    rng: &mut R,
+) -> Result<CapacitySimulation, String> {
+    if runs == 0 {
+        return Err("Simulation needs at least one run".to_string());
+    }
+    if runs > MAX_SIMULATION_RUNS {
+        return Err(format!(
+            "Simulation is limited to {} runs, got {}",
+            MAX_SIMULATION_RUNS, runs
+        ));
+    }
+
+    let mut person_samples: HashMap<i64, Vec<f64>> = HashMap::new();
+    let mut project_samples: HashMap<i64, Vec<f64>> = HashMap::new();
+
+    for _ in 0..runs {
+        let sampled = data.sample_optional_overhead(rng);
+
+        for person in &sampled.people {
+            let breakdown = sampled.person_breakdown(person)?;
+            person_samples
+                .entry(person.id)
+                .or_default()
+                .push(breakdown.available_hours);
+        }
+
+        let result = SequencedStrategy {
+            inner: &ProportionalStrategy,
+            dependencies,
+        }
+        .optimize(&sampled, assignments, requirements, project_names)?;
+        let effective_hours: HashMap<i64, f64> = result
+            .calculations
+            .iter()
+            .chain(&result.pinned_calculations)
+            .map(|c| (c.assignment_id, c.calculated_effective_hours))
+            .collect();
+
+        for requirement in requirements {
+            let total_effective_hours: f64 = assignments
+                .iter()
+                .filter(|a| a.project_id == requirement.project_id)
+                .filter_map(|a| effective_hours.get(&a.id))
+                .sum();
+            // Nothing required is fully staffed, as project_shortfall also has it
+            let staffing_percentage = if requirement.required_hours > 0.0 {
+                (total_effective_hours / requirement.required_hours) * 100.0
+            } else {
+                100.0
+            };
+            project_samples
+                .entry(requirement.project_id)
+                .or_default()
+                .push(staffing_percentage);
+        }
+    }
+
+    let people = data
+        .people
+        .iter()
+        .map(|person| PersonSimulation {
+            person_id: person.id,
+            person_name: person.name.clone(),
+            available_hours: simulation_distribution(
+                person_samples
+                    .get_mut(&person.id)
+                    .map(Vec::as_mut_slice)
+                    .unwrap_or_default(),
+            ),
+        })
+        .collect();
+
+    let mut projects: Vec<ProjectSimulation> = requirements
+        .iter()
+        .map(|requirement| {
+            let samples = project_samples
+                .get_mut(&requirement.project_id)
+                .map(Vec::as_mut_slice)
+                .unwrap_or_default();
+            // Use tolerance for floating-point comparison (99.95% rounds to 100.0%)
+            let viable_runs = samples.iter().filter(|pct| **pct >= 99.95).count();
+            ProjectSimulation {
+                project_id: requirement.project_id,
+                project_name: project_names
+                    .get(&requirement.project_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Project {}", requirement.project_id)),
+                required_hours: requirement.required_hours,
+                viable_probability: viable_runs as f64 / runs as f64,
+                staffing_percentage: simulation_distribution(samples),
+            }
+        })
+        .collect();
+    projects.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+
+    Ok(CapacitySimulation {
+        planning_period_id: data.planning_period.id,
+        runs,
+        people,
+        projects,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+    use chrono::NaiveDate;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     // Tests for parse_working_days_count
     #[test]
@@ -720,253 +2545,1811 @@ mod tests {
         assert_eq!(parse_working_days_count("Mon, Tue, Wed"), 3);
     }
 
-    // Tests for parse_working_days_set
+    // Tests for parse_working_days_set
+    #[test]
+    fn test_parse_working_days_set_standard_weekdays() {
+        let days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        assert_eq!(days.len(), 5);
+        assert!(days.contains(&Weekday::Mon));
+        assert!(days.contains(&Weekday::Tue));
+        assert!(days.contains(&Weekday::Wed));
+        assert!(days.contains(&Weekday::Thu));
+        assert!(days.contains(&Weekday::Fri));
+        assert!(!days.contains(&Weekday::Sat));
+        assert!(!days.contains(&Weekday::Sun));
+    }
+
+    #[test]
+    fn test_parse_working_days_set_weekend_only() {
+        let days = parse_working_days_set("Sat,Sun");
+        assert_eq!(days.len(), 2);
+        assert!(days.contains(&Weekday::Sat));
+        assert!(days.contains(&Weekday::Sun));
+    }
+
+    #[test]
+    fn test_parse_working_days_set_with_invalid_day() {
+        // Invalid days should be skipped
+        let days = parse_working_days_set("Mon,InvalidDay,Wed");
+        assert_eq!(days.len(), 2);
+        assert!(days.contains(&Weekday::Mon));
+        assert!(days.contains(&Weekday::Wed));
+    }
+
+    #[test]
+    fn test_parse_working_days_set_empty_string() {
+        let days = parse_working_days_set("");
+        assert!(days.is_empty());
+    }
+
+    // Tests for is_working_day
+    #[test]
+    fn test_is_working_day_monday_on_weekday_schedule() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(); // Monday
+        let working_days = vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        assert!(is_working_day(&date, &working_days));
+    }
+
+    #[test]
+    fn test_is_working_day_saturday_on_weekday_schedule() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(); // Saturday
+        let working_days = vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        assert!(!is_working_day(&date, &working_days));
+    }
+
+    #[test]
+    fn test_is_working_day_saturday_on_weekend_schedule() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(); // Saturday
+        let working_days = vec![Weekday::Sat, Weekday::Sun];
+        assert!(is_working_day(&date, &working_days));
+    }
+
+    #[test]
+    fn test_is_working_day_empty_schedule() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(); // Monday
+        let working_days: Vec<Weekday> = vec![];
+        assert!(!is_working_day(&date, &working_days));
+    }
+
+    // Tests for count_working_days
+    #[test]
+    fn test_count_working_days_full_weeks() {
+        // Mon 2024-01-01 to Sun 2024-01-14: two full weeks
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 14).unwrap();
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        assert_eq!(count_working_days(start, end, &working_days), 10);
+    }
+
+    #[test]
+    fn test_count_working_days_ten_days_starting_saturday() {
+        // Sat 2024-01-06 to Mon 2024-01-15: Mon-Fri once, plus the final Monday
+        let start = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        assert_eq!(count_working_days(start, end, &working_days), 6);
+    }
+
+    #[test]
+    fn test_count_working_days_ten_days_starting_monday() {
+        // Mon 2024-01-08 to Wed 2024-01-17: Mon-Fri once, plus Mon-Wed
+        let start = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        assert_eq!(count_working_days(start, end, &working_days), 8);
+    }
+
+    #[test]
+    fn test_count_working_days_custom_schedule() {
+        // Mon/Wed/Fri schedule over two full weeks
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 14).unwrap();
+        let working_days = parse_working_days_set("Mon,Wed,Fri");
+        assert_eq!(count_working_days(start, end, &working_days), 6);
+    }
+
+    #[test]
+    fn test_count_working_days_single_day() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(); // Saturday
+        assert_eq!(
+            count_working_days(date, date, &parse_working_days_set("Mon,Tue,Wed,Thu,Fri")),
+            0
+        );
+        assert_eq!(
+            count_working_days(date, date, &parse_working_days_set("Sat,Sun")),
+            1
+        );
+    }
+
+    #[test]
+    fn test_count_working_days_end_before_start() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        assert_eq!(count_working_days(start, end, &working_days), 0);
+    }
+
+    // Tests for working_dates_in_ranges
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_working_dates_in_ranges_clips_to_bounds() {
+        // Absence Thu 2024-01-04 to Wed 2024-01-10, range starts Mon 2024-01-08
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let dates = working_dates_in_ranges(
+            &[(date(2024, 1, 4), date(2024, 1, 10))],
+            date(2024, 1, 8),
+            date(2024, 1, 31),
+            &working_days,
+        );
+        assert_eq!(dates.len(), 3); // Mon, Tue, Wed
+        assert_eq!(dates.first(), Some(&date(2024, 1, 8)));
+    }
+
+    #[test]
+    fn test_working_dates_in_ranges_skips_non_working_days() {
+        // Fri 2024-01-05 to Mon 2024-01-08 spans a weekend
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let dates = working_dates_in_ranges(
+            &[(date(2024, 1, 5), date(2024, 1, 8))],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+        );
+        assert_eq!(dates.len(), 2);
+    }
+
+    #[test]
+    fn test_working_dates_in_ranges_merges_overlaps() {
+        // Mon-Wed and Tue-Fri overlap on Tue and Wed
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let dates = working_dates_in_ranges(
+            &[
+                (date(2024, 1, 8), date(2024, 1, 10)),
+                (date(2024, 1, 9), date(2024, 1, 12)),
+            ],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+        );
+        assert_eq!(dates.len(), 5);
+    }
+
+    #[test]
+    fn test_working_dates_in_ranges_outside_bounds() {
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let dates = working_dates_in_ranges(
+            &[(date(2023, 12, 18), date(2023, 12, 22))],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+        );
+        assert!(dates.is_empty());
+    }
+
+    #[test]
+    fn test_absence_dates_exclude_holidays() {
+        // Week-long absence containing a public holiday on Wednesday
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let start = date(2024, 1, 1);
+        let end = date(2024, 1, 31);
+        let holidays = working_dates_in_ranges(
+            &[(date(2024, 1, 10), date(2024, 1, 10))],
+            start,
+            end,
+            &working_days,
+        );
+        let absences = working_dates_in_ranges(
+            &[(date(2024, 1, 8), date(2024, 1, 12))],
+            start,
+            end,
+            &working_days,
+        );
+        assert_eq!(absences.difference(&holidays).count(), 4);
+    }
+
+    // Tests for timeline_ranges
+    #[test]
+    fn test_timeline_ranges_iso_weeks_clipped() {
+        // Wednesday 2024-01-03 to Tuesday 2024-01-16
+        let ranges = timeline_ranges(date(2024, 1, 3), date(2024, 1, 16), "week").unwrap();
+        assert_eq!(
+            ranges,
+            vec![
+                ("2024-W01".to_string(), date(2024, 1, 3), date(2024, 1, 7)),
+                ("2024-W02".to_string(), date(2024, 1, 8), date(2024, 1, 14)),
+                ("2024-W03".to_string(), date(2024, 1, 15), date(2024, 1, 16)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeline_ranges_iso_week_year_boundary() {
+        // 2024-12-30 belongs to ISO week 1 of 2025
+        let ranges = timeline_ranges(date(2024, 12, 30), date(2025, 1, 5), "week").unwrap();
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].0, "2025-W01");
+    }
+
+    #[test]
+    fn test_timeline_ranges_months() {
+        // Quarter starting mid-month
+        let ranges = timeline_ranges(date(2024, 11, 15), date(2025, 2, 10), "month").unwrap();
+        let labels: Vec<&str> = ranges.iter().map(|(label, _, _)| label.as_str()).collect();
+        assert_eq!(labels, vec!["2024-11", "2024-12", "2025-01", "2025-02"]);
+        assert_eq!(ranges[0].1, date(2024, 11, 15));
+        assert_eq!(ranges[1].2, date(2024, 12, 31));
+        assert_eq!(ranges[3].2, date(2025, 2, 10));
+    }
+
+    #[test]
+    fn test_timeline_ranges_invalid_granularity() {
+        assert!(timeline_ranges(date(2024, 1, 1), date(2024, 1, 31), "day").is_err());
+    }
+
+    // Tests for partial-day absences
+    fn absence(
+        start: &str,
+        end: &str,
+        hours: Option<f64>,
+        day_fraction: Option<f64>,
+    ) -> ModelAbsence {
+        ModelAbsence {
+            id: 0,
+            person_id: 1,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            days: 0,
+            hours,
+            day_fraction,
+            reason: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_absence_day_fraction_full_day() {
+        let a = absence("2024-01-08", "2024-01-08", None, None);
+        assert!((absence_day_fraction(&a, 8.0) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_day_fraction_half_day() {
+        let a = absence("2024-01-08", "2024-01-08", None, Some(0.5));
+        assert!((absence_day_fraction(&a, 8.0) - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_day_fraction_hours_uses_hours_per_day() {
+        // Leaving two hours early on an 8h day
+        let a = absence("2024-01-08", "2024-01-08", Some(2.0), None);
+        assert!((absence_day_fraction(&a, 8.0) - 0.25).abs() < 0.001);
+        // Same two hours on a 6h day (30h over 5 days)
+        assert!((absence_day_fraction(&a, 6.0) - (2.0 / 6.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_day_fraction_hours_capped_at_full_day() {
+        let a = absence("2024-01-08", "2024-01-08", Some(12.0), None);
+        assert!((absence_day_fraction(&a, 8.0) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_fractions_overlap_keeps_largest() {
+        // Half day on Tuesday inside a full-week absence counts as a full day
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let fractions = absence_fractions_by_date(
+            &[
+                absence("2024-01-09", "2024-01-09", None, Some(0.5)),
+                absence("2024-01-08", "2024-01-12", None, None),
+            ],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        let total: f64 = fractions.values().sum();
+        assert!((total - 5.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_fractions_partial_days_sum() {
+        // Two hours off on three consecutive working days at 8h/day
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let fractions = absence_fractions_by_date(
+            &[absence("2024-01-08", "2024-01-10", Some(2.0), None)],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        let total: f64 = fractions.values().sum();
+        assert!((total * 8.0 - 6.0).abs() < 0.001);
+    }
+
+    // Tests for partial-day holidays
+    fn holiday(start: &str, end: &str, hours: Option<f64>, day_fraction: Option<f64>) -> Holiday {
+        Holiday {
+            id: 0,
+            country_id: 1,
+            name: None,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            hours,
+            day_fraction,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_holiday_fractions_half_day() {
+        // Christmas Eve as a half day plus Christmas and St. Stephen's Day in full
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let fractions = holiday_fractions_by_date(
+            &[
+                holiday("2024-12-24", "2024-12-24", None, Some(0.5)),
+                holiday("2024-12-25", "2024-12-26", None, None),
+            ],
+            date(2024, 12, 1),
+            date(2024, 12, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        let total: f64 = fractions.values().sum();
+        assert!((total - 2.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_holiday_fractions_hours_use_hours_per_day() {
+        // New Year's Eve off from 12:00 on a 6h day (30h over 5 days)
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let fractions = holiday_fractions_by_date(
+            &[holiday("2024-12-31", "2024-12-31", Some(3.0), None)],
+            date(2024, 12, 1),
+            date(2024, 12, 31),
+            &working_days,
+            6.0,
+        )
+        .unwrap();
+        assert!((fractions[&date(2024, 12, 31)] - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_days_outside_full_holiday() {
+        // Week-long absence containing a full-day holiday on Wednesday
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let absences = absence_fractions_by_date(
+            &[absence("2024-01-08", "2024-01-12", None, None)],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        let holidays = holiday_fractions_by_date(
+            &[holiday("2024-01-10", "2024-01-10", None, None)],
+            date(2024, 1, 1),
+            date(2024, 1, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        assert!((absence_days_outside_holidays(&absences, &holidays) - 4.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_absence_days_outside_half_day_holiday() {
+        // Full day off on a half-day holiday only deducts the other half as absence
+        let working_days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
+        let holidays = holiday_fractions_by_date(
+            &[holiday("2024-12-24", "2024-12-24", None, Some(0.5))],
+            date(2024, 12, 1),
+            date(2024, 12, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        let full_day = absence_fractions_by_date(
+            &[absence("2024-12-23", "2024-12-24", None, None)],
+            date(2024, 12, 1),
+            date(2024, 12, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        assert!((absence_days_outside_holidays(&full_day, &holidays) - 1.5).abs() < 0.001);
+
+        // Two hours off in the working half of the day are deducted in full
+        let two_hours = absence_fractions_by_date(
+            &[absence("2024-12-24", "2024-12-24", Some(2.0), None)],
+            date(2024, 12, 1),
+            date(2024, 12, 31),
+            &working_days,
+            8.0,
+        )
+        .unwrap();
+        assert!((absence_days_outside_holidays(&two_hours, &holidays) - 0.25).abs() < 0.001);
+    }
+
+    // Tests for calculate_assignment_effective_hours
+    #[test]
+    fn test_effective_hours_full_allocation_expert() {
+        // 40h available, 100% allocation, 0.8 productivity (expert)
+        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.8);
+        assert!((effective - 32.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_half_allocation_proficient() {
+        // 40h available, 50% allocation, 0.5 productivity (proficient)
+        let effective = calculate_assignment_effective_hours(40.0, 50.0, 0.5);
+        assert!((effective - 10.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_zero_allocation() {
+        let effective = calculate_assignment_effective_hours(40.0, 0.0, 0.8);
+        assert!((effective - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_zero_productivity() {
+        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.0);
+        assert!((effective - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_zero_available() {
+        let effective = calculate_assignment_effective_hours(0.0, 100.0, 0.8);
+        assert!((effective - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_real_scenario() {
+        // Realistic scenario: 35h/week available, 25% allocation, 0.65 productivity (advanced)
+        // Expected: 35 * 0.25 * 0.65 = 5.6875
+        let effective = calculate_assignment_effective_hours(35.0, 25.0, 0.65);
+        assert!((effective - 5.6875).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_master_level() {
+        // Master level: 40h available, 100% allocation, 0.9 productivity
+        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.9);
+        assert!((effective - 36.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_effective_hours_trainee_level() {
+        // Trainee level: 40h available, 100% allocation, 0.1 productivity
+        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.1);
+        assert!((effective - 4.0).abs() < 0.001);
+    }
+
+    // Tests for per-day person capacity tracking
+    fn window(first_day: usize, last_day: usize) -> AssignmentWindow {
+        AssignmentWindow {
+            available_hours: 0.0,
+            first_day,
+            last_day,
+        }
+    }
+
+    #[test]
+    fn test_person_state_starts_fully_available() {
+        let state = PersonState::new(10, 100.0);
+        assert!((state.remaining_in(&window(0, 10)) - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_person_state_disjoint_windows_do_not_compete() {
+        // First half of the period fully allocated, second half untouched
+        let mut state = PersonState::new(10, 100.0);
+        state.allocate(&window(0, 5), 100.0);
+        assert!((state.remaining_in(&window(0, 5)) - 0.0).abs() < 0.001);
+        assert!((state.remaining_in(&window(5, 10)) - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_person_state_overlapping_window_uses_minimum() {
+        let mut state = PersonState::new(10, 100.0);
+        state.allocate(&window(3, 6), 60.0);
+        // Window spanning the allocated days is limited by the busiest day
+        assert!((state.remaining_in(&window(0, 10)) - 40.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_person_state_allocation_floors_at_zero() {
+        let mut state = PersonState::new(5, 100.0);
+        state.allocate(&window(0, 5), 70.0);
+        state.allocate(&window(0, 5), 70.0);
+        assert!((state.remaining_in(&window(0, 5)) - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_person_state_empty_window_has_no_capacity() {
+        let state = PersonState::new(5, 100.0);
+        assert!((state.remaining_in(&window(3, 3)) - 0.0).abs() < 0.001);
+    }
+
+    // Tests for calculate_available_hours and optional overhead weighting
+    #[test]
+    fn test_available_hours_no_deductions() {
+        // Base case: no absences, holidays, or overhead
+        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 0.0, 0.5);
+        assert!((available - 160.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_with_absences_only() {
+        // 160h base, 16h absence (2 days at 8h/day)
+        let available = calculate_available_hours(160.0, 16.0, 0.0, 0.0, 0.0, 0.5);
+        assert!((available - 144.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_with_holidays_only() {
+        // 160h base, 8h holiday (1 day)
+        let available = calculate_available_hours(160.0, 0.0, 8.0, 0.0, 0.0, 0.5);
+        assert!((available - 152.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_with_required_overhead_only() {
+        // 160h base, 10h required overhead
+        let available = calculate_available_hours(160.0, 0.0, 0.0, 10.0, 0.0, 0.5);
+        assert!((available - 150.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_with_optional_overhead_50_percent_weight() {
+        // 160h base, 20h optional overhead at 50% weight = 10h deduction
+        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 20.0, 0.5);
+        assert!((available - 150.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_with_optional_overhead_zero_weight() {
+        // 160h base, 20h optional overhead at 0% weight = 0h deduction
+        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 20.0, 0.0);
+        assert!((available - 160.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_with_optional_overhead_full_weight() {
+        // 160h base, 20h optional overhead at 100% weight = 20h deduction
+        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 20.0, 1.0);
+        assert!((available - 140.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_combined_all_deductions() {
+        // Realistic scenario:
+        // - 160h base (4 weeks at 40h/week)
+        // - 16h absence (2 vacation days)
+        // - 8h holidays (1 public holiday)
+        // - 8h required overhead (team meetings)
+        // - 4h optional overhead at 50% = 2h deduction (optional training)
+        // Expected: 160 - 16 - 8 - 8 - 2 = 126h
+        let available = calculate_available_hours(160.0, 16.0, 8.0, 8.0, 4.0, 0.5);
+        assert!((available - 126.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_mixed_overhead_types() {
+        // Test with both required and optional overhead
+        // 160h base, 20h required, 10h optional at 50% = 5h
+        // Expected: 160 - 20 - 5 = 135h
+        let available = calculate_available_hours(160.0, 0.0, 0.0, 20.0, 10.0, 0.5);
+        assert!((available - 135.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_floor_at_zero() {
+        // Deductions exceed base hours - should floor at 0
+        let available = calculate_available_hours(100.0, 50.0, 30.0, 30.0, 0.0, 0.5);
+        assert!((available - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_available_hours_optional_weight_boundary_values() {
+        // Test weight at edge values
+        let base = 100.0;
+        let optional = 40.0;
+
+        // Weight 0.0: no deduction from optional
+        assert!(
+            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.0) - 100.0).abs() < 0.001
+        );
+
+        // Weight 0.25: 10h deduction
+        assert!(
+            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.25) - 90.0).abs() < 0.001
+        );
+
+        // Weight 0.5 (default): 20h deduction
+        assert!(
+            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.5) - 80.0).abs() < 0.001
+        );
+
+        // Weight 0.75: 30h deduction
+        assert!(
+            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.75) - 70.0).abs() < 0.001
+        );
+
+        // Weight 1.0: full 40h deduction
+        assert!(
+            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 1.0) - 60.0).abs() < 0.001
+        );
+    }
+
+    #[test]
+    fn test_default_optional_weight_constant() {
+        // Verify the default constant is 0.5 (50%)
+        assert!((DEFAULT_OPTIONAL_WEIGHT - 0.5).abs() < 0.001);
+    }
+
+    // Scenario tests for the in-memory capacity core
+    fn overhead_task(
+        effort_hours: f64,
+        effort_period: &str,
+        optional_weight: Option<f64>,
+    ) -> JobOverheadTask {
+        JobOverheadTask {
+            id: 0,
+            job_id: 1,
+            name: String::new(),
+            description: None,
+            effort_hours,
+            effort_period: effort_period.to_string(),
+            is_optional: optional_weight.is_some(),
+            optional_weight: optional_weight.unwrap_or(0.0),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_person_breakdown_all_deductions() {
+        // 160h base - 16h absence - 8h Good Friday - 8h weekly sync - 5h weighted optional
+        let job_assignment = PersonJobAssignment {
+            id: 1,
+            person_id: 1,
+            job_id: 1,
+            planning_period_id: 1,
+            created_at: String::new(),
+        };
+        let data = PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, Some(1))],
+            vec![absence("2024-03-11", "2024-03-12", None, None)],
+            vec![holiday("2024-03-29", "2024-03-29", None, None)],
+            vec![job_assignment],
+            vec![
+                overhead_task(2.0, "weekly", None),
+                overhead_task(0.5, "daily", Some(0.5)),
+            ],
+        )
+        .unwrap();
+
+        let person = data.person(1).unwrap();
+        let breakdown = data.person_breakdown(person).unwrap();
+        assert_eq!(breakdown.working_days, 20);
+        assert!((breakdown.base_hours - 160.0).abs() < 0.001);
+        assert!((breakdown.absence_hours - 16.0).abs() < 0.001);
+        assert!((breakdown.holiday_hours - 8.0).abs() < 0.001);
+        assert!((breakdown.overhead_hours - 8.0).abs() < 0.001);
+        assert!((breakdown.optional_overhead_hours - 10.0).abs() < 0.001);
+        assert!((breakdown.available_hours - 123.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_person_breakdown_ignores_other_country_holidays() {
+        let data = PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, Some(2))],
+            vec![],
+            vec![holiday("2024-03-29", "2024-03-29", None, None)],
+            vec![],
+            vec![],
+        )
+        .unwrap();
+
+        let breakdown = data.person_breakdown(data.person(1).unwrap()).unwrap();
+        assert!((breakdown.holiday_hours - 0.0).abs() < 0.001);
+        assert!((breakdown.available_hours - 160.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_assignment_breakdown_uses_window() {
+        let data = single_person_data();
+        let assignment = project_assignment(1, 1, "2024-03-18", "2024-04-30");
+
+        // Window is clipped to the period: two weeks remain
+        let breakdown = data
+            .assignment_breakdown(data.person(1).unwrap(), &assignment)
+            .unwrap();
+        assert_eq!(breakdown.working_days, 10);
+        assert!((breakdown.available_hours - 80.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_batched_breakdowns_match_per_person_data() {
+        // Person 1 works Mon-Fri in country 1, person 2 works Mon/Wed/Fri in country 2
+        let mut part_timer = full_timer(2, Some(2));
+        part_timer.available_hours_per_week = 24.0;
+        part_timer.working_days = "Mon,Wed,Fri".to_string();
+        let people = vec![full_timer(1, Some(1)), part_timer];
+
+        let mut absences = vec![
+            absence("2024-03-11", "2024-03-12", None, None),
+            absence("2024-03-20", "2024-03-20", None, Some(0.5)),
+            absence("2024-03-06", "2024-03-08", None, None),
+            absence("2024-02-26", "2024-03-05", None, None), // Starts before the period
+        ];
+        absences[2].person_id = 2;
+        absences[3].person_id = 2;
+
+        let mut holidays = vec![
+            holiday("2024-03-29", "2024-03-29", None, None),
+            holiday("2024-04-01", "2024-04-01", None, None), // After the period
+            holiday("2024-03-18", "2024-03-18", None, Some(0.5)),
+        ];
+        holidays[2].country_id = 2;
+
+        let job_assignments: Vec<PersonJobAssignment> = [(1, 1), (2, 2)]
+            .into_iter()
+            .map(|(person_id, job_id)| PersonJobAssignment {
+                id: person_id,
+                person_id,
+                job_id,
+                planning_period_id: 1,
+                created_at: String::new(),
+            })
+            .collect();
+        let mut overhead_tasks = vec![
+            overhead_task(2.0, "weekly", None),
+            overhead_task(0.5, "daily", Some(0.5)),
+        ];
+        overhead_tasks[1].job_id = 2;
+
+        let batched = PeriodCapacityData::new(
+            march_period(),
+            people.clone(),
+            absences.clone(),
+            holidays.clone(),
+            job_assignments.clone(),
+            overhead_tasks.clone(),
+        )
+        .unwrap();
+
+        let windows = [
+            ("2024-03-04", "2024-03-31"),
+            ("2024-03-11", "2024-03-22"),
+            ("2024-02-15", "2024-03-08"), // Clipped at the period start
+            ("2024-03-25", "2024-04-30"), // Clipped at the period end
+        ];
+        for person in &people {
+            // The records a per-person query returns: own absences, own country's holidays,
+            // own job assignments and the overhead tasks of those jobs
+            let person_jobs: Vec<PersonJobAssignment> = job_assignments
+                .iter()
+                .filter(|job| job.person_id == person.id)
+                .cloned()
+                .collect();
+            let single = PeriodCapacityData::new(
+                march_period(),
+                vec![person.clone()],
+                absences
+                    .iter()
+                    .filter(|absence| absence.person_id == person.id)
+                    .cloned()
+                    .collect(),
+                holidays
+                    .iter()
+                    .filter(|holiday| Some(holiday.country_id) == person.country_id)
+                    .cloned()
+                    .collect(),
+                person_jobs.clone(),
+                overhead_tasks
+                    .iter()
+                    .filter(|task| person_jobs.iter().any(|job| job.job_id == task.job_id))
+                    .cloned()
+                    .collect(),
+            )
+            .unwrap();
+
+            assert_eq!(
+                batched.person_breakdown(person).unwrap(),
+                single.person_breakdown(person).unwrap()
+            );
+            for (start, end) in windows {
+                let mut assignment = project_assignment(1, 1, start, end);
+                assignment.person_id = person.id;
+                assert_eq!(
+                    batched.assignment_breakdown(person, &assignment).unwrap(),
+                    single.assignment_breakdown(person, &assignment).unwrap()
+                );
+                assert_eq!(
+                    batched
+                        .assignment_breakdown_in_range(
+                            person,
+                            &assignment,
+                            date(2024, 3, 6),
+                            date(2024, 3, 20)
+                        )
+                        .unwrap(),
+                    single
+                        .assignment_breakdown_in_range(
+                            person,
+                            &assignment,
+                            date(2024, 3, 6),
+                            date(2024, 3, 20)
+                        )
+                        .unwrap()
+                );
+            }
+        }
+
+        // The batch really does deduct something for both people
+        let part_timer = batched.person_breakdown(&people[1]).unwrap();
+        assert!(part_timer.absence_days > 0.0);
+        assert!(part_timer.holiday_days > 0.0);
+        assert!(part_timer.optional_overhead_hours > 0.0);
+    }
+
+    #[test]
+    fn test_optimize_higher_priority_served_first() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 100.0, 0), requirement(2, 100.0, 20)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        // High priority gets 100h of 160h, low priority only the remaining 60h
+        let high = result
+            .calculations
+            .iter()
+            .find(|c| c.assignment_id == 2)
+            .unwrap();
+        let low = result
+            .calculations
+            .iter()
+            .find(|c| c.assignment_id == 1)
+            .unwrap();
+        assert!((high.calculated_allocation_percentage - 62.5).abs() < 0.001);
+        assert!((high.calculated_effective_hours - 100.0).abs() < 0.001);
+        assert!((low.calculated_allocation_percentage - 37.5).abs() < 0.001);
+        assert!((low.calculated_effective_hours - 60.0).abs() < 0.001);
+
+        assert_eq!(result.infeasible_projects.len(), 1);
+        let shortfall = &result.infeasible_projects[0];
+        assert_eq!(shortfall.project_id, 1);
+        assert_eq!(shortfall.project_name, "Project 1");
+        assert!((shortfall.shortfall - 40.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_optimize_explains_shortfall() {
+        let data = PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, Some(1))],
+            vec![],
+            vec![holiday("2024-03-29", "2024-03-29", None, None)],
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+            project_assignment(3, 3, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![
+            requirement(1, 100.0, 10),
+            requirement(2, 100.0, 20),
+            requirement(3, 100.0, 0),
+        ];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        // 152h after the holiday: project 2 takes 100h, project 1 the remaining 52h
+        let shortfall = result
+            .infeasible_projects
+            .iter()
+            .find(|s| s.project_id == 1)
+            .unwrap();
+        assert_eq!(shortfall.explanation.len(), 1);
+        let person = &shortfall.explanation[0];
+        assert_eq!(person.assignment_id, 1);
+        assert_eq!(person.person_name, "Person 1");
+        assert!((person.available_hours - 152.0).abs() < 0.001);
+        assert!((person.holiday_hours - 8.0).abs() < 0.001);
+        assert!((person.effective_hours - 52.0).abs() < 0.001);
+
+        // Only the higher-priority project competed; the low-priority one came later
+        assert_eq!(person.competing_projects.len(), 1);
+        assert_eq!(person.competing_projects[0].project_id, 2);
+        assert_eq!(person.competing_projects[0].priority, 20);
+        assert!((person.competing_projects[0].allocated_hours - 100.0).abs() < 0.001);
+
+        // The low-priority project sees both projects ahead of it
+        let low = result
+            .infeasible_projects
+            .iter()
+            .find(|s| s.project_id == 3)
+            .unwrap();
+        let competing: Vec<i64> = low.explanation[0]
+            .competing_projects
+            .iter()
+            .map(|c| c.project_id)
+            .collect();
+        assert_eq!(competing, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_optimize_disjoint_windows_do_not_compete() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-17"),
+            project_assignment(2, 2, "2024-03-18", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 80.0, 20), requirement(2, 80.0, 0)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert!(result.infeasible_projects.is_empty());
+        for calculation in &result.calculations {
+            assert!((calculation.calculated_allocation_percentage - 100.0).abs() < 0.001);
+            assert!((calculation.calculated_effective_hours - 80.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_optimize_warns_about_missing_requirement() {
+        let data = single_person_data();
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+
+        let result = optimize_proportional(&data, &assignments, &[], &HashMap::new()).unwrap();
+
+        assert!(result.calculations.is_empty());
+        assert_eq!(
+            result.warnings,
+            vec!["Project ID 1 has assignments but no requirement defined".to_string()]
+        );
+    }
+
+    // Tests for the capacity simulation
+    fn data_with_optional_task(optional_weight: f64) -> PeriodCapacityData {
+        let job_assignment = PersonJobAssignment {
+            id: 1,
+            person_id: 1,
+            job_id: 1,
+            planning_period_id: 1,
+            created_at: String::new(),
+        };
+        // 40h optional workshop on top of 160h base hours
+        PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, None)],
+            vec![],
+            vec![],
+            vec![job_assignment],
+            vec![overhead_task(10.0, "weekly", Some(optional_weight))],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_simulation_distribution_nearest_rank() {
+        let mut samples: Vec<f64> = (1..=10).rev().map(f64::from).collect();
+        let distribution = simulation_distribution(&mut samples);
+        assert!((distribution.p10 - 1.0).abs() < 0.001);
+        assert!((distribution.p50 - 5.0).abs() < 0.001);
+        assert!((distribution.p90 - 9.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_simulation_distribution_empty() {
+        let distribution = simulation_distribution(&mut []);
+        assert!((distribution.p50 - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sample_optional_overhead_certain_outcomes() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let always = data_with_optional_task(1.0).sample_optional_overhead(&mut rng);
+        let breakdown = always.person_breakdown(always.person(1).unwrap()).unwrap();
+        assert!((breakdown.available_hours - 120.0).abs() < 0.001);
+
+        let never = data_with_optional_task(0.0).sample_optional_overhead(&mut rng);
+        let breakdown = never.person_breakdown(never.person(1).unwrap()).unwrap();
+        assert!((breakdown.available_hours - 160.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_simulate_without_optional_overhead_is_deterministic() {
+        let data = single_person_data();
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let requirements = vec![requirement(1, 100.0, 10)];
+
+        let simulation = simulate_proportional(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &[],
+            50,
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+
+        assert_eq!(simulation.runs, 50);
+        let person = &simulation.people[0];
+        assert!((person.available_hours.p10 - 160.0).abs() < 0.001);
+        assert!((person.available_hours.p90 - 160.0).abs() < 0.001);
+        let project = &simulation.projects[0];
+        assert!((project.staffing_percentage.p50 - 100.0).abs() < 0.001);
+        assert!((project.viable_probability - 1.0).abs() < 0.001);
+    }
+
     #[test]
-    fn test_parse_working_days_set_standard_weekdays() {
-        let days = parse_working_days_set("Mon,Tue,Wed,Thu,Fri");
-        assert_eq!(days.len(), 5);
-        assert!(days.contains(&Weekday::Mon));
-        assert!(days.contains(&Weekday::Tue));
-        assert!(days.contains(&Weekday::Wed));
-        assert!(days.contains(&Weekday::Thu));
-        assert!(days.contains(&Weekday::Fri));
-        assert!(!days.contains(&Weekday::Sat));
-        assert!(!days.contains(&Weekday::Sun));
+    fn test_simulate_optional_overhead_puts_project_at_risk() {
+        // The project needs 140h: fully staffed only when the workshop doesn't happen
+        let data = data_with_optional_task(0.5);
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let requirements = vec![requirement(1, 140.0, 10)];
+
+        let simulation = simulate_proportional(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &[],
+            1000,
+            &mut StdRng::seed_from_u64(42),
+        )
+        .unwrap();
+
+        let person = &simulation.people[0];
+        assert!((person.available_hours.p10 - 120.0).abs() < 0.001);
+        assert!((person.available_hours.p90 - 160.0).abs() < 0.001);
+
+        let project = &simulation.projects[0];
+        assert!((project.staffing_percentage.p10 - 120.0 / 140.0 * 100.0).abs() < 0.001);
+        assert!((project.staffing_percentage.p90 - 100.0).abs() < 0.001);
+        assert!(project.viable_probability > 0.4 && project.viable_probability < 0.6);
     }
 
     #[test]
-    fn test_parse_working_days_set_weekend_only() {
-        let days = parse_working_days_set("Sat,Sun");
-        assert_eq!(days.len(), 2);
-        assert!(days.contains(&Weekday::Sat));
-        assert!(days.contains(&Weekday::Sun));
+    fn test_simulate_counts_zero_hour_requirement_as_viable() {
+        let data = single_person_data();
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let requirements = vec![requirement(1, 0.0, 10)];
+
+        let simulation = simulate_proportional(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &[],
+            10,
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+
+        let project = &simulation.projects[0];
+        assert!((project.staffing_percentage.p10 - 100.0).abs() < 0.001);
+        assert!((project.viable_probability - 1.0).abs() < 0.001);
+        assert!(project_shortfall(&requirements[0], 0.0, &HashMap::new()).is_none());
     }
 
     #[test]
-    fn test_parse_working_days_set_with_invalid_day() {
-        // Invalid days should be skipped
-        let days = parse_working_days_set("Mon,InvalidDay,Wed");
-        assert_eq!(days.len(), 2);
-        assert!(days.contains(&Weekday::Mon));
-        assert!(days.contains(&Weekday::Wed));
+    fn test_simulate_rejects_zero_runs() {
+        let data = single_person_data();
+        let result = simulate_proportional(
+            &data,
+            &[],
+            &[],
+            &HashMap::new(),
+            &[],
+            0,
+            &mut StdRng::seed_from_u64(1),
+        );
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_parse_working_days_set_empty_string() {
-        let days = parse_working_days_set("");
-        assert!(days.is_empty());
+    fn test_simulate_rejects_too_many_runs() {
+        let data = single_person_data();
+        let error = simulate_proportional(
+            &data,
+            &[],
+            &[],
+            &HashMap::new(),
+            &[],
+            MAX_SIMULATION_RUNS + 1,
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap_err();
+        assert_eq!(error, "Simulation is limited to 10000 runs, got 10001");
     }
 
-    // Tests for is_working_day
     #[test]
-    fn test_is_working_day_monday_on_weekday_schedule() {
-        let date = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(); // Monday
-        let working_days = vec![
-            Weekday::Mon,
-            Weekday::Tue,
-            Weekday::Wed,
-            Weekday::Thu,
-            Weekday::Fri,
+    fn test_optimize_distributes_remaining_capacity_around_pinned() {
+        let data = single_person_data();
+        let assignments = vec![
+            pinned(project_assignment(1, 1, "2024-03-04", "2024-03-31"), 40.0),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
         ];
-        assert!(is_working_day(&date, &working_days));
+        // The pinned project is low priority, but still gets its 40% first
+        let requirements = vec![requirement(1, 64.0, 0), requirement(2, 160.0, 30)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert_eq!(result.pinned_calculations.len(), 1);
+        let pinned = &result.pinned_calculations[0];
+        assert_eq!(pinned.assignment_id, 1);
+        assert!((pinned.calculated_allocation_percentage - 40.0).abs() < 0.001);
+        assert!((pinned.calculated_effective_hours - 64.0).abs() < 0.001);
+
+        assert_eq!(result.calculations.len(), 1);
+        let other = &result.calculations[0];
+        assert!((other.calculated_allocation_percentage - 60.0).abs() < 0.001);
+        assert!((other.calculated_effective_hours - 96.0).abs() < 0.001);
+
+        // Only the blocker falls short; the pinned project is fully covered by the pin
+        assert_eq!(result.infeasible_projects.len(), 1);
+        assert_eq!(result.infeasible_projects[0].project_id, 2);
     }
 
     #[test]
-    fn test_is_working_day_saturday_on_weekday_schedule() {
-        let date = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(); // Saturday
-        let working_days = vec![
-            Weekday::Mon,
-            Weekday::Tue,
-            Weekday::Wed,
-            Weekday::Thu,
-            Weekday::Fri,
+    fn test_optimize_pinned_effective_hours_count_towards_requirement() {
+        let mut second = project_assignment(2, 1, "2024-03-04", "2024-03-31");
+        second.person_id = 2;
+        let data = PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, None), full_timer(2, None)],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let assignments = vec![
+            pinned(project_assignment(1, 1, "2024-03-04", "2024-03-31"), 50.0),
+            second,
         ];
-        assert!(!is_working_day(&date, &working_days));
+        let requirements = vec![requirement(1, 100.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        // 80h from the pin, only the missing 20h go to the second person
+        let other = &result.calculations[0];
+        assert!((other.calculated_effective_hours - 20.0).abs() < 0.001);
+        assert!(result.infeasible_projects.is_empty());
     }
 
     #[test]
-    fn test_is_working_day_saturday_on_weekend_schedule() {
-        let date = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(); // Saturday
-        let working_days = vec![Weekday::Sat, Weekday::Sun];
-        assert!(is_working_day(&date, &working_days));
+    fn test_optimize_warns_when_pinned_exceeds_full_time() {
+        let data = single_person_data();
+        let assignments = vec![
+            pinned(project_assignment(1, 1, "2024-03-04", "2024-03-31"), 70.0),
+            pinned(project_assignment(2, 2, "2024-03-18", "2024-03-31"), 50.0),
+        ];
+        let requirements = vec![requirement(1, 10.0, 10), requirement(2, 10.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert!(result.warnings.contains(
+            &"Pinned allocations of Person 1 add up to 120.0%, more than 100%".to_string()
+        ));
     }
 
     #[test]
-    fn test_is_working_day_empty_schedule() {
-        let date = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(); // Monday
-        let working_days: Vec<Weekday> = vec![];
-        assert!(!is_working_day(&date, &working_days));
+    fn test_optimize_disjoint_pins_do_not_warn() {
+        let data = single_person_data();
+        let assignments = vec![
+            pinned(project_assignment(1, 1, "2024-03-04", "2024-03-17"), 70.0),
+            pinned(project_assignment(2, 2, "2024-03-18", "2024-03-31"), 50.0),
+        ];
+        let requirements = vec![requirement(1, 10.0, 10), requirement(2, 10.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert!(result.warnings.is_empty());
     }
 
-    // Tests for calculate_assignment_effective_hours
-    #[test]
-    fn test_effective_hours_full_allocation_expert() {
-        // 40h available, 100% allocation, 0.8 productivity (expert)
-        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.8);
-        assert!((effective - 32.0).abs() < 0.001);
+    fn capped_person_data(max_utilization: f64) -> PeriodCapacityData {
+        let mut person = full_timer(1, None);
+        person.max_utilization_percentage = Some(max_utilization);
+        PeriodCapacityData::new(march_period(), vec![person], vec![], vec![], vec![], vec![])
+            .unwrap()
     }
 
     #[test]
-    fn test_effective_hours_half_allocation_proficient() {
-        // 40h available, 50% allocation, 0.5 productivity (proficient)
-        let effective = calculate_assignment_effective_hours(40.0, 50.0, 0.5);
-        assert!((effective - 10.0).abs() < 0.001);
+    fn test_every_strategy_respects_utilization_cap() {
+        let data = capped_person_data(85.0);
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 96.0, 30), requirement(2, 160.0, 0)];
+
+        for name in OPTIMIZATION_STRATEGIES {
+            let strategy =
+                optimization_strategy(Some(name), OptimizationOptions::default()).unwrap();
+            let result = strategy
+                .optimize(&data, &assignments, &requirements, &HashMap::new())
+                .unwrap();
+
+            // 60% covers the blocker, only 25% is left below the cap for the rest
+            let total: f64 = result
+                .calculations
+                .iter()
+                .map(|c| c.calculated_allocation_percentage)
+                .sum();
+            assert!((total - 85.0).abs() < 0.001, "{}", name);
+            assert!(result.infeasible_projects.iter().all(|s| s.project_id == 2));
+        }
     }
 
     #[test]
-    fn test_effective_hours_zero_allocation() {
-        let effective = calculate_assignment_effective_hours(40.0, 0.0, 0.8);
-        assert!((effective - 0.0).abs() < 0.001);
+    fn test_optimize_warns_when_pinned_exceeds_utilization_cap() {
+        let data = capped_person_data(80.0);
+        let assignments = vec![pinned(
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            90.0,
+        )];
+        let requirements = vec![requirement(1, 10.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert!(result.warnings.contains(
+            &"Pinned allocations of Person 1 add up to 90.0%, above their utilization cap of 80.0%"
+                .to_string()
+        ));
     }
 
     #[test]
-    fn test_effective_hours_zero_productivity() {
-        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.0);
-        assert!((effective - 0.0).abs() < 0.001);
+    fn test_utilization_cap_defaults_to_full_time() {
+        let mut person = full_timer(1, None);
+        assert!((max_utilization(&person) - 100.0).abs() < 0.001);
+        person.max_utilization_percentage = Some(85.0);
+        assert!((max_utilization(&person) - 85.0).abs() < 0.001);
+
+        assert!(!is_over_utilization(85.0, 85.0));
+        assert!(is_over_utilization(86.0, 85.0));
     }
 
+    // Tests for the optimization strategies
     #[test]
-    fn test_effective_hours_zero_available() {
-        let effective = calculate_assignment_effective_hours(0.0, 100.0, 0.8);
-        assert!((effective - 0.0).abs() < 0.001);
+    fn test_optimize_priority_first_uses_most_productive_people_first() {
+        let data = two_person_data();
+        let mut junior = person_assignment(1, 1, 1);
+        junior.productivity_factor = 0.5;
+        let assignments = vec![junior, person_assignment(2, 2, 1)];
+        let requirements = vec![requirement(1, 100.0, 10)];
+
+        let result =
+            optimize_priority_first(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert_eq!(result.strategy, "priority_first");
+        let allocation = |id: i64| {
+            result
+                .calculations
+                .iter()
+                .find(|c| c.assignment_id == id)
+                .unwrap()
+                .calculated_allocation_percentage
+        };
+        assert!((allocation(2) - 62.5).abs() < 0.001);
+        assert!((allocation(1) - 0.0).abs() < 0.001);
+        assert!(result.infeasible_projects.is_empty());
     }
 
     #[test]
-    fn test_effective_hours_real_scenario() {
-        // Realistic scenario: 35h/week available, 25% allocation, 0.65 productivity (advanced)
-        // Expected: 35 * 0.25 * 0.65 = 5.6875
-        let effective = calculate_assignment_effective_hours(35.0, 25.0, 0.65);
-        assert!((effective - 5.6875).abs() < 0.001);
+    fn test_optimize_even_spread_shares_person_within_tier() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 160.0, 10), requirement(2, 160.0, 10)];
+
+        // Priority first staffs the first project fully before the second
+        let priority_first =
+            optimize_priority_first(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+        assert_eq!(priority_first.infeasible_projects.len(), 1);
+        assert_eq!(priority_first.infeasible_projects[0].project_id, 2);
+
+        let result =
+            optimize_even_spread(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert_eq!(result.strategy, "even_spread");
+        assert_eq!(result.calculations.len(), 2);
+        for calc in &result.calculations {
+            assert!((calc.calculated_allocation_percentage - 50.0).abs() < 0.001);
+        }
+        assert_eq!(result.infeasible_projects.len(), 2);
     }
 
     #[test]
-    fn test_effective_hours_master_level() {
-        // Master level: 40h available, 100% allocation, 0.9 productivity
-        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.9);
-        assert!((effective - 36.0).abs() < 0.001);
+    fn test_optimize_proportional_shares_tier_by_demand() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        // 240h of demand on 160h: each project gets two thirds of what it needs
+        let requirements = vec![requirement(1, 160.0, 10), requirement(2, 80.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert!((allocation_of(&result, 1) - 66.667).abs() < 0.01);
+        assert!((allocation_of(&result, 2) - 33.333).abs() < 0.01);
+        assert_eq!(result.infeasible_projects.len(), 2);
     }
 
     #[test]
-    fn test_effective_hours_trainee_level() {
-        // Trainee level: 40h available, 100% allocation, 0.1 productivity
-        let effective = calculate_assignment_effective_hours(40.0, 100.0, 0.1);
-        assert!((effective - 4.0).abs() < 0.001);
+    fn test_optimize_proportional_returns_unused_share_to_tier() {
+        let data = two_person_data();
+        let assignments = vec![
+            person_assignment(1, 1, 1),
+            person_assignment(2, 1, 2),
+            person_assignment(3, 2, 2),
+        ];
+        let requirements = vec![requirement(1, 160.0, 10), requirement(2, 160.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        // Person 1 starts split 50/50; project 2 is covered with a third of them thanks
+        // to person 2, and the unused sixth goes to project 1 in the next round
+        assert!((allocation_of(&result, 1) - 66.667).abs() < 0.01);
+        assert!((allocation_of(&result, 2) - 33.333).abs() < 0.01);
+        assert!((allocation_of(&result, 3) - 66.667).abs() < 0.01);
+        assert_eq!(result.infeasible_projects.len(), 1);
+        assert_eq!(result.infeasible_projects[0].project_id, 1);
     }
 
-    // Tests for calculate_available_hours and optional overhead weighting
     #[test]
-    fn test_available_hours_no_deductions() {
-        // Base case: no absences, holidays, or overhead
-        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 0.0, 0.5);
-        assert!((available - 160.0).abs() < 0.001);
+    fn test_optimize_proportional_ignores_input_order() {
+        let data = two_person_data();
+        let assignments = vec![
+            person_assignment(1, 1, 1),
+            person_assignment(2, 1, 2),
+            person_assignment(3, 2, 2),
+            person_assignment(4, 2, 3),
+        ];
+        let requirements = vec![
+            requirement(1, 120.0, 10),
+            requirement(2, 150.0, 10),
+            requirement(3, 90.0, 10),
+        ];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        let mut reversed_assignments = assignments.clone();
+        reversed_assignments.reverse();
+        let mut reversed_requirements = requirements.clone();
+        reversed_requirements.reverse();
+        let reversed = optimize_proportional(
+            &data,
+            &reversed_assignments,
+            &reversed_requirements,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        for id in 1..=4 {
+            assert!((allocation_of(&result, id) - allocation_of(&reversed, id)).abs() < 1e-9);
+        }
     }
 
     #[test]
-    fn test_available_hours_with_absences_only() {
-        // 160h base, 16h absence (2 days at 8h/day)
-        let available = calculate_available_hours(160.0, 16.0, 0.0, 0.0, 0.0, 0.5);
-        assert!((available - 144.0).abs() < 0.001);
+    fn test_optimize_even_spread_gives_leftover_to_short_projects() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        // Project 1 needs less than its even share, project 2 takes the rest
+        let requirements = vec![requirement(1, 40.0, 10), requirement(2, 160.0, 10)];
+
+        let result =
+            optimize_even_spread(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        let allocation = |id: i64| {
+            result
+                .calculations
+                .iter()
+                .find(|c| c.assignment_id == id)
+                .unwrap()
+                .calculated_allocation_percentage
+        };
+        assert!((allocation(1) - 25.0).abs() < 0.001);
+        assert!((allocation(2) - 75.0).abs() < 0.001);
     }
 
-    #[test]
-    fn test_available_hours_with_holidays_only() {
-        // 160h base, 8h holiday (1 day)
-        let available = calculate_available_hours(160.0, 0.0, 8.0, 0.0, 0.0, 0.5);
-        assert!((available - 152.0).abs() < 0.001);
+    fn bounded(mut assignment: Assignment, min: Option<f64>, max: Option<f64>) -> Assignment {
+        assignment.min_allocation_percentage = min;
+        assignment.max_allocation_percentage = max;
+        assignment
     }
 
-    #[test]
-    fn test_available_hours_with_required_overhead_only() {
-        // 160h base, 10h required overhead
-        let available = calculate_available_hours(160.0, 0.0, 0.0, 10.0, 0.0, 0.5);
-        assert!((available - 150.0).abs() < 0.001);
+    fn allocation_of(result: &OptimizationResult, assignment_id: i64) -> f64 {
+        result
+            .calculations
+            .iter()
+            .chain(&result.pinned_calculations)
+            .find(|c| c.assignment_id == assignment_id)
+            .unwrap()
+            .calculated_allocation_percentage
     }
 
     #[test]
-    fn test_available_hours_with_optional_overhead_50_percent_weight() {
-        // 160h base, 20h optional overhead at 50% weight = 10h deduction
-        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 20.0, 0.5);
-        assert!((available - 150.0).abs() < 0.001);
+    fn test_every_strategy_reserves_minimum_allocation() {
+        let data = single_person_data();
+        let assignments = vec![
+            bounded(
+                project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+                Some(20.0),
+                None,
+            ),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        // The maintenance project needs little and has low priority, but keeps its 20%
+        let requirements = vec![requirement(1, 16.0, 0), requirement(2, 160.0, 30)];
+
+        for name in OPTIMIZATION_STRATEGIES {
+            let strategy =
+                optimization_strategy(Some(name), OptimizationOptions::default()).unwrap();
+            let result = strategy
+                .optimize(&data, &assignments, &requirements, &HashMap::new())
+                .unwrap();
+
+            assert!((allocation_of(&result, 1) - 20.0).abs() < 0.001, "{}", name);
+            assert!((allocation_of(&result, 2) - 80.0).abs() < 0.001, "{}", name);
+            assert!(result.unsatisfied_constraints.is_empty(), "{}", name);
+        }
     }
 
     #[test]
-    fn test_available_hours_with_optional_overhead_zero_weight() {
-        // 160h base, 20h optional overhead at 0% weight = 0h deduction
-        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 20.0, 0.0);
-        assert!((available - 160.0).abs() < 0.001);
+    fn test_minimum_of_project_without_requirement_reserves_nothing() {
+        let data = single_person_data();
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            bounded(
+                project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+                Some(50.0),
+                None,
+            ),
+        ];
+        // Project 2 has no requirement in the period
+        let requirements = vec![requirement(1, 160.0, 10)];
+
+        for name in OPTIMIZATION_STRATEGIES {
+            let strategy =
+                optimization_strategy(Some(name), OptimizationOptions::default()).unwrap();
+            let result = strategy
+                .optimize(&data, &assignments, &requirements, &HashMap::new())
+                .unwrap();
+
+            assert!(
+                (allocation_of(&result, 1) - 100.0).abs() < 0.001,
+                "{}",
+                name
+            );
+            assert_eq!(result.skipped_assignment_ids, vec![2], "{}", name);
+            assert!(result.infeasible_projects.is_empty(), "{}", name);
+        }
     }
 
     #[test]
-    fn test_available_hours_with_optional_overhead_full_weight() {
-        // 160h base, 20h optional overhead at 100% weight = 20h deduction
-        let available = calculate_available_hours(160.0, 0.0, 0.0, 0.0, 20.0, 1.0);
-        assert!((available - 140.0).abs() < 0.001);
+    fn test_every_strategy_caps_at_maximum_allocation() {
+        let data = single_person_data();
+        let assignments = vec![
+            bounded(
+                project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+                None,
+                Some(50.0),
+            ),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 160.0, 30), requirement(2, 160.0, 0)];
+
+        for name in OPTIMIZATION_STRATEGIES {
+            let strategy =
+                optimization_strategy(Some(name), OptimizationOptions::default()).unwrap();
+            let result = strategy
+                .optimize(&data, &assignments, &requirements, &HashMap::new())
+                .unwrap();
+
+            assert!((allocation_of(&result, 1) - 50.0).abs() < 0.001, "{}", name);
+            assert!((allocation_of(&result, 2) - 50.0).abs() < 0.001, "{}", name);
+            assert!(result.unsatisfied_constraints.is_empty(), "{}", name);
+        }
     }
 
     #[test]
-    fn test_available_hours_combined_all_deductions() {
-        // Realistic scenario:
-        // - 160h base (4 weeks at 40h/week)
-        // - 16h absence (2 vacation days)
-        // - 8h holidays (1 public holiday)
-        // - 8h required overhead (team meetings)
-        // - 4h optional overhead at 50% = 2h deduction (optional training)
-        // Expected: 160 - 16 - 8 - 8 - 2 = 126h
-        let available = calculate_available_hours(160.0, 16.0, 8.0, 8.0, 4.0, 0.5);
-        assert!((available - 126.0).abs() < 0.001);
+    fn test_optimize_reports_unsatisfied_allocation_bounds() {
+        let data = single_person_data();
+        let assignments = vec![
+            // Pinned above its own maximum
+            bounded(
+                pinned(project_assignment(1, 1, "2024-03-04", "2024-03-31"), 70.0),
+                None,
+                Some(50.0),
+            ),
+            // Wants 40%, but the pin leaves only 30%
+            bounded(
+                project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+                Some(40.0),
+                None,
+            ),
+        ];
+        let requirements = vec![requirement(1, 10.0, 10), requirement(2, 10.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        assert_eq!(result.unsatisfied_constraints.len(), 2);
+        let pin = &result.unsatisfied_constraints[0];
+        assert_eq!(pin.assignment_id, 1);
+        assert_eq!(pin.bound, AllocationBound::Maximum);
+        assert!((pin.bound_percentage - 50.0).abs() < 0.001);
+        assert!((pin.allocation_percentage - 70.0).abs() < 0.001);
+        let minimum = &result.unsatisfied_constraints[1];
+        assert_eq!(minimum.assignment_id, 2);
+        assert_eq!(minimum.bound, AllocationBound::Minimum);
+        assert!((minimum.allocation_percentage - 30.0).abs() < 0.001);
+
+        assert!(result.warnings.contains(
+            &"Assignment ID 2 is allocated 30.0%, below its minimum of 40.0%".to_string()
+        ));
     }
 
+    // Tests for optimization previews
     #[test]
-    fn test_available_hours_mixed_overhead_types() {
-        // Test with both required and optional overhead
-        // 160h base, 20h required, 10h optional at 50% = 5h
-        // Expected: 160 - 20 - 5 = 135h
-        let available = calculate_available_hours(160.0, 0.0, 0.0, 20.0, 10.0, 0.5);
-        assert!((available - 135.0).abs() < 0.001);
+    fn test_optimization_preview_diffs_against_stored_calculations() {
+        let data = single_person_data();
+        let mut calculated = project_assignment(1, 1, "2024-03-04", "2024-03-31");
+        calculated.calculated_allocation_percentage = Some(100.0);
+        calculated.calculated_effective_hours = Some(160.0);
+        let assignments = vec![
+            calculated,
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 80.0, 10), requirement(2, 80.0, 10)];
+        let project_names = HashMap::from([(1, "Beta".to_string()), (2, "Alpha".to_string())]);
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &project_names).unwrap();
+        let preview = optimization_preview(1, String::new(), &assignments, &project_names, result);
+
+        assert_eq!(preview.assignment_diffs.len(), 2);
+        let first = &preview.assignment_diffs[0];
+        assert_eq!(first.old_allocation_percentage, Some(100.0));
+        assert!((first.new_allocation_percentage.unwrap() - 50.0).abs() < 0.001);
+        let second = &preview.assignment_diffs[1];
+        assert_eq!(second.old_effective_hours, None);
+        assert!((second.new_effective_hours.unwrap() - 80.0).abs() < 0.001);
+
+        // Sorted by project name
+        assert_eq!(preview.project_deltas[0].project_name, "Alpha");
+        assert!((preview.project_deltas[0].delta_hours - 80.0).abs() < 0.001);
+        assert_eq!(preview.project_deltas[1].project_name, "Beta");
+        assert!((preview.project_deltas[1].delta_hours + 80.0).abs() < 0.001);
     }
 
     #[test]
-    fn test_available_hours_floor_at_zero() {
-        // Deductions exceed base hours - should floor at 0
-        let available = calculate_available_hours(100.0, 50.0, 30.0, 30.0, 0.0, 0.5);
-        assert!((available - 0.0).abs() < 0.001);
+    fn test_hash_records_is_stable_across_builds() {
+        // Stored runs compare fingerprints, so a known input must keep its hash
+        assert_eq!(hash_records(&(1, "Alpha")).unwrap(), 0x756c_916d_d3d0_29cc);
     }
 
     #[test]
-    fn test_available_hours_optional_weight_boundary_values() {
-        // Test weight at edge values
-        let base = 100.0;
-        let optional = 40.0;
+    fn test_optimization_fingerprint_tracks_input_changes() {
+        let data = single_person_data();
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let requirements = vec![requirement(1, 80.0, 10)];
 
-        // Weight 0.0: no deduction from optional
-        assert!(
-            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.0) - 100.0).abs() < 0.001
+        let fingerprint =
+            optimization_fingerprint(&data, &assignments, &requirements, &[]).unwrap();
+        assert_eq!(
+            fingerprint,
+            optimization_fingerprint(&single_person_data(), &assignments, &requirements, &[])
+                .unwrap()
         );
 
-        // Weight 0.25: 10h deduction
-        assert!(
-            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.25) - 90.0).abs() < 0.001
+        // A stored calculation changes the fingerprint
+        let mut calculated = assignments.clone();
+        calculated[0].calculated_allocation_percentage = Some(50.0);
+        assert_ne!(
+            fingerprint,
+            optimization_fingerprint(&data, &calculated, &requirements, &[]).unwrap()
         );
 
-        // Weight 0.5 (default): 20h deduction
-        assert!(
-            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.5) - 80.0).abs() < 0.001
+        // So does a change of the capacity data
+        let absent = PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, None)],
+            vec![absence("2024-03-05", "2024-03-05", None, None)],
+            vec![],
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        assert_ne!(
+            fingerprint,
+            optimization_fingerprint(&absent, &assignments, &requirements, &[]).unwrap()
         );
 
-        // Weight 0.75: 30h deduction
-        assert!(
-            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 0.75) - 70.0).abs() < 0.001
+        // And a new dependency between projects
+        let dependency = ProjectDependency {
+            id: 1,
+            project_id: 2,
+            depends_on_project_id: 1,
+            lag_days: 0,
+            created_at: String::new(),
+        };
+        assert_ne!(
+            fingerprint,
+            optimization_fingerprint(&data, &assignments, &requirements, &[dependency]).unwrap()
         );
+    }
 
-        // Weight 1.0: full 40h deduction
+    // Tests for the optimization run history
+    fn stored_run(id: i64, calculations: Vec<OptimizationRunCalculation>) -> OptimizationRun {
+        OptimizationRun {
+            id,
+            planning_period_id: 1,
+            strategy: PROPORTIONAL_STRATEGY.to_string(),
+            options: OptimizationOptions::default(),
+            inputs: optimization_inputs_summary(&single_person_data(), &[], &[], &[]).unwrap(),
+            calculations,
+            infeasible_projects: vec![],
+            warnings: vec![],
+            created_at: String::new(),
+        }
+    }
+
+    fn run_calculation(
+        assignment_id: i64,
+        project_id: i64,
+        pct: f64,
+    ) -> OptimizationRunCalculation {
+        OptimizationRunCalculation {
+            assignment_id,
+            person_id: 1,
+            project_id,
+            allocation_percentage: pct,
+            effective_hours: pct * 1.6,
+            is_pinned: false,
+            calculated_start_date: None,
+        }
+    }
+
+    #[test]
+    fn test_optimization_run_calculations_flag_pinned_assignments() {
+        let data = single_person_data();
+        let assignments = vec![
+            pinned(project_assignment(1, 1, "2024-03-04", "2024-03-31"), 40.0),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+        ];
+        let requirements = vec![requirement(1, 64.0, 10), requirement(2, 40.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+        let calculations = optimization_run_calculations(&result, &assignments);
+
+        assert_eq!(calculations.len(), 2);
+        let pinned = calculations.iter().find(|c| c.assignment_id == 1).unwrap();
+        assert!(pinned.is_pinned);
+        assert_eq!(pinned.project_id, 1);
         assert!(
-            (calculate_available_hours(base, 0.0, 0.0, 0.0, optional, 1.0) - 60.0).abs() < 0.001
+            !calculations
+                .iter()
+                .find(|c| c.assignment_id == 2)
+                .unwrap()
+                .is_pinned
         );
+
+        let summary = optimization_inputs_summary(&data, &assignments, &requirements, &[]).unwrap();
+        assert_eq!(summary.assignments, 2);
+        assert_eq!(summary.pinned_assignments, 1);
+        assert!((summary.required_hours - 104.0).abs() < 0.001);
     }
 
     #[test]
-    fn test_default_optional_weight_constant() {
-        // Verify the default constant is 0.5 (50%)
-        assert!((DEFAULT_OPTIONAL_WEIGHT - 0.5).abs() < 0.001);
+    fn test_optimization_run_comparison() {
+        let base = stored_run(
+            1,
+            vec![run_calculation(1, 1, 100.0), run_calculation(2, 2, 0.0)],
+        );
+        let other = stored_run(
+            2,
+            vec![run_calculation(2, 2, 50.0), run_calculation(3, 1, 50.0)],
+        );
+
+        let comparison = optimization_run_comparison(&base, &other, &HashMap::new());
+
+        assert_eq!(comparison.base_run_id, 1);
+        assert_eq!(comparison.other_run_id, 2);
+        assert_eq!(comparison.assignment_diffs.len(), 3);
+        // Assignment 1 is missing from the newer run, assignment 3 from the older one
+        let removed = &comparison.assignment_diffs[0];
+        assert_eq!(removed.old_allocation_percentage, Some(100.0));
+        assert_eq!(removed.new_allocation_percentage, None);
+        let added = &comparison.assignment_diffs[2];
+        assert_eq!(added.old_allocation_percentage, None);
+        assert_eq!(added.new_allocation_percentage, Some(50.0));
+
+        let project_1 = &comparison.project_deltas[0];
+        assert_eq!(project_1.project_name, "Project 1");
+        assert!((project_1.delta_hours + 80.0).abs() < 0.001);
+        let project_2 = &comparison.project_deltas[1];
+        assert!((project_2.delta_hours - 80.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_optimization_preview_clears_skipped_assignments() {
+        let data = single_person_data();
+        // Project 2 has no requirement, so its stale calculation is cleared
+        let mut stale = project_assignment(2, 2, "2024-03-04", "2024-03-31");
+        stale.calculated_allocation_percentage = Some(30.0);
+        stale.calculated_effective_hours = Some(48.0);
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31"), stale];
+        let requirements = vec![requirement(1, 80.0, 10)];
+
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+        assert_eq!(result.skipped_assignment_ids, vec![2]);
+
+        let preview = optimization_preview(1, String::new(), &assignments, &HashMap::new(), result);
+        let cleared = preview
+            .assignment_diffs
+            .iter()
+            .find(|diff| diff.assignment_id == 2)
+            .unwrap();
+        assert_eq!(cleared.old_allocation_percentage, Some(30.0));
+        assert_eq!(cleared.new_allocation_percentage, None);
+        let project_2 = preview
+            .project_deltas
+            .iter()
+            .find(|delta| delta.project_id == 2)
+            .unwrap();
+        assert!((project_2.delta_hours + 48.0).abs() < 0.001);
     }
 }
@@ -0,0 +1,349 @@
+use super::{
+    effective_hours_by_project, planning_period_bounds, OptimizationResult, OptimizationStrategy,
+    PeriodCapacityData,
+};
+use crate::models::{Assignment, ProjectRequirement};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Unmet hours below which nothing is carried into the next period
+const CARRY_OVER_TOLERANCE: f64 = 1e-6;
+
+/// Inputs of one planning period of a sequence
+pub struct PeriodSequenceInputs<'a> {
+    pub data: &'a PeriodCapacityData,
+    pub assignments: &'a [Assignment],
+    pub requirements: &'a [ProjectRequirement],
+}
+
+/// Result of optimizing consecutive planning periods with carry-over of unmet demand
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultiPeriodOptimizationResult {
+    pub strategy: String,
+    pub periods: Vec<PeriodOptimizationResult>, // In date order
+    pub projects: Vec<ProjectProgress>,         // Sorted by project name
+}
+
+/// Optimization of one period of the sequence
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeriodOptimizationResult {
+    pub planning_period_id: i64,
+    pub planning_period_name: Option<String>,
+    pub carried_in: Vec<CarriedDemand>, // Added to this period's requirements
+    pub result: OptimizationResult,
+}
+
+/// Unmet hours of a project moved from one period into the next
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CarriedDemand {
+    pub project_id: i64,
+    pub project_name: String,
+    pub hours: f64,
+}
+
+/// Cumulative progress of a project across the sequence
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectProgress {
+    pub project_id: i64,
+    pub project_name: String,
+    pub periods: Vec<ProjectPeriodProgress>, // Periods with a requirement or carried demand
+    pub unmet_hours: f64,                    // Still missing after the last period
+}
+
+/// Progress of a project in one period of the sequence
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectPeriodProgress {
+    pub planning_period_id: i64,
+    pub required_hours: f64,   // The period's own requirement
+    pub carried_in_hours: f64, // Unmet in earlier periods
+    pub effective_hours: f64,
+    pub carried_out_hours: f64, // Unmet, moved on to the next period
+    pub cumulative_required_hours: f64,
+    pub cumulative_effective_hours: f64,
+}
+
+/// Order the periods of a sequence by date and make sure each starts the day after the
+/// previous one ends
+///
+/// Overlapping periods and gaps between periods are rejected, since unmet demand is only
+/// carried over between adjacent periods.
+pub fn order_period_sequence(periods: &mut [PeriodSequenceInputs]) -> Result<(), String> {
+    if periods.is_empty() {
+        return Err("At least one planning period is required".to_string());
+    }
+    periods.sort_by(|a, b| {
+        a.data
+            .planning_period
+            .start_date
+            .cmp(&b.data.planning_period.start_date)
+    });
+
+    for pair in periods.windows(2) {
+        let (previous, next) = (&pair[0].data.planning_period, &pair[1].data.planning_period);
+        let (_, previous_end) = planning_period_bounds(previous)?;
+        let (next_start, _) = planning_period_bounds(next)?;
+        if next_start <= previous_end {
+            return Err(format!(
+                "Planning periods {} and {} overlap",
+                previous.id, next.id
+            ));
+        }
+        if next_start > previous_end + Duration::days(1) {
+            return Err(format!(
+                "Planning periods {} and {} are not consecutive, there is a gap between them",
+                previous.id, next.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Optimize consecutive planning periods, rolling unmet demand into the next period
+///
+/// Each period is optimized with the strategy as usual, after the hours a project missed
+/// in the periods before have been added to its requirement. Demand of a project without
+/// a requirement in a period is carried on to the next one. The periods must already be
+/// in order, see `order_period_sequence`.
+pub fn optimize_period_sequence(
+    periods: &[PeriodSequenceInputs],
+    project_names: &HashMap<i64, String>,
+    strategy: &dyn OptimizationStrategy,
+) -> Result<MultiPeriodOptimizationResult, String> {
+    let project_name = |project_id: i64| {
+        project_names
+            .get(&project_id)
+            .cloned()
+            .unwrap_or_else(|| format!("Project {}", project_id))
+    };
+
+    let mut carry: BTreeMap<i64, f64> = BTreeMap::new();
+    let mut totals: BTreeMap<i64, (f64, f64)> = BTreeMap::new(); // (required, effective)
+    let mut progress: BTreeMap<i64, Vec<ProjectPeriodProgress>> = BTreeMap::new();
+    let mut period_results = Vec::with_capacity(periods.len());
+
+    for period in periods {
+        let planning_period = &period.data.planning_period;
+
+        // Add the unmet demand of earlier periods to this period's requirements
+        let mut requirements = period.requirements.to_vec();
+        let mut carried_in = Vec::new();
+        for requirement in &mut requirements {
+            if let Some(hours) = carry.remove(&requirement.project_id) {
+                requirement.required_hours += hours;
+                carried_in.push(CarriedDemand {
+                    project_id: requirement.project_id,
+                    project_name: project_name(requirement.project_id),
+                    hours,
+                });
+            }
+        }
+        carried_in.sort_by_key(|demand| demand.project_id);
+
+        let mut result = strategy.optimize(
+            period.data,
+            period.assignments,
+            &requirements,
+            project_names,
+        )?;
+        for demand in &carried_in {
+            result.warnings.push(format!(
+                "Carried over {:.1}h of unmet demand of project {} from earlier planning periods",
+                demand.hours, demand.project_id
+            ));
+        }
+        for (project_id, hours) in &carry {
+            result.warnings.push(format!(
+                "Project {} has no requirement in planning period {}, carrying {:.1}h on",
+                project_id, planning_period.id, hours
+            ));
+        }
+
+        let effective_hours = effective_hours_by_project(period.assignments, &result);
+        let carried_in_by_project: HashMap<i64, f64> = carried_in
+            .iter()
+            .map(|demand| (demand.project_id, demand.hours))
+            .collect();
+        for requirement in period.requirements {
+            let project_id = requirement.project_id;
+            let effective = effective_hours.get(&project_id).copied().unwrap_or(0.0);
+            let carried_in_hours = carried_in_by_project
+                .get(&project_id)
+                .copied()
+                .unwrap_or(0.0);
+            let unmet = requirement.required_hours + carried_in_hours - effective;
+            let carried_out_hours = if unmet > CARRY_OVER_TOLERANCE {
+                unmet
+            } else {
+                0.0
+            };
+            if carried_out_hours > 0.0 {
+                carry.insert(project_id, carried_out_hours);
+            }
+
+            let total = totals.entry(project_id).or_default();
+            total.0 += requirement.required_hours;
+            total.1 += effective;
+            progress
+                .entry(project_id)
+                .or_default()
+                .push(ProjectPeriodProgress {
+                    planning_period_id: planning_period.id,
+                    required_hours: requirement.required_hours,
+                    carried_in_hours,
+                    effective_hours: effective,
+                    carried_out_hours,
+                    cumulative_required_hours: total.0,
+                    cumulative_effective_hours: total.1,
+                });
+        }
+
+        period_results.push(PeriodOptimizationResult {
+            planning_period_id: planning_period.id,
+            planning_period_name: planning_period.name.clone(),
+            carried_in,
+            result,
+        });
+    }
+
+    let mut projects: Vec<ProjectProgress> = progress
+        .into_iter()
+        .map(|(project_id, periods)| ProjectProgress {
+            project_id,
+            project_name: project_name(project_id),
+            periods,
+            unmet_hours: carry.get(&project_id).copied().unwrap_or(0.0),
+        })
+        .collect();
+    projects.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+
+    Ok(MultiPeriodOptimizationResult {
+        strategy: strategy.name().to_string(),
+        periods: period_results,
+        projects,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+    use crate::capacity::ProportionalStrategy;
+    use crate::models::PlanningPeriod;
+
+    fn april_period() -> PlanningPeriod {
+        PlanningPeriod {
+            id: 2,
+            name: None,
+            start_date: "2024-04-01".to_string(),
+            end_date: "2024-04-28".to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    fn period_data(period: PlanningPeriod) -> PeriodCapacityData {
+        PeriodCapacityData::new(
+            period,
+            vec![full_timer(1, None)],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_period_sequence_carries_unmet_demand() {
+        let (march, april) = (period_data(march_period()), period_data(april_period()));
+        let march_assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let mut april_assignment = project_assignment(2, 1, "2024-04-01", "2024-04-28");
+        april_assignment.planning_period_id = 2;
+        let april_assignments = vec![april_assignment];
+        let march_requirements = vec![requirement(1, 200.0, 10)];
+        let mut april_requirement = requirement(1, 100.0, 10);
+        april_requirement.planning_period_id = 2;
+        let april_requirements = vec![april_requirement];
+
+        // Passed out of order on purpose
+        let mut periods = vec![
+            PeriodSequenceInputs {
+                data: &april,
+                assignments: &april_assignments,
+                requirements: &april_requirements,
+            },
+            PeriodSequenceInputs {
+                data: &march,
+                assignments: &march_assignments,
+                requirements: &march_requirements,
+            },
+        ];
+        order_period_sequence(&mut periods).unwrap();
+        let result =
+            optimize_period_sequence(&periods, &HashMap::new(), &ProportionalStrategy).unwrap();
+
+        // March misses 40h, which April covers on top of its own 100h
+        assert_eq!(result.periods[0].planning_period_id, 1);
+        assert!(result.periods[0].carried_in.is_empty());
+        assert_eq!(result.periods[1].carried_in.len(), 1);
+        assert!((result.periods[1].carried_in[0].hours - 40.0).abs() < 0.001);
+        assert!(result.periods[1].result.infeasible_projects.is_empty());
+
+        let progress = &result.projects[0];
+        assert_eq!(progress.periods.len(), 2);
+        assert!((progress.periods[0].effective_hours - 160.0).abs() < 0.001);
+        assert!((progress.periods[0].carried_out_hours - 40.0).abs() < 0.001);
+        assert!((progress.periods[1].effective_hours - 140.0).abs() < 0.001);
+        assert!((progress.periods[1].cumulative_required_hours - 300.0).abs() < 0.001);
+        assert!((progress.periods[1].cumulative_effective_hours - 300.0).abs() < 0.001);
+        assert!((progress.unmet_hours - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_period_sequence_rejects_overlapping_periods() {
+        let march = period_data(march_period());
+        let mut overlapping_period = april_period();
+        overlapping_period.start_date = "2024-03-25".to_string();
+        let overlapping = period_data(overlapping_period);
+
+        let mut periods = vec![
+            PeriodSequenceInputs {
+                data: &march,
+                assignments: &[],
+                requirements: &[],
+            },
+            PeriodSequenceInputs {
+                data: &overlapping,
+                assignments: &[],
+                requirements: &[],
+            },
+        ];
+        assert!(order_period_sequence(&mut periods).is_err());
+    }
+
+    #[test]
+    fn test_period_sequence_rejects_gaps() {
+        let march = period_data(march_period());
+        let mut later_period = april_period();
+        later_period.start_date = "2024-04-02".to_string();
+        let later = period_data(later_period);
+
+        let mut periods = vec![
+            PeriodSequenceInputs {
+                data: &march,
+                assignments: &[],
+                requirements: &[],
+            },
+            PeriodSequenceInputs {
+                data: &later,
+                assignments: &[],
+                requirements: &[],
+            },
+        ];
+        let error = order_period_sequence(&mut periods).unwrap_err();
+        assert_eq!(
+            error,
+            "Planning periods 1 and 2 are not consecutive, there is a gap between them"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use crate::models::{Assignment, PersonSkill, ProjectSkillRequirement};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A proficiency preset and the productivity factor it stands for
+///
+/// The frontend reads the presets through the `list_proficiency_levels` command.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ProficiencyLevel {
+    pub value: &'static str,
+    pub factor: f64,
+    pub label: &'static str,
+    pub description: &'static str,
+}
+
+/// Proficiency levels from most to least productive
+///
+/// Even experts don't reach 1.0, as meetings, planning and context switching take their share.
+pub const PROFICIENCY_LEVELS: [ProficiencyLevel; 7] = [
+    ProficiencyLevel {
+        value: "master",
+        factor: 0.9,
+        label: "Master",
+        description: "Subject matter expert with deep mastery. Can architect solutions, mentor \
+              others, and maintain highest productivity.",
+    },
+    ProficiencyLevel {
+        value: "expert",
+        factor: 0.8,
+        label: "Expert",
+        description: "Deep expertise and experience. Works independently, rarely needs guidance, \
+              high efficiency.",
+    },
+    ProficiencyLevel {
+        value: "advanced",
+        factor: 0.65,
+        label: "Advanced",
+        description: "Strong knowledge and experience. Works independently most of the time, \
+              occasional guidance needed.",
+    },
+    ProficiencyLevel {
+        value: "proficient",
+        factor: 0.5,
+        label: "Proficient",
+        description: "Solid understanding and competence. Baseline productivity. Regular but \
+              manageable guidance needed.",
+    },
+    ProficiencyLevel {
+        value: "intermediate",
+        factor: 0.35,
+        label: "Intermediate",
+        description: "Developing skills and knowledge. Regular guidance and code reviews \
+              required. Learning curve impacts output.",
+    },
+    ProficiencyLevel {
+        value: "beginner",
+        factor: 0.2,
+        label: "Beginner",
+        description: "Basic familiarity only. Frequent support and mentoring required. \
+              Significant learning overhead.",
+    },
+    ProficiencyLevel {
+        value: "trainee",
+        factor: 0.1,
+        label: "Trainee",
+        description: "Shadowing/training mode. Little to no prior experience. Minimal direct \
+              output, focused on learning.",
+    },
+];
+
+/// Productivity assumed for a required skill the person doesn't have (trainee level)
+pub const MISSING_SKILL_PRODUCTIVITY_FACTOR: f64 = 0.1;
+
+/// Productivity factor of a proficiency level, None for unknown levels
+pub fn proficiency_factor(proficiency: &str) -> Option<f64> {
+    PROFICIENCY_LEVELS
+        .iter()
+        .find(|level| level.value == proficiency)
+        .map(|level| level.factor)
+}
+
+/// How a person's proficiency in one of the project's skills counts toward the suggestion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillMatch {
+    pub skill_id: i64,
+    pub skill_name: String,
+    pub required_hours: f64,
+    pub proficiency: Option<String>, // None when the person doesn't have the skill
+    pub productivity_factor: f64,
+}
+
+/// Productivity factor derived from a person's proficiency in a project's skills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivitySuggestion {
+    pub person_id: i64,
+    pub project_id: i64,
+    pub productivity_factor: f64, // Average over the skills, weighted by their required hours
+    pub skills: Vec<SkillMatch>,
+}
+
+/// Staffing of one skill a project requires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillStaffing {
+    pub skill_id: i64,
+    pub skill_name: String,
+    pub required_hours: f64,
+    pub effective_hours: f64, // From assigned people who have the skill
+    pub staffing_percentage: f64,
+    pub is_viable: bool,
+    pub shortfall: f64,
+}
+
+/// Skills, proficiencies and skill requirements of a planning period
+#[derive(Debug, Default)]
+pub struct SkillData {
+    pub skill_names: HashMap<i64, String>,
+    pub person_skills: Vec<PersonSkill>,
+    pub requirements: Vec<ProjectSkillRequirement>,
+}
+
+impl SkillData {
+    fn skill_name(&self, skill_id: i64) -> String {
+        self.skill_names
+            .get(&skill_id)
+            .cloned()
+            .unwrap_or_else(|| format!("Skill {}", skill_id))
+    }
+
+    /// Skill requirements of a project, by skill name
+    fn project_requirements(&self, project_id: i64) -> Vec<&ProjectSkillRequirement> {
+        let mut requirements: Vec<&ProjectSkillRequirement> = self
+            .requirements
+            .iter()
+            .filter(|requirement| requirement.project_id == project_id)
+            .filter(|requirement| requirement.required_hours > 0.0)
+            .collect();
+        requirements.sort_by_key(|requirement| self.skill_name(requirement.skill_id));
+        requirements
+    }
+
+    /// Proficiency level of a person in a skill, if it is a known level
+    fn proficiency(&self, person_id: i64, skill_id: i64) -> Option<&str> {
+        self.person_skills
+            .iter()
+            .find(|skill| skill.person_id == person_id && skill.skill_id == skill_id)
+            .map(|skill| skill.proficiency.as_str())
+            .filter(|proficiency| proficiency_factor(proficiency).is_some())
+    }
+
+    /// Productivity factor for a person on a project; None when the project requires no skills
+    pub fn productivity_suggestion(
+        &self,
+        person_id: i64,
+        project_id: i64,
+    ) -> Option<ProductivitySuggestion> {
+        let requirements = self.project_requirements(project_id);
+        let total_hours: f64 = requirements.iter().map(|r| r.required_hours).sum();
+        if total_hours <= 0.0 {
+            return None;
+        }
+
+        let skills: Vec<SkillMatch> = requirements
+            .iter()
+            .map(|requirement| {
+                let proficiency = self.proficiency(person_id, requirement.skill_id);
+                SkillMatch {
+                    skill_id: requirement.skill_id,
+                    skill_name: self.skill_name(requirement.skill_id),
+                    required_hours: requirement.required_hours,
+                    proficiency: proficiency.map(str::to_string),
+                    productivity_factor: proficiency
+                        .and_then(proficiency_factor)
+                        .unwrap_or(MISSING_SKILL_PRODUCTIVITY_FACTOR),
+                }
+            })
+            .collect();
+        let weighted_factor: f64 = skills
+            .iter()
+            .map(|skill| skill.productivity_factor * skill.required_hours)
+            .sum();
+
+        Some(ProductivitySuggestion {
+            person_id,
+            project_id,
+            productivity_factor: weighted_factor / total_hours,
+            skills,
+        })
+    }
+
+    /// Effective hours per required skill of a project
+    ///
+    /// Each assignment's effective hours go to the required skills the person has, split by
+    /// the hours the skills require. Hours of people without any of the skills count for none.
+    pub fn project_skill_staffing(
+        &self,
+        project_id: i64,
+        assignments: &[&Assignment],
+    ) -> Vec<SkillStaffing> {
+        let requirements = self.project_requirements(project_id);
+        let mut effective_hours: HashMap<i64, f64> = HashMap::new();
+
+        for assignment in assignments {
+            let person_requirements: Vec<&&ProjectSkillRequirement> = requirements
+                .iter()
+                .filter(|r| self.proficiency(assignment.person_id, r.skill_id).is_some())
+                .collect();
+            let person_required_hours: f64 =
+                person_requirements.iter().map(|r| r.required_hours).sum();
+            if person_required_hours <= 0.0 {
+                continue;
+            }
+            let hours = assignment.calculated_effective_hours.unwrap_or(0.0);
+            for requirement in person_requirements {
+                *effective_hours.entry(requirement.skill_id).or_default() +=
+                    hours * requirement.required_hours / person_required_hours;
+            }
+        }
+
+        requirements
+            .iter()
+            .map(|requirement| {
+                let effective_hours = effective_hours
+                    .get(&requirement.skill_id)
+                    .copied()
+                    .unwrap_or(0.0);
+                let staffing_percentage = effective_hours / requirement.required_hours * 100.0;
+                // Same tolerance as the project staffing (99.95% rounds to 100.0%)
+                let is_viable = staffing_percentage >= 99.95;
+                SkillStaffing {
+                    skill_id: requirement.skill_id,
+                    skill_name: self.skill_name(requirement.skill_id),
+                    required_hours: requirement.required_hours,
+                    effective_hours,
+                    staffing_percentage,
+                    is_viable,
+                    shortfall: if is_viable {
+                        0.0
+                    } else {
+                        requirement.required_hours - effective_hours
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+
+    fn person_skill(person_id: i64, skill_id: i64, proficiency: &str) -> PersonSkill {
+        PersonSkill {
+            id: 0,
+            person_id,
+            skill_id,
+            proficiency: proficiency.to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    fn skill_requirement(skill_id: i64, required_hours: f64) -> ProjectSkillRequirement {
+        ProjectSkillRequirement {
+            id: 0,
+            project_id: 1,
+            planning_period_id: 1,
+            skill_id,
+            required_hours,
+            created_at: String::new(),
+        }
+    }
+
+    fn skill_data() -> SkillData {
+        SkillData {
+            skill_names: HashMap::from([(1, "Rust".to_string()), (2, "React".to_string())]),
+            person_skills: vec![
+                person_skill(1, 1, "expert"),
+                person_skill(2, 2, "proficient"),
+            ],
+            requirements: vec![skill_requirement(1, 60.0), skill_requirement(2, 40.0)],
+        }
+    }
+
+    #[test]
+    fn test_productivity_suggestion_weights_skills_by_required_hours() {
+        let skills = skill_data();
+
+        // Expert in Rust (0.8) for 60h, lacks React (trainee 0.1) for 40h
+        let suggestion = skills.productivity_suggestion(1, 1).unwrap();
+        assert!((suggestion.productivity_factor - 0.52).abs() < 0.001);
+        let react = suggestion
+            .skills
+            .iter()
+            .find(|skill| skill.skill_name == "React")
+            .unwrap();
+        assert_eq!(react.proficiency, None);
+
+        // Projects without skill requirements have nothing to suggest
+        assert!(skills.productivity_suggestion(1, 2).is_none());
+    }
+
+    #[test]
+    fn test_project_skill_staffing_reports_missing_skill_hours() {
+        let skills = skill_data();
+        let mut rust_developer = project_assignment(1, 1, "2024-03-04", "2024-03-31");
+        rust_developer.calculated_effective_hours = Some(90.0);
+        let mut unskilled = project_assignment(2, 1, "2024-03-04", "2024-03-31");
+        unskilled.person_id = 3;
+        unskilled.calculated_effective_hours = Some(50.0);
+
+        // 140 effective hours cover the project, but none of them are React hours
+        let staffing = skills.project_skill_staffing(1, &[&rust_developer, &unskilled]);
+        let rust = staffing.iter().find(|s| s.skill_name == "Rust").unwrap();
+        let react = staffing.iter().find(|s| s.skill_name == "React").unwrap();
+        assert!(rust.is_viable);
+        assert!((rust.effective_hours - 90.0).abs() < 0.001);
+        assert!(!react.is_viable);
+        assert!((react.shortfall - 40.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_missing_skills_fall_back_to_missing_skill_factor() {
+        let skills = skill_data();
+
+        // Person 3 has none of the project's skills
+        let suggestion = skills.productivity_suggestion(3, 1).unwrap();
+        assert!((suggestion.productivity_factor - MISSING_SKILL_PRODUCTIVITY_FACTOR).abs() < 0.001);
+        assert_eq!(suggestion.skills.len(), 2);
+        for skill in &suggestion.skills {
+            assert_eq!(skill.proficiency, None);
+            assert_eq!(skill.productivity_factor, MISSING_SKILL_PRODUCTIVITY_FACTOR);
+        }
+    }
+
+    #[test]
+    fn test_unknown_stored_proficiency_counts_as_missing_skill() {
+        let mut skills = skill_data();
+        skills.person_skills = vec![person_skill(1, 1, "guru"), person_skill(1, 2, "expert")];
+
+        // Rust at the missing skill factor for 60h, React at expert (0.8) for 40h
+        let suggestion = skills.productivity_suggestion(1, 1).unwrap();
+        assert!((suggestion.productivity_factor - 0.38).abs() < 0.001);
+        let rust = suggestion
+            .skills
+            .iter()
+            .find(|skill| skill.skill_name == "Rust")
+            .unwrap();
+        assert_eq!(rust.proficiency, None);
+
+        // The unknown level earns no Rust hours either
+        let mut assignment = project_assignment(1, 1, "2024-03-04", "2024-03-31");
+        assignment.calculated_effective_hours = Some(50.0);
+        let staffing = skills.project_skill_staffing(1, &[&assignment]);
+        let rust = staffing.iter().find(|s| s.skill_name == "Rust").unwrap();
+        let react = staffing.iter().find(|s| s.skill_name == "React").unwrap();
+        assert_eq!(rust.effective_hours, 0.0);
+        assert!((react.effective_hours - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_zero_hour_requirements_are_ignored() {
+        let mut skills = skill_data();
+        skills.requirements = vec![skill_requirement(1, 60.0), skill_requirement(2, 0.0)];
+
+        // Lacking React doesn't count against the expert when React needs no hours
+        let suggestion = skills.productivity_suggestion(1, 1).unwrap();
+        assert!((suggestion.productivity_factor - 0.8).abs() < 0.001);
+        assert_eq!(suggestion.skills.len(), 1);
+
+        let mut assignment = project_assignment(1, 1, "2024-03-04", "2024-03-31");
+        assignment.calculated_effective_hours = Some(60.0);
+        let staffing = skills.project_skill_staffing(1, &[&assignment]);
+        assert_eq!(staffing.len(), 1);
+        assert!(staffing[0].is_viable);
+
+        // Only zero-hour requirements leave nothing to suggest or staff
+        skills.requirements = vec![skill_requirement(1, 0.0), skill_requirement(2, 0.0)];
+        assert!(skills.productivity_suggestion(1, 1).is_none());
+        assert!(skills.project_skill_staffing(1, &[&assignment]).is_empty());
+    }
+
+    #[test]
+    fn test_project_skill_staffing_splits_hours_across_matched_skills() {
+        let mut skills = skill_data();
+        skills.person_skills = vec![person_skill(1, 1, "expert"), person_skill(1, 2, "beginner")];
+        let mut assignment = project_assignment(1, 1, "2024-03-04", "2024-03-31");
+        assignment.calculated_effective_hours = Some(50.0);
+
+        // 50 effective hours split 60:40 like the hours the skills require
+        let staffing = skills.project_skill_staffing(1, &[&assignment]);
+        let rust = staffing.iter().find(|s| s.skill_name == "Rust").unwrap();
+        let react = staffing.iter().find(|s| s.skill_name == "React").unwrap();
+        assert!((rust.effective_hours - 30.0).abs() < 0.001);
+        assert!((react.effective_hours - 20.0).abs() < 0.001);
+        assert!((rust.shortfall - 30.0).abs() < 0.001);
+        assert!((react.shortfall - 20.0).abs() < 0.001);
+    }
+}
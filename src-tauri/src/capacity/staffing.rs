@@ -0,0 +1,375 @@
+use super::{
+    effective_hours_by_project, max_utilization, project_staffing_deltas, OptimizationResult,
+    OptimizationStrategy, PeriodCapacityData, ProjectStaffingDelta,
+};
+use crate::models::{Assignment, ProjectRequirement};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Productivity factor assumed for proposed assignments when none is given ("Proficient")
+pub const DEFAULT_RECOMMENDATION_PRODUCTIVITY_FACTOR: f64 = 0.5;
+
+/// Hours below which a person counts as fully allocated
+const SPARE_HOURS_TOLERANCE: f64 = 1e-6;
+
+/// People with spare capacity and the assignments that would close each shortfall
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StaffingRecommendations {
+    pub planning_period_id: i64,
+    pub strategy: String,         // Strategy the proposals were evaluated with
+    pub productivity_factor: f64, // Assumed for every proposed assignment
+    pub spare_capacity: Vec<PersonSpareCapacity>, // Most unallocated hours first
+    pub proposals: Vec<StaffingProposal>, // One per infeasible project
+}
+
+/// Capacity a person has left after the optimization
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonSpareCapacity {
+    pub person_id: i64,
+    pub person_name: String,
+    pub available_hours: f64, // Over the whole period, limited by the utilization cap
+    pub allocated_hours: f64,
+    pub unallocated_hours: f64,
+}
+
+/// New assignments that would close, or at least reduce, one project's shortfall
+///
+/// Every proposal is evaluated on its own against the current assignments.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StaffingProposal {
+    pub project_id: i64,
+    pub project_name: String,
+    pub shortfall: f64,           // Before the proposed assignments
+    pub remaining_shortfall: f64, // After the proposed assignments
+    pub assignments: Vec<ProposedAssignment>,
+    pub project_deltas: Vec<ProjectStaffingDelta>, // Other projects whose effective hours change
+}
+
+/// An assignment the recommendation proposes to create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedAssignment {
+    pub person_id: i64,
+    pub person_name: String,
+    pub project_id: i64,
+    pub planning_period_id: i64,
+    pub productivity_factor: f64,
+    pub start_date: String,
+    pub end_date: String,
+    pub allocation_percentage: f64, // What the optimization gave the new assignment
+    pub effective_hours: f64,
+}
+
+/// Capacity every person has left in an optimization result, most unallocated hours first
+pub fn spare_capacity(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    result: &OptimizationResult,
+) -> Result<Vec<PersonSpareCapacity>, String> {
+    let allocations: HashMap<i64, f64> = result
+        .calculations
+        .iter()
+        .chain(&result.pinned_calculations)
+        .map(|calc| (calc.assignment_id, calc.calculated_allocation_percentage))
+        .collect();
+
+    let mut allocated_by_person: HashMap<i64, f64> = HashMap::new();
+    for assignment in assignments {
+        let Some(pct) = allocations.get(&assignment.id) else {
+            continue;
+        };
+        let Some(person) = data.person(assignment.person_id) else {
+            continue;
+        };
+        let breakdown = data.assignment_breakdown(person, assignment)?;
+        *allocated_by_person.entry(person.id).or_default() +=
+            breakdown.available_hours * pct / 100.0;
+    }
+
+    let mut spare = Vec::with_capacity(data.people.len());
+    for person in &data.people {
+        let available_hours =
+            data.person_breakdown(person)?.available_hours * max_utilization(person) / 100.0;
+        let allocated_hours = allocated_by_person.get(&person.id).copied().unwrap_or(0.0);
+        spare.push(PersonSpareCapacity {
+            person_id: person.id,
+            person_name: person.name.clone(),
+            available_hours,
+            allocated_hours,
+            unallocated_hours: (available_hours - allocated_hours).max(0.0),
+        });
+    }
+    // Stable sort keeps people with equal spare hours in name order
+    spare.sort_by(|a, b| b.unallocated_hours.total_cmp(&a.unallocated_hours));
+    Ok(spare)
+}
+
+/// Recommend new assignments that close the shortfalls of an optimization result
+///
+/// For each infeasible project, people who are not on it yet are tried in order of their
+/// unallocated hours. A person is proposed when re-running the strategy with the new
+/// assignment gives them any hours on the project, until the shortfall is closed.
+pub fn recommend_staffing(
+    data: &PeriodCapacityData,
+    assignments: &[Assignment],
+    requirements: &[ProjectRequirement],
+    project_names: &HashMap<i64, String>,
+    strategy: &dyn OptimizationStrategy,
+    productivity_factor: f64,
+) -> Result<StaffingRecommendations, String> {
+    if !(productivity_factor > 0.0 && productivity_factor <= 1.0) {
+        return Err("Productivity factor must be greater than 0 and at most 1".to_string());
+    }
+
+    let baseline = strategy.optimize(data, assignments, requirements, project_names)?;
+    let spare = spare_capacity(data, assignments, &baseline)?;
+    let baseline_hours = effective_hours_by_project(assignments, &baseline);
+
+    let mut proposals = Vec::with_capacity(baseline.infeasible_projects.len());
+    for shortfall in &baseline.infeasible_projects {
+        let mut trial_assignments = assignments.to_vec();
+        let mut proposed_people = Vec::new();
+        let mut last_result = None;
+
+        for candidate in &spare {
+            if candidate.unallocated_hours <= SPARE_HOURS_TOLERANCE {
+                break;
+            }
+            let already_assigned = trial_assignments.iter().any(|assignment| {
+                assignment.person_id == candidate.person_id
+                    && assignment.project_id == shortfall.project_id
+            });
+            if already_assigned {
+                continue;
+            }
+
+            // Negative IDs cannot collide with stored assignments
+            let proposed_id = -(proposed_people.len() as i64 + 1);
+            trial_assignments.push(proposed_assignment(
+                data,
+                proposed_id,
+                candidate.person_id,
+                shortfall.project_id,
+                productivity_factor,
+            ));
+            let result =
+                strategy.optimize(data, &trial_assignments, requirements, project_names)?;
+
+            let staffed = result.calculations.iter().any(|calc| {
+                calc.assignment_id == proposed_id && calc.calculated_effective_hours > 0.0
+            });
+            if !staffed {
+                trial_assignments.pop();
+                continue;
+            }
+
+            proposed_people.push((proposed_id, candidate));
+            let closed = !result
+                .infeasible_projects
+                .iter()
+                .any(|other| other.project_id == shortfall.project_id);
+            last_result = Some(result);
+            if closed {
+                break;
+            }
+        }
+
+        let Some(result) = last_result else {
+            debug!(
+                "No one with spare capacity can reduce the shortfall of project {}",
+                shortfall.project_id
+            );
+            proposals.push(StaffingProposal {
+                project_id: shortfall.project_id,
+                project_name: shortfall.project_name.clone(),
+                shortfall: shortfall.shortfall,
+                remaining_shortfall: shortfall.shortfall,
+                assignments: vec![],
+                project_deltas: vec![],
+            });
+            continue;
+        };
+
+        let calculations: HashMap<i64, _> = result
+            .calculations
+            .iter()
+            .map(|calc| (calc.assignment_id, calc))
+            .collect();
+        let proposed_assignments = proposed_people
+            .iter()
+            .map(|(proposed_id, candidate)| {
+                let calc = calculations[proposed_id];
+                ProposedAssignment {
+                    person_id: candidate.person_id,
+                    person_name: candidate.person_name.clone(),
+                    project_id: shortfall.project_id,
+                    planning_period_id: data.planning_period.id,
+                    productivity_factor,
+                    start_date: data.planning_period.start_date.clone(),
+                    end_date: data.planning_period.end_date.clone(),
+                    allocation_percentage: calc.calculated_allocation_percentage,
+                    effective_hours: calc.calculated_effective_hours,
+                }
+            })
+            .collect();
+
+        // Knock-on effect of the new assignments on the other projects
+        let trial_hours = effective_hours_by_project(&trial_assignments, &result);
+        let mut totals_by_project: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+        for (project_id, hours) in &baseline_hours {
+            totals_by_project.entry(*project_id).or_default().0 = *hours;
+        }
+        for (project_id, hours) in &trial_hours {
+            totals_by_project.entry(*project_id).or_default().1 = *hours;
+        }
+        totals_by_project.remove(&shortfall.project_id);
+        totals_by_project.retain(|_, (old, new)| (*new - *old).abs() > SPARE_HOURS_TOLERANCE);
+
+        proposals.push(StaffingProposal {
+            project_id: shortfall.project_id,
+            project_name: shortfall.project_name.clone(),
+            shortfall: shortfall.shortfall,
+            remaining_shortfall: result
+                .infeasible_projects
+                .iter()
+                .find(|other| other.project_id == shortfall.project_id)
+                .map_or(0.0, |other| other.shortfall),
+            assignments: proposed_assignments,
+            project_deltas: project_staffing_deltas(totals_by_project, project_names),
+        });
+    }
+
+    Ok(StaffingRecommendations {
+        planning_period_id: data.planning_period.id,
+        strategy: baseline.strategy,
+        productivity_factor,
+        spare_capacity: spare,
+        proposals,
+    })
+}
+
+/// Unsaved assignment of a person to a project over the whole planning period
+fn proposed_assignment(
+    data: &PeriodCapacityData,
+    id: i64,
+    person_id: i64,
+    project_id: i64,
+    productivity_factor: f64,
+) -> Assignment {
+    Assignment {
+        id,
+        person_id,
+        project_id,
+        planning_period_id: data.planning_period.id,
+        productivity_factor,
+        start_date: data.planning_period.start_date.clone(),
+        end_date: data.planning_period.end_date.clone(),
+        is_pinned: false,
+        pinned_allocation_percentage: None,
+        min_allocation_percentage: None,
+        max_allocation_percentage: None,
+        calculated_allocation_percentage: None,
+        calculated_effective_hours: None,
+        calculated_start_date: None,
+        last_calculated_at: None,
+        created_at: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+    use crate::capacity::{optimize_proportional, ProportionalStrategy};
+
+    fn staffing_data() -> (PeriodCapacityData, Vec<Assignment>, Vec<ProjectRequirement>) {
+        let data = PeriodCapacityData::new(
+            march_period(),
+            vec![full_timer(1, None), full_timer(2, None)],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let mut side_project = project_assignment(3, 3, "2024-03-04", "2024-03-31");
+        side_project.person_id = 2;
+        let assignments = vec![
+            project_assignment(1, 1, "2024-03-04", "2024-03-31"),
+            project_assignment(2, 2, "2024-03-04", "2024-03-31"),
+            side_project,
+        ];
+        // Project 2 is 40h short; person 2 has 40h to spare after project 3
+        let requirements = vec![
+            requirement(1, 100.0, 20),
+            requirement(2, 100.0, 10),
+            requirement(3, 120.0, 0),
+        ];
+        (data, assignments, requirements)
+    }
+
+    #[test]
+    fn test_spare_capacity_ranks_people_by_unallocated_hours() {
+        let (data, assignments, requirements) = staffing_data();
+        let result =
+            optimize_proportional(&data, &assignments, &requirements, &HashMap::new()).unwrap();
+
+        let spare = spare_capacity(&data, &assignments, &result).unwrap();
+        assert_eq!(spare[0].person_id, 2);
+        assert!((spare[0].allocated_hours - 120.0).abs() < 0.001);
+        assert!((spare[0].unallocated_hours - 40.0).abs() < 0.001);
+        assert_eq!(spare[1].person_id, 1);
+        assert!((spare[1].unallocated_hours - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_recommend_staffing_closes_shortfall() {
+        let (data, assignments, requirements) = staffing_data();
+
+        let recommendations = recommend_staffing(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &ProportionalStrategy,
+            0.5,
+        )
+        .unwrap();
+
+        assert_eq!(recommendations.proposals.len(), 1);
+        let proposal = &recommendations.proposals[0];
+        assert_eq!(proposal.project_id, 2);
+        assert!((proposal.shortfall - 40.0).abs() < 0.001);
+        assert!((proposal.remaining_shortfall - 0.0).abs() < 0.001);
+
+        assert_eq!(proposal.assignments.len(), 1);
+        let proposed = &proposal.assignments[0];
+        assert_eq!(proposed.person_id, 2);
+        assert_eq!(proposed.start_date, "2024-03-04");
+        assert!(
+            (proposed.effective_hours - proposed.allocation_percentage / 100.0 * 160.0 * 0.5).abs()
+                < 0.001
+        );
+
+        // The lower-priority project of person 2 gives up what exceeds their 40h to spare
+        let moved_hours = proposed.allocation_percentage / 100.0 * 160.0;
+        assert!(moved_hours > 40.0);
+        assert_eq!(proposal.project_deltas.len(), 1);
+        assert_eq!(proposal.project_deltas[0].project_id, 3);
+        assert!((proposal.project_deltas[0].delta_hours + (moved_hours - 40.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_recommend_staffing_rejects_invalid_productivity() {
+        let (data, assignments, requirements) = staffing_data();
+        let result = recommend_staffing(
+            &data,
+            &assignments,
+            &requirements,
+            &HashMap::new(),
+            &ProportionalStrategy,
+            0.0,
+        );
+        assert!(result.is_err());
+    }
+}
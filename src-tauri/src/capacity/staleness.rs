@@ -0,0 +1,284 @@
+use crate::models::{PlanningPeriod, PlanningPeriodInputChange};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Whether the stored calculations of a planning period are out of date
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CalculationStaleness {
+    pub is_stale: bool,       // Inputs changed since the last optimization
+    pub reasons: Vec<String>, // e.g. "3 absences added", "requirement for Project X changed"
+}
+
+/// Input of the optimization whose change makes calculations stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangedInput {
+    Absence,
+    Holiday,
+    Job,
+    OverheadTask,
+    JobAssignment,
+    Requirement,
+    Assignment,
+    Person,
+    Project,
+    ProjectDependency,
+    PlanningPeriod,
+    Settings,
+    OptimizationRun,
+}
+
+impl ChangedInput {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangedInput::Absence => "absence",
+            ChangedInput::Holiday => "holiday",
+            ChangedInput::Job => "job",
+            ChangedInput::OverheadTask => "overhead_task",
+            ChangedInput::JobAssignment => "job_assignment",
+            ChangedInput::Requirement => "requirement",
+            ChangedInput::Assignment => "assignment",
+            ChangedInput::Person => "person",
+            ChangedInput::Project => "project",
+            ChangedInput::ProjectDependency => "project_dependency",
+            ChangedInput::PlanningPeriod => "planning_period",
+            ChangedInput::Settings => "settings",
+            ChangedInput::OptimizationRun => "optimization_run",
+        }
+    }
+
+    /// Whether the change re-runs the optimization of periods that opted in
+    pub fn triggers_auto_optimization(self) -> bool {
+        matches!(
+            self,
+            ChangedInput::Absence
+                | ChangedInput::Holiday
+                | ChangedInput::Assignment
+                | ChangedInput::Requirement
+                | ChangedInput::Job
+                | ChangedInput::OverheadTask
+                | ChangedInput::JobAssignment
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputChangeAction {
+    Added,
+    Changed,
+    Deleted,
+    Restored,
+}
+
+impl InputChangeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InputChangeAction::Added => "added",
+            InputChangeAction::Changed => "changed",
+            InputChangeAction::Deleted => "deleted",
+            InputChangeAction::Restored => "restored",
+        }
+    }
+}
+
+/// Input, action and subject that changes are counted by
+type InputChangeKey<'a> = (&'a str, &'a str, Option<&'a str>);
+
+/// Singular and plural noun of a stored input, and how a subject attaches to it
+fn input_wording(input: &str) -> (&str, &str, &str) {
+    match input {
+        "absence" => ("absence", "absences", "of"),
+        "holiday" => ("holiday", "holidays", "in"),
+        "job" => ("job", "jobs", ""),
+        "overhead_task" => ("overhead task", "overhead tasks", "of job"),
+        "job_assignment" => ("job assignment", "job assignments", "of"),
+        "requirement" => ("requirement", "requirements", "for"),
+        "assignment" => ("assignment", "assignments", "to"),
+        "person" => ("person", "people", ""),
+        "project" => ("project", "projects", ""),
+        "project_dependency" => ("dependency", "dependencies", "of"),
+        "planning_period" => ("planning period", "planning periods", ""),
+        "settings" => ("app setting", "app settings", ""),
+        "optimization_run" => ("optimization run", "optimization runs", ""),
+        other => (other, other, "of"),
+    }
+}
+
+/// Readable reasons for a list of input changes, e.g. "3 absences added"
+///
+/// Changes of the same input, action and subject are counted together, in the order
+/// they first happened.
+pub fn summarize_input_changes(changes: &[PlanningPeriodInputChange]) -> Vec<String> {
+    let mut groups: Vec<(InputChangeKey, i64)> = Vec::new();
+    for change in changes {
+        let key = (
+            change.input.as_str(),
+            change.action.as_str(),
+            change.subject.as_deref(),
+        );
+        match groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, count)) => *count += change.change_count,
+            None => groups.push((key, change.change_count)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((input, action, subject), count)| {
+            let (singular, plural, connector) = input_wording(input);
+            let counted = if count == 1 {
+                singular.to_string()
+            } else {
+                format!("{} {}", count, plural)
+            };
+            match (subject, connector) {
+                (Some(subject), "") => format!("{} {} {}", counted, subject, action),
+                (Some(subject), connector) => {
+                    format!("{} {} {} {}", counted, connector, subject, action)
+                }
+                (None, _) => format!("{} {}", counted, action),
+            }
+        })
+        .collect()
+}
+
+/// Staleness of a planning period from the input changes recorded for it
+pub fn calculation_staleness(changes: &[PlanningPeriodInputChange]) -> CalculationStaleness {
+    CalculationStaleness {
+        is_stale: !changes.is_empty(),
+        reasons: summarize_input_changes(changes),
+    }
+}
+
+/// Number of changed inputs whose dates overlap each planning period
+///
+/// Each changed input has one or more (start date, end date) ranges, e.g. its old and new
+/// dates. Periods none of the ranges overlap are left out.
+pub fn dated_change_counts(
+    periods: &[PlanningPeriod],
+    changed_ranges: &[Vec<(String, String)>],
+) -> Vec<(i64, i64)> {
+    periods
+        .iter()
+        .filter_map(|period| {
+            // ISO 8601 dates compare correctly as strings
+            let overlapping = changed_ranges
+                .iter()
+                .filter(|ranges| {
+                    ranges.iter().any(|(start, end)| {
+                        start.as_str() <= period.end_date.as_str()
+                            && end.as_str() >= period.start_date.as_str()
+                    })
+                })
+                .count() as i64;
+            (overlapping > 0).then_some((period.id, overlapping))
+        })
+        .collect()
+}
+
+/// IDs without duplicates, in their original order
+pub(super) fn unique_ids(ids: &[i64]) -> Vec<i64> {
+    let mut seen = HashSet::new();
+    ids.iter().copied().filter(|id| seen.insert(*id)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_change(
+        input: ChangedInput,
+        action: InputChangeAction,
+        subject: Option<&str>,
+        change_count: i64,
+    ) -> crate::models::PlanningPeriodInputChange {
+        crate::models::PlanningPeriodInputChange {
+            id: 0,
+            planning_period_id: 1,
+            input: input.as_str().to_string(),
+            action: action.as_str().to_string(),
+            subject: subject.map(str::to_string),
+            change_count,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_input_changes_counts_alike_changes() {
+        let changes = vec![
+            input_change(ChangedInput::Absence, InputChangeAction::Added, None, 1),
+            input_change(
+                ChangedInput::Requirement,
+                InputChangeAction::Changed,
+                Some("Project X"),
+                1,
+            ),
+            input_change(ChangedInput::Absence, InputChangeAction::Added, None, 2),
+            input_change(ChangedInput::Holiday, InputChangeAction::Deleted, None, 1),
+            input_change(
+                ChangedInput::OverheadTask,
+                InputChangeAction::Changed,
+                Some("Developer"),
+                2,
+            ),
+            input_change(
+                ChangedInput::Person,
+                InputChangeAction::Changed,
+                Some("Person 1"),
+                1,
+            ),
+        ];
+
+        assert_eq!(
+            summarize_input_changes(&changes),
+            vec![
+                "3 absences added",
+                "requirement for Project X changed",
+                "holiday deleted",
+                "2 overhead tasks of job Developer changed",
+                "person Person 1 changed",
+            ]
+        );
+
+        let staleness = calculation_staleness(&changes);
+        assert!(staleness.is_stale);
+        assert!(!calculation_staleness(&[]).is_stale);
+    }
+
+    #[test]
+    fn test_dated_change_counts_marks_overlapping_periods() {
+        let period = |id: i64, start: &str, end: &str| PlanningPeriod {
+            id,
+            name: None,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            created_at: String::new(),
+        };
+        let periods = vec![
+            period(1, "2024-03-04", "2024-03-31"),
+            period(2, "2024-04-01", "2024-04-28"),
+            period(3, "2024-04-29", "2024-05-26"),
+        ];
+        let range = |start: &str, end: &str| vec![(start.to_string(), end.to_string())];
+
+        // One absence across the turn of the month, one moved from March into May
+        let changed_ranges = vec![
+            range("2024-03-28", "2024-04-02"),
+            vec![
+                ("2024-03-11".to_string(), "2024-03-12".to_string()),
+                ("2024-05-06".to_string(), "2024-05-07".to_string()),
+            ],
+        ];
+        assert_eq!(
+            dated_change_counts(&periods, &changed_ranges),
+            vec![(1, 2), (2, 1), (3, 1)]
+        );
+
+        // Boundary days count, days outside every period don't
+        assert_eq!(
+            dated_change_counts(&periods, &[range("2024-04-28", "2024-04-28")]),
+            vec![(2, 1)]
+        );
+        assert!(dated_change_counts(&periods, &[range("2024-06-03", "2024-06-07")]).is_empty());
+        assert!(dated_change_counts(&periods, &[range("2024-02-26", "2024-03-03")]).is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+use super::{
+    optimize_even_spread, optimize_linear, optimize_priority_first, optimize_proportional,
+    OptimizationOptions, OptimizationResult, PeriodCapacityData,
+};
+use crate::models::{Assignment, ProjectRequirement};
+use std::collections::HashMap;
+
+pub const PROPORTIONAL_STRATEGY: &str = "proportional";
+pub const PRIORITY_FIRST_STRATEGY: &str = "priority_first";
+pub const EVEN_SPREAD_STRATEGY: &str = "even_spread";
+pub const LINEAR_STRATEGY: &str = "linear";
+
+/// Names of the available optimization strategies, the default first
+pub const OPTIMIZATION_STRATEGIES: [&str; 4] = [
+    PROPORTIONAL_STRATEGY,
+    PRIORITY_FIRST_STRATEGY,
+    EVEN_SPREAD_STRATEGY,
+    LINEAR_STRATEGY,
+];
+
+/// A way of distributing people's capacity over the projects they are assigned to
+pub trait OptimizationStrategy: Send + Sync {
+    /// Name the strategy is selected by and recorded under
+    fn name(&self) -> &'static str;
+
+    /// Calculate the allocation of every assignment
+    fn optimize(
+        &self,
+        data: &PeriodCapacityData,
+        assignments: &[Assignment],
+        requirements: &[ProjectRequirement],
+        project_names: &HashMap<i64, String>,
+    ) -> Result<OptimizationResult, String>;
+}
+
+pub struct ProportionalStrategy;
+
+impl OptimizationStrategy for ProportionalStrategy {
+    fn name(&self) -> &'static str {
+        PROPORTIONAL_STRATEGY
+    }
+
+    fn optimize(
+        &self,
+        data: &PeriodCapacityData,
+        assignments: &[Assignment],
+        requirements: &[ProjectRequirement],
+        project_names: &HashMap<i64, String>,
+    ) -> Result<OptimizationResult, String> {
+        optimize_proportional(data, assignments, requirements, project_names)
+    }
+}
+
+pub struct PriorityFirstStrategy;
+
+impl OptimizationStrategy for PriorityFirstStrategy {
+    fn name(&self) -> &'static str {
+        PRIORITY_FIRST_STRATEGY
+    }
+
+    fn optimize(
+        &self,
+        data: &PeriodCapacityData,
+        assignments: &[Assignment],
+        requirements: &[ProjectRequirement],
+        project_names: &HashMap<i64, String>,
+    ) -> Result<OptimizationResult, String> {
+        optimize_priority_first(data, assignments, requirements, project_names)
+    }
+}
+
+pub struct EvenSpreadStrategy;
+
+impl OptimizationStrategy for EvenSpreadStrategy {
+    fn name(&self) -> &'static str {
+        EVEN_SPREAD_STRATEGY
+    }
+
+    fn optimize(
+        &self,
+        data: &PeriodCapacityData,
+        assignments: &[Assignment],
+        requirements: &[ProjectRequirement],
+        project_names: &HashMap<i64, String>,
+    ) -> Result<OptimizationResult, String> {
+        optimize_even_spread(data, assignments, requirements, project_names)
+    }
+}
+
+pub struct LinearStrategy {
+    pub options: OptimizationOptions,
+}
+
+impl OptimizationStrategy for LinearStrategy {
+    fn name(&self) -> &'static str {
+        LINEAR_STRATEGY
+    }
+
+    fn optimize(
+        &self,
+        data: &PeriodCapacityData,
+        assignments: &[Assignment],
+        requirements: &[ProjectRequirement],
+        project_names: &HashMap<i64, String>,
+    ) -> Result<OptimizationResult, String> {
+        optimize_linear(
+            data,
+            assignments,
+            requirements,
+            project_names,
+            &self.options,
+        )
+    }
+}
+
+/// Look up a strategy by name and check its options
+///
+/// Without a name the proportional strategy is used. Options the strategy has no use for
+/// are rejected, so a run never records options it didn't apply.
+pub fn optimization_strategy(
+    name: Option<&str>,
+    options: OptimizationOptions,
+) -> Result<Box<dyn OptimizationStrategy>, String> {
+    if let Some(step) = options.priority_weight_step {
+        if !step.is_finite() || step < 0.0 {
+            return Err(format!(
+                "Priority weight step must be zero or more, got {}",
+                step
+            ));
+        }
+    }
+
+    let name = name.unwrap_or(PROPORTIONAL_STRATEGY);
+    // The heuristic strategies take no options
+    let is_heuristic = OPTIMIZATION_STRATEGIES.contains(&name) && name != LINEAR_STRATEGY;
+    if is_heuristic && options != OptimizationOptions::default() {
+        return Err(format!(
+            "The {} strategy takes no options, the priority weight step only applies to the {} strategy",
+            name, LINEAR_STRATEGY
+        ));
+    }
+
+    match name {
+        PROPORTIONAL_STRATEGY => Ok(Box::new(ProportionalStrategy)),
+        PRIORITY_FIRST_STRATEGY => Ok(Box::new(PriorityFirstStrategy)),
+        EVEN_SPREAD_STRATEGY => Ok(Box::new(EvenSpreadStrategy)),
+        LINEAR_STRATEGY => Ok(Box::new(LinearStrategy { options })),
+        other => Err(format!(
+            "Unknown optimization strategy '{}', expected one of: {}",
+            other,
+            OPTIMIZATION_STRATEGIES.join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capacity::test_support::*;
+
+    #[test]
+    fn test_optimization_strategy_lookup() {
+        let strategy = optimization_strategy(None, OptimizationOptions::default()).unwrap();
+        assert_eq!(strategy.name(), "proportional");
+
+        for name in OPTIMIZATION_STRATEGIES {
+            let strategy =
+                optimization_strategy(Some(name), OptimizationOptions::default()).unwrap();
+            assert_eq!(strategy.name(), name);
+        }
+
+        assert!(optimization_strategy(Some("random"), OptimizationOptions::default()).is_err());
+        let negative_step = OptimizationOptions {
+            priority_weight_step: Some(-1.0),
+        };
+        assert!(optimization_strategy(Some("linear"), negative_step).is_err());
+    }
+
+    #[test]
+    fn test_heuristic_strategies_reject_options() {
+        let step = OptimizationOptions {
+            priority_weight_step: Some(2.0),
+        };
+        for name in [
+            PROPORTIONAL_STRATEGY,
+            PRIORITY_FIRST_STRATEGY,
+            EVEN_SPREAD_STRATEGY,
+        ] {
+            let error = optimization_strategy(Some(name), step.clone())
+                .err()
+                .unwrap();
+            assert!(error.contains("takes no options"), "{}", error);
+        }
+        assert!(optimization_strategy(None, step.clone()).is_err());
+
+        // Unknown names still report the unknown strategy
+        let error = optimization_strategy(Some("random"), step.clone())
+            .err()
+            .unwrap();
+        assert!(error.starts_with("Unknown optimization strategy"));
+
+        let data = single_person_data();
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let requirements = vec![requirement(1, 80.0, 10)];
+        let result = optimization_strategy(Some(LINEAR_STRATEGY), step)
+            .unwrap()
+            .optimize(&data, &assignments, &requirements, &HashMap::new())
+            .unwrap();
+        assert_eq!(result.options.priority_weight_step, Some(2.0));
+    }
+
+    #[test]
+    fn test_strategy_records_resolved_options() {
+        let data = single_person_data();
+        let assignments = vec![project_assignment(1, 1, "2024-03-04", "2024-03-31")];
+        let requirements = vec![requirement(1, 80.0, 10)];
+
+        let strategy =
+            optimization_strategy(Some("linear"), OptimizationOptions::default()).unwrap();
+        let result = strategy
+            .optimize(&data, &assignments, &requirements, &HashMap::new())
+            .unwrap();
+
+        assert_eq!(result.strategy, "linear");
+        assert_eq!(result.options.priority_weight_step, Some(1.0));
+    }
+}
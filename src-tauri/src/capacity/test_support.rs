@@ -0,0 +1,122 @@
+//! Fixtures shared by the capacity tests
+
+use super::PeriodCapacityData;
+use crate::db::{init_test_database, DbPool};
+use crate::models::{Assignment, Person, PlanningPeriod, ProjectRequirement};
+
+/// March 4-31, 2024: four full weeks with 20 weekdays
+pub fn march_period() -> PlanningPeriod {
+    PlanningPeriod {
+        id: 1,
+        name: None,
+        start_date: "2024-03-04".to_string(),
+        end_date: "2024-03-31".to_string(),
+        created_at: String::new(),
+    }
+}
+
+pub fn full_timer(id: i64, country_id: Option<i64>) -> Person {
+    Person {
+        id,
+        name: format!("Person {}", id),
+        email: String::new(),
+        available_hours_per_week: 40.0,
+        country_id,
+        working_days: "Mon,Tue,Wed,Thu,Fri".to_string(),
+        max_utilization_percentage: None,
+        created_at: String::new(),
+    }
+}
+
+pub fn project_assignment(id: i64, project_id: i64, start: &str, end: &str) -> Assignment {
+    Assignment {
+        id,
+        person_id: 1,
+        project_id,
+        planning_period_id: 1,
+        productivity_factor: 1.0,
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        is_pinned: false,
+        pinned_allocation_percentage: None,
+        min_allocation_percentage: None,
+        max_allocation_percentage: None,
+        calculated_allocation_percentage: None,
+        calculated_effective_hours: None,
+        calculated_start_date: None,
+        last_calculated_at: None,
+        created_at: String::new(),
+    }
+}
+
+pub fn pinned(mut assignment: Assignment, pct: f64) -> Assignment {
+    assignment.is_pinned = true;
+    assignment.pinned_allocation_percentage = Some(pct);
+    assignment
+}
+
+pub fn requirement(project_id: i64, required_hours: f64, priority: i64) -> ProjectRequirement {
+    ProjectRequirement {
+        id: project_id,
+        project_id,
+        planning_period_id: 1,
+        required_hours,
+        priority,
+        created_at: String::new(),
+    }
+}
+
+pub fn single_person_data() -> PeriodCapacityData {
+    PeriodCapacityData::new(
+        march_period(),
+        vec![full_timer(1, None)],
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap()
+}
+
+pub fn two_person_data() -> PeriodCapacityData {
+    PeriodCapacityData::new(
+        march_period(),
+        vec![full_timer(1, None), full_timer(2, None)],
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap()
+}
+
+pub fn person_assignment(id: i64, person_id: i64, project_id: i64) -> Assignment {
+    let mut assignment = project_assignment(id, project_id, "2024-03-04", "2024-03-31");
+    assignment.person_id = person_id;
+    assignment
+}
+
+/// In-memory database holding the March period, in which person 1 is assigned to projects
+/// 1 and 2 (assignments 1 and 2); both projects require 80h
+pub async fn seeded_database() -> DbPool {
+    let pool = init_test_database().await;
+    for statement in [
+        "INSERT INTO planning_periods (id, start_date, end_date)
+         VALUES (1, '2024-03-04', '2024-03-31')",
+        "INSERT INTO people (id, name, email, available_hours_per_week)
+         VALUES (1, 'Person 1', 'person1@example.com', 40.0)",
+        "INSERT INTO projects (id, name, required_hours) VALUES (1, 'Alpha', 80.0), (2, 'Beta', 80.0)",
+        "INSERT INTO project_requirements (project_id, planning_period_id, required_hours, priority)
+         VALUES (1, 1, 80.0, 20), (2, 1, 80.0, 10)",
+        "INSERT INTO assignments
+         (id, person_id, project_id, planning_period_id, productivity_factor, start_date, end_date)
+         VALUES (1, 1, 1, 1, 1.0, '2024-03-04', '2024-03-31'),
+                (2, 1, 2, 1, 1.0, '2024-03-04', '2024-03-31')",
+    ] {
+        sqlx::query(statement)
+            .execute(&pool)
+            .await
+            .expect("Failed to seed database");
+    }
+    pool
+}
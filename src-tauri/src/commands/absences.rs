@@ -1,14 +1,76 @@
+use crate::capacity::{
+    calculate_absence_days, calculate_absence_deduction, person_planning_periods,
+    record_dated_input_changes, ChangedInput, InputChangeAction,
+};
 use crate::db::DbPool;
-use crate::models::{Absence, CreateAbsenceInput};
-use log::{debug, error, info};
+use crate::models::{Absence, AbsenceWithHours, CreateAbsenceInput, Person};
+use log::{debug, error, info, warn};
+
+/// Validate an absence's dates and derive its day count from the calendar
+///
+/// A `days` value that doesn't match the dates is corrected rather than stored as-is.
+async fn resolve_absence_days(pool: &DbPool, input: &CreateAbsenceInput) -> Result<i64, String> {
+    if input.start_date > input.end_date {
+        warn!("Invalid date range: start date after end date");
+        return Err("Start date must be on or before end date".to_string());
+    }
+
+    // Partial-day absences are expressed either in hours or as a fraction of the day
+    if input.hours.is_some() && input.day_fraction.is_some() {
+        warn!("Invalid partial-day absence: both hours and day fraction set");
+        return Err("Specify either hours or a fraction of the day, not both".to_string());
+    }
+    if input.hours.is_some_and(|hours| hours <= 0.0) {
+        warn!("Invalid partial-day absence: hours must be positive");
+        return Err("Absence hours must be greater than 0".to_string());
+    }
+    if input
+        .day_fraction
+        .is_some_and(|fraction| fraction <= 0.0 || fraction > 1.0)
+    {
+        warn!("Invalid partial-day absence: day fraction out of range");
+        return Err("Fraction of the day must be greater than 0 and at most 1".to_string());
+    }
+
+    let person = sqlx::query_as::<_, Person>("SELECT * FROM people WHERE id = ?")
+        .bind(input.person_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch person: {}", e);
+            e.to_string()
+        })?;
+
+    let days = calculate_absence_days(&person, &input.start_date, &input.end_date, pool).await?;
+
+    if let Some(requested_days) = input.days {
+        if requested_days != days {
+            warn!(
+                "Absence days {} do not match the dates {} to {} ({} working days), correcting",
+                requested_days, input.start_date, input.end_date, days
+            );
+        }
+    }
+
+    Ok(days)
+}
 
 #[tauri::command]
 pub async fn list_absences(
     pool: tauri::State<'_, DbPool>,
     person_id: i64,
-) -> Result<Vec<Absence>, String> {
+) -> Result<Vec<AbsenceWithHours>, String> {
     debug!("Fetching absences for person ID: {}", person_id);
 
+    let person = sqlx::query_as::<_, Person>("SELECT * FROM people WHERE id = ?")
+        .bind(person_id)
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch person: {}", e);
+            e.to_string()
+        })?;
+
     let absences = sqlx::query_as::<_, Absence>(
         "SELECT * FROM absences WHERE person_id = ? ORDER BY start_date DESC",
     )
@@ -20,8 +82,48 @@ pub async fn list_absences(
         e.to_string()
     })?;
 
-    info!("Successfully fetched {} absences", absences.len());
-    Ok(absences)
+    // Report the deducted days and hours, including partial-day absences
+    let mut absences_with_hours = Vec::with_capacity(absences.len());
+    for absence in absences {
+        let deduction = calculate_absence_deduction(&person, &absence, pool.inner()).await?;
+        absences_with_hours.push(AbsenceWithHours {
+            id: absence.id,
+            person_id: absence.person_id,
+            start_date: absence.start_date,
+            end_date: absence.end_date,
+            days: absence.days,
+            hours: absence.hours,
+            day_fraction: absence.day_fraction,
+            reason: absence.reason,
+            created_at: absence.created_at,
+            absence_days: deduction.days,
+            absence_hours: deduction.hours,
+        });
+    }
+
+    info!(
+        "Successfully fetched {} absences",
+        absences_with_hours.len()
+    );
+    Ok(absences_with_hours)
+}
+
+/// Mark the planning periods of the person that the absence dates fall into as stale
+async fn record_absence_change(
+    pool: &DbPool,
+    person_id: i64,
+    ranges: Vec<(String, String)>,
+    action: InputChangeAction,
+) -> Result<(), String> {
+    let planning_period_ids = person_planning_periods(person_id, pool).await?;
+    record_dated_input_changes(
+        &planning_period_ids,
+        &[ranges],
+        ChangedInput::Absence,
+        action,
+        pool,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -31,14 +133,18 @@ pub async fn create_absence(
 ) -> Result<Absence, String> {
     debug!("Creating absence for person ID: {}", input.person_id);
 
+    let days = resolve_absence_days(pool.inner(), &input).await?;
+
     let result = sqlx::query(
-        "INSERT INTO absences (person_id, start_date, end_date, days, reason) 
-         VALUES (?, ?, ?, ?, ?)",
+        "INSERT INTO absences (person_id, start_date, end_date, days, hours, day_fraction, reason) 
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(input.person_id)
     .bind(&input.start_date)
     .bind(&input.end_date)
-    .bind(input.days)
+    .bind(days)
+    .bind(input.hours)
+    .bind(input.day_fraction)
     .bind(&input.reason)
     .execute(pool.inner())
     .await
@@ -59,6 +165,14 @@ pub async fn create_absence(
             e.to_string()
         })?;
 
+    record_absence_change(
+        pool.inner(),
+        absence.person_id,
+        vec![(absence.start_date.clone(), absence.end_date.clone())],
+        InputChangeAction::Added,
+    )
+    .await?;
+
     info!("Successfully created absence");
     Ok(absence)
 }
@@ -71,14 +185,27 @@ pub async fn update_absence(
 ) -> Result<Absence, String> {
     debug!("Updating absence ID: {}", id);
 
+    let days = resolve_absence_days(pool.inner(), &input).await?;
+
+    let previous = sqlx::query_as::<_, Absence>("SELECT * FROM absences WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch absence: {}", e);
+            e.to_string()
+        })?;
+
     sqlx::query(
         "UPDATE absences 
-         SET start_date = ?, end_date = ?, days = ?, reason = ?
+         SET start_date = ?, end_date = ?, days = ?, hours = ?, day_fraction = ?, reason = ?
          WHERE id = ?",
     )
     .bind(&input.start_date)
     .bind(&input.end_date)
-    .bind(input.days)
+    .bind(days)
+    .bind(input.hours)
+    .bind(input.day_fraction)
     .bind(&input.reason)
     .bind(id)
     .execute(pool.inner())
@@ -97,6 +224,18 @@ pub async fn update_absence(
             e.to_string()
         })?;
 
+    // Both the old and the new dates may fall into planning periods
+    record_absence_change(
+        pool.inner(),
+        absence.person_id,
+        vec![
+            (previous.start_date, previous.end_date),
+            (absence.start_date.clone(), absence.end_date.clone()),
+        ],
+        InputChangeAction::Changed,
+    )
+    .await?;
+
     info!("Successfully updated absence ID: {}", id);
     Ok(absence)
 }
@@ -105,6 +244,15 @@ pub async fn update_absence(
 pub async fn delete_absence(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting absence ID: {}", id);
 
+    let absence = sqlx::query_as::<_, Absence>("SELECT * FROM absences WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch absence: {}", e);
+            e.to_string()
+        })?;
+
     sqlx::query("DELETE FROM absences WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -114,6 +262,16 @@ pub async fn delete_absence(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(
             e.to_string()
         })?;
 
+    if let Some(absence) = absence {
+        record_absence_change(
+            pool.inner(),
+            absence.person_id,
+            vec![(absence.start_date, absence.end_date)],
+            InputChangeAction::Deleted,
+        )
+        .await?;
+    }
+
     info!("Successfully deleted absence ID: {}", id);
     Ok(())
 }
@@ -1,7 +1,72 @@
+use crate::capacity::{input_subject_name, record_input_change, ChangedInput, InputChangeAction};
 use crate::db::DbPool;
 use crate::models::{Assignment, CreateAssignmentInput, PlanningPeriod};
 use log::{debug, error, info, warn};
 
+/// Mark the planning period of an assignment as stale
+async fn record_assignment_change(
+    pool: &DbPool,
+    assignment: &Assignment,
+    action: InputChangeAction,
+) -> Result<(), String> {
+    let project_name = input_subject_name("projects", assignment.project_id, pool).await?;
+    record_input_change(
+        &[assignment.planning_period_id],
+        ChangedInput::Assignment,
+        action,
+        project_name.as_deref(),
+        pool,
+    )
+    .await
+}
+
+/// Validate the pinned allocation of an assignment input
+///
+/// Returns the pinned flag and percentage to store.
+fn validate_pinned_allocation(
+    is_pinned: Option<bool>,
+    pinned_allocation_percentage: Option<f64>,
+) -> Result<(bool, Option<f64>), String> {
+    let is_pinned = is_pinned.unwrap_or(false);
+    match pinned_allocation_percentage {
+        Some(pct) if !(0.0..=100.0).contains(&pct) => {
+            warn!("Pinned allocation validation failed: {} out of range", pct);
+            Err("Pinned allocation percentage must be between 0 and 100".to_string())
+        }
+        None if is_pinned => {
+            warn!("Pinned allocation validation failed: percentage missing");
+            Err("Pinned assignments need a pinned allocation percentage".to_string())
+        }
+        pct => Ok((is_pinned, pct)),
+    }
+}
+
+/// Validate the minimum and maximum allocation of an assignment input
+fn validate_allocation_bounds(
+    min_allocation_percentage: Option<f64>,
+    max_allocation_percentage: Option<f64>,
+) -> Result<(), String> {
+    for pct in [min_allocation_percentage, max_allocation_percentage]
+        .into_iter()
+        .flatten()
+    {
+        if !(0.0..=100.0).contains(&pct) {
+            warn!("Allocation bounds validation failed: {} out of range", pct);
+            return Err("Allocation bounds must be between 0 and 100".to_string());
+        }
+    }
+    if let (Some(min), Some(max)) = (min_allocation_percentage, max_allocation_percentage) {
+        if min > max {
+            warn!(
+                "Allocation bounds validation failed: minimum {} above maximum {}",
+                min, max
+            );
+            return Err("Minimum allocation percentage must not be above the maximum".to_string());
+        }
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn list_assignments(
     pool: tauri::State<'_, DbPool>,
@@ -87,10 +152,17 @@ pub async fn create_assignment(
         return Err("Start date must be before end date".to_string());
     }
 
+    let (is_pinned, pinned_allocation_percentage) =
+        validate_pinned_allocation(input.is_pinned, input.pinned_allocation_percentage)?;
+    let min_allocation_percentage = input.min_allocation_percentage.flatten();
+    let max_allocation_percentage = input.max_allocation_percentage.flatten();
+    validate_allocation_bounds(min_allocation_percentage, max_allocation_percentage)?;
+
     let result = sqlx::query(
         "INSERT INTO assignments 
-         (person_id, project_id, planning_period_id, productivity_factor, start_date, end_date) 
-         VALUES (?, ?, ?, ?, ?, ?)",
+         (person_id, project_id, planning_period_id, productivity_factor, start_date, end_date,
+          is_pinned, pinned_allocation_percentage, min_allocation_percentage, max_allocation_percentage) 
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(input.person_id)
     .bind(input.project_id)
@@ -98,6 +170,10 @@ pub async fn create_assignment(
     .bind(input.productivity_factor)
     .bind(&start_date)
     .bind(&end_date)
+    .bind(is_pinned)
+    .bind(pinned_allocation_percentage)
+    .bind(min_allocation_percentage)
+    .bind(max_allocation_percentage)
     .execute(pool.inner())
     .await
     .map_err(|e| {
@@ -117,6 +193,8 @@ pub async fn create_assignment(
             e.to_string()
         })?;
 
+    record_assignment_change(pool.inner(), &assignment, InputChangeAction::Added).await?;
+
     info!("Successfully created assignment");
     Ok(assignment)
 }
@@ -178,10 +256,42 @@ pub async fn update_assignment(
         return Err("Start date must be before end date".to_string());
     }
 
+    let current = sqlx::query_as::<_, Assignment>("SELECT * FROM assignments WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Assignment not found: {}", e);
+            format!("Assignment not found: {}", e)
+        })?;
+
+    // Omitting the pin keeps the assignment's current pinned allocation
+    let (is_pinned, pinned_allocation_percentage) = match input.is_pinned {
+        Some(_) => validate_pinned_allocation(input.is_pinned, input.pinned_allocation_percentage)?,
+        None if input.pinned_allocation_percentage.is_some() => {
+            warn!("Pinned allocation validation failed: percentage sent without is_pinned");
+            return Err(
+                "A pinned allocation percentage needs is_pinned, set it to pin or unpin the assignment"
+                    .to_string(),
+            );
+        }
+        None => (current.is_pinned, current.pinned_allocation_percentage),
+    };
+    // Omitted bounds keep their current values too, null clears them
+    let min_allocation_percentage = input
+        .min_allocation_percentage
+        .unwrap_or(current.min_allocation_percentage);
+    let max_allocation_percentage = input
+        .max_allocation_percentage
+        .unwrap_or(current.max_allocation_percentage);
+    validate_allocation_bounds(min_allocation_percentage, max_allocation_percentage)?;
+
     sqlx::query(
         "UPDATE assignments 
          SET person_id = ?, project_id = ?, planning_period_id = ?, 
-             productivity_factor = ?, start_date = ?, end_date = ? 
+             productivity_factor = ?, start_date = ?, end_date = ?,
+             is_pinned = ?, pinned_allocation_percentage = ?,
+             min_allocation_percentage = ?, max_allocation_percentage = ? 
          WHERE id = ?",
     )
     .bind(input.person_id)
@@ -190,6 +300,10 @@ pub async fn update_assignment(
     .bind(input.productivity_factor)
     .bind(&start_date)
     .bind(&end_date)
+    .bind(is_pinned)
+    .bind(pinned_allocation_percentage)
+    .bind(min_allocation_percentage)
+    .bind(max_allocation_percentage)
     .bind(id)
     .execute(pool.inner())
     .await
@@ -207,6 +321,12 @@ pub async fn update_assignment(
             e.to_string()
         })?;
 
+    record_assignment_change(pool.inner(), &assignment, InputChangeAction::Changed).await?;
+    if current.planning_period_id != assignment.planning_period_id {
+        // Moved out of its old planning period
+        record_assignment_change(pool.inner(), &current, InputChangeAction::Deleted).await?;
+    }
+
     info!("Successfully updated assignment ID: {}", id);
     Ok(assignment)
 }
@@ -215,6 +335,15 @@ pub async fn update_assignment(
 pub async fn delete_assignment(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting assignment ID: {}", id);
 
+    let assignment = sqlx::query_as::<_, Assignment>("SELECT * FROM assignments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch assignment: {}", e);
+            e.to_string()
+        })?;
+
     sqlx::query("DELETE FROM assignments WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -224,6 +353,10 @@ pub async fn delete_assignment(pool: tauri::State<'_, DbPool>, id: i64) -> Resul
             e.to_string()
         })?;
 
+    if let Some(assignment) = assignment {
+        record_assignment_change(pool.inner(), &assignment, InputChangeAction::Deleted).await?;
+    }
+
     info!("Successfully deleted assignment ID: {}", id);
     Ok(())
 }
@@ -1,6 +1,8 @@
 use crate::capacity::{
-    calculate_person_available_hours, AssignmentSummary, CapacityOverview, PersonAssignmentSummary,
-    PersonCapacity, ProjectStaffing,
+    calculate_assignment_effective_hours, is_over_utilization, load_calculation_staleness,
+    max_utilization, planning_period_bounds, timeline_ranges, AssignmentSummary, CapacityOverview,
+    CapacityTimeline, PeriodCapacityData, PersonAssignmentSummary, PersonCapacity, PersonTimeline,
+    ProjectStaffing, ProjectTimeline, SkillData, TimelineBucket, TimelinePoint,
 };
 use crate::db::DbPool;
 use crate::models::{Assignment, Person, PlanningPeriod, Project, ProjectRequirement};
@@ -25,11 +27,9 @@ pub async fn get_capacity_overview(
             .await
             .map_err(|e| format!("Failed to fetch planning period: {}", e))?;
 
-    // Load all people
-    let people = sqlx::query_as::<_, Person>("SELECT * FROM people ORDER BY name")
-        .fetch_all(pool.inner())
-        .await
-        .map_err(|e| format!("Failed to fetch people: {}", e))?;
+    // Load people, absences, holidays and overhead for the whole period at once
+    let data = PeriodCapacityData::load(planning_period, pool.inner()).await?;
+    let people = &data.people;
 
     // Load all projects
     let projects = sqlx::query_as::<_, Project>("SELECT * FROM projects ORDER BY name")
@@ -59,13 +59,25 @@ pub async fn get_capacity_overview(
             .await
             .map_err(|e| format!("Failed to fetch assignments: {}", e))?;
 
+    // Load skill proficiencies and the skills projects require in this period
+    let skills = SkillData::load(planning_period_id, pool.inner()).await?;
+
+    // Calculate available hours inside each assignment's date window
+    let mut assignment_breakdowns = HashMap::new();
+    for assignment in &assignments {
+        if let Some(person) = data.person(assignment.person_id) {
+            let breakdown = data.assignment_breakdown(person, assignment)?;
+            assignment_breakdowns.insert(assignment.id, breakdown);
+        }
+    }
+
     // Build people capacity
     let mut people_capacity = Vec::new();
     let mut over_committed_count = 0;
+    let mut over_cap_count = 0;
 
-    for person in &people {
-        let breakdown =
-            calculate_person_available_hours(person, &planning_period, pool.inner()).await?;
+    for person in people {
+        let breakdown = data.person_breakdown(person)?;
 
         let person_assignments: Vec<&Assignment> = assignments
             .iter()
@@ -79,7 +91,12 @@ pub async fn get_capacity_overview(
         for assignment in &person_assignments {
             let allocation_pct = assignment.calculated_allocation_percentage.unwrap_or(0.0);
 
-            let allocated_hours = breakdown.available_hours * (allocation_pct / 100.0);
+            // Allocation is a share of the hours available inside the assignment window
+            let window_available_hours = assignment_breakdowns
+                .get(&assignment.id)
+                .map(|b| b.available_hours)
+                .unwrap_or(0.0);
+            let allocated_hours = window_available_hours * (allocation_pct / 100.0);
             let effective_hours = assignment.calculated_effective_hours.unwrap_or(0.0);
 
             total_allocated_hours += allocated_hours;
@@ -110,16 +127,24 @@ pub async fn get_capacity_overview(
         if is_over_committed {
             over_committed_count += 1;
         }
+        let max_utilization_percentage = max_utilization(person);
+        let is_over_utilization_cap = is_over_utilization(utilization, max_utilization_percentage);
+        if is_over_utilization_cap {
+            over_cap_count += 1;
+        }
 
         people_capacity.push(PersonCapacity {
             person_id: person.id,
             person_name: person.name.clone(),
             person_email: person.email.clone(),
+            working_days: breakdown.working_days,
             total_available_hours: breakdown.available_hours,
             total_allocated_hours,
             total_effective_hours,
             utilization_percentage: utilization,
             is_over_committed,
+            max_utilization_percentage,
+            is_over_utilization_cap,
             assignments: assignment_summaries,
             absence_days: breakdown.absence_days,
             absence_hours: breakdown.absence_hours,
@@ -148,13 +173,10 @@ pub async fn get_capacity_overview(
             let mut assigned_people_summaries = Vec::new();
 
             for assignment in &project_assignments {
-                let person = people.iter().find(|p| p.id == assignment.person_id);
-
-                if let Some(person) = person {
-                    let breakdown =
-                        calculate_person_available_hours(person, &planning_period, pool.inner())
-                            .await?;
+                let person = data.person(assignment.person_id);
+                let breakdown = assignment_breakdowns.get(&assignment.id);
 
+                if let (Some(person), Some(breakdown)) = (person, breakdown) {
                     let allocation_pct = assignment.calculated_allocation_percentage.unwrap_or(0.0);
 
                     let allocated_hours = breakdown.available_hours * (allocation_pct / 100.0);
@@ -207,6 +229,7 @@ pub async fn get_capacity_overview(
                 is_viable,
                 shortfall,
                 assigned_people: assigned_people_summaries,
+                skill_staffing: skills.project_skill_staffing(project.id, &project_assignments),
             });
         }
     }
@@ -215,9 +238,11 @@ pub async fn get_capacity_overview(
         total_people: people.len(),
         total_projects: project_staffing.len(),
         over_committed_people: over_committed_count,
+        over_utilization_cap_people: over_cap_count,
         under_staffed_projects: under_staffed_count,
         people_capacity,
         project_staffing,
+        staleness: load_calculation_staleness(planning_period_id, pool.inner()).await?,
     };
 
     info!("Successfully generated capacity overview");
@@ -251,8 +276,11 @@ pub async fn get_person_capacity(
             .map_err(|e| format!("Failed to fetch planning period: {}", e))?;
 
     // Calculate available hours
-    let breakdown =
-        calculate_person_available_hours(&person, &planning_period, pool.inner()).await?;
+    let data = PeriodCapacityData::load(planning_period, pool.inner()).await?;
+    let breakdown = data.person_breakdown(&person)?;
+
+    // The loaded period data carries the person's cap with the app-wide default applied
+    let max_utilization_percentage = max_utilization(data.person(person.id).unwrap_or(&person));
 
     // Load assignments
     let assignments = sqlx::query_as::<_, Assignment>(
@@ -271,7 +299,9 @@ pub async fn get_person_capacity(
     for assignment in assignments {
         let allocation_pct = assignment.calculated_allocation_percentage.unwrap_or(0.0);
 
-        let allocated_hours = breakdown.available_hours * (allocation_pct / 100.0);
+        // Allocation is a share of the hours available inside the assignment window
+        let window_breakdown = data.assignment_breakdown(&person, &assignment)?;
+        let allocated_hours = window_breakdown.available_hours * (allocation_pct / 100.0);
         let effective_hours = assignment.calculated_effective_hours.unwrap_or(0.0);
 
         total_allocated_hours += allocated_hours;
@@ -303,11 +333,14 @@ pub async fn get_person_capacity(
         person_id: person.id,
         person_name: person.name.clone(),
         person_email: person.email,
+        working_days: breakdown.working_days,
         total_available_hours: breakdown.available_hours,
         total_allocated_hours,
         total_effective_hours,
         utilization_percentage: utilization,
         is_over_committed: utilization > 100.0,
+        max_utilization_percentage,
+        is_over_utilization_cap: is_over_utilization(utilization, max_utilization_percentage),
         assignments: assignment_summaries,
         absence_days: breakdown.absence_days,
         absence_hours: breakdown.absence_hours,
@@ -372,16 +405,16 @@ pub async fn get_project_staffing(
     let mut total_effective_hours = 0.0;
     let mut assigned_people_summaries = Vec::new();
 
-    for assignment in assignments {
-        // Load person
-        let person = sqlx::query_as::<_, Person>("SELECT * FROM people WHERE id = ?")
-            .bind(assignment.person_id)
-            .fetch_one(pool.inner())
-            .await
-            .map_err(|e| format!("Failed to fetch person: {}", e))?;
+    // Load people, absences, holidays and overhead for the whole period at once
+    let data = PeriodCapacityData::load(planning_period, pool.inner()).await?;
+    let skills = SkillData::load(planning_period_id, pool.inner()).await?;
+
+    for assignment in &assignments {
+        let person = data
+            .person(assignment.person_id)
+            .ok_or_else(|| format!("Failed to fetch person: {}", assignment.person_id))?;
 
-        let breakdown =
-            calculate_person_available_hours(&person, &planning_period, pool.inner()).await?;
+        let breakdown = data.assignment_breakdown(person, assignment)?;
 
         let allocation_pct = assignment.calculated_allocation_percentage.unwrap_or(0.0);
 
@@ -393,7 +426,7 @@ pub async fn get_project_staffing(
 
         assigned_people_summaries.push(PersonAssignmentSummary {
             assignment_id: assignment.id,
-            person_name: person.name,
+            person_name: person.name.clone(),
             allocation_percentage: allocation_pct,
             productivity_factor: assignment.productivity_factor,
             effective_hours,
@@ -420,6 +453,9 @@ pub async fn get_project_staffing(
         0.0
     };
 
+    let project_assignments: Vec<&Assignment> = assignments.iter().collect();
+    let skill_staffing = skills.project_skill_staffing(project_id, &project_assignments);
+
     let staffing = ProjectStaffing {
         project_id: project.id,
         project_name: project.name,
@@ -430,8 +466,173 @@ pub async fn get_project_staffing(
         is_viable,
         shortfall,
         assigned_people: assigned_people_summaries,
+        skill_staffing,
     };
 
     info!("Successfully generated project staffing");
     Ok(staffing)
 }
+
+#[tauri::command]
+pub async fn get_capacity_timeline(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+    granularity: String,
+) -> Result<CapacityTimeline, String> {
+    debug!(
+        "Getting {} capacity timeline for planning period ID: {}",
+        granularity, planning_period_id
+    );
+
+    // Load planning period
+    let planning_period =
+        sqlx::query_as::<_, PlanningPeriod>("SELECT * FROM planning_periods WHERE id = ?")
+            .bind(planning_period_id)
+            .fetch_one(pool.inner())
+            .await
+            .map_err(|e| format!("Failed to fetch planning period: {}", e))?;
+
+    let (period_start, period_end) = planning_period_bounds(&planning_period)?;
+    let ranges = timeline_ranges(period_start, period_end, &granularity)?;
+
+    // Load people, absences, holidays and overhead for the whole period at once
+    let data = PeriodCapacityData::load(planning_period, pool.inner()).await?;
+
+    // Load all projects
+    let projects = sqlx::query_as::<_, Project>("SELECT * FROM projects ORDER BY name")
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|e| format!("Failed to fetch projects: {}", e))?;
+
+    // Load project requirements
+    let requirements = sqlx::query_as::<_, ProjectRequirement>(
+        "SELECT * FROM project_requirements WHERE planning_period_id = ?",
+    )
+    .bind(planning_period_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| format!("Failed to fetch project requirements: {}", e))?;
+
+    // Load all assignments for this planning period
+    let assignments =
+        sqlx::query_as::<_, Assignment>("SELECT * FROM assignments WHERE planning_period_id = ?")
+            .bind(planning_period_id)
+            .fetch_all(pool.inner())
+            .await
+            .map_err(|e| format!("Failed to fetch assignments: {}", e))?;
+
+    // Calculate each assignment's available, allocated and effective hours per bucket
+    // (the part of its date window that falls inside the bucket)
+    let mut assignment_points: HashMap<i64, Vec<TimelinePoint>> = HashMap::new();
+    for assignment in &assignments {
+        let Some(person) = data.person(assignment.person_id) else {
+            continue;
+        };
+        let allocation_pct = assignment.calculated_allocation_percentage.unwrap_or(0.0);
+
+        let mut points = Vec::new();
+        for (_, bucket_start, bucket_end) in &ranges {
+            let available_hours = data
+                .assignment_breakdown_in_range(person, assignment, *bucket_start, *bucket_end)?
+                .map(|b| b.available_hours)
+                .unwrap_or(0.0);
+
+            points.push(TimelinePoint {
+                available_hours,
+                allocated_hours: available_hours * (allocation_pct / 100.0),
+                effective_hours: calculate_assignment_effective_hours(
+                    available_hours,
+                    allocation_pct,
+                    assignment.productivity_factor,
+                ),
+            });
+        }
+        assignment_points.insert(assignment.id, points);
+    }
+
+    // Sum the points of a set of assignments bucket by bucket
+    let sum_points = |assignment_ids: Vec<i64>| -> Vec<TimelinePoint> {
+        (0..ranges.len())
+            .map(|i| {
+                let mut point = TimelinePoint {
+                    available_hours: 0.0,
+                    allocated_hours: 0.0,
+                    effective_hours: 0.0,
+                };
+                for id in &assignment_ids {
+                    if let Some(points) = assignment_points.get(id) {
+                        point.allocated_hours += points[i].allocated_hours;
+                        point.effective_hours += points[i].effective_hours;
+                        point.available_hours += points[i].available_hours;
+                    }
+                }
+                point
+            })
+            .collect()
+    };
+
+    // Build people series: available hours cover the whole bucket, not just assignments
+    let mut people_timeline = Vec::new();
+    for person in &data.people {
+        let mut points = sum_points(
+            assignments
+                .iter()
+                .filter(|a| a.person_id == person.id)
+                .map(|a| a.id)
+                .collect(),
+        );
+
+        for (point, (_, bucket_start, bucket_end)) in points.iter_mut().zip(&ranges) {
+            let breakdown = data.breakdown_in_range(person, *bucket_start, *bucket_end)?;
+            point.available_hours = breakdown.available_hours;
+        }
+
+        people_timeline.push(PersonTimeline {
+            person_id: person.id,
+            person_name: person.name.clone(),
+            points,
+        });
+    }
+
+    // Build project series for projects with requirements in this planning period:
+    // available hours are the assigned people's hours inside their assignment windows
+    let mut projects_timeline = Vec::new();
+    for project in &projects {
+        if let Some(requirement) = requirements.iter().find(|r| r.project_id == project.id) {
+            projects_timeline.push(ProjectTimeline {
+                project_id: project.id,
+                project_name: project.name.clone(),
+                required_hours: requirement.required_hours,
+                points: sum_points(
+                    assignments
+                        .iter()
+                        .filter(|a| a.project_id == project.id)
+                        .map(|a| a.id)
+                        .collect(),
+                ),
+            });
+        }
+    }
+
+    let buckets = ranges
+        .iter()
+        .map(|(label, bucket_start, bucket_end)| TimelineBucket {
+            label: label.clone(),
+            start_date: bucket_start.format("%Y-%m-%d").to_string(),
+            end_date: bucket_end.format("%Y-%m-%d").to_string(),
+        })
+        .collect();
+
+    info!(
+        "Successfully generated capacity timeline with {} buckets",
+        ranges.len()
+    );
+    Ok(CapacityTimeline {
+        planning_period_id,
+        granularity,
+        buckets,
+        people: people_timeline,
+        projects: projects_timeline,
+        staleness: load_calculation_staleness(planning_period_id, pool.inner()).await?,
+    })
+}
@@ -1,7 +1,46 @@
 use crate::api;
+use crate::capacity::{record_holiday_changes, InputChangeAction};
 use crate::db::DbPool;
-use crate::models::{Country, CountryDependencies, CreateCountryInput};
+use crate::models::{Country, CountryDependencies, CreateCountryInput, Holiday};
 use log::{debug, error, info, warn};
+use std::collections::BTreeMap;
+
+/// Mark the planning periods losing holidays with their countries as stale
+///
+/// Covers the holidays of one country, or of all countries when none is given.
+async fn record_country_holidays_deleted(
+    pool: &DbPool,
+    country_id: Option<i64>,
+) -> Result<(), String> {
+    let holidays =
+        sqlx::query_as::<_, Holiday>("SELECT * FROM holidays WHERE ? IS NULL OR country_id = ?")
+            .bind(country_id)
+            .bind(country_id)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch holidays: {}", e);
+                e.to_string()
+            })?;
+
+    let mut ranges_by_country: BTreeMap<i64, Vec<Vec<(String, String)>>> = BTreeMap::new();
+    for holiday in holidays {
+        ranges_by_country
+            .entry(holiday.country_id)
+            .or_default()
+            .push(vec![(holiday.start_date, holiday.end_date)]);
+    }
+    for (country_id, changed_ranges) in &ranges_by_country {
+        record_holiday_changes(
+            *country_id,
+            changed_ranges,
+            InputChangeAction::Deleted,
+            pool,
+        )
+        .await?;
+    }
+    Ok(())
+}
 
 #[tauri::command]
 pub async fn list_countries(pool: tauri::State<'_, DbPool>) -> Result<Vec<Country>, String> {
@@ -110,6 +149,9 @@ pub async fn update_country(
 pub async fn delete_country(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting country ID: {}", id);
 
+    // Record first, the people lose their country with it
+    record_country_holidays_deleted(pool.inner(), Some(id)).await?;
+
     // Delete country (CASCADE will delete holidays, SET NULL will update people)
     sqlx::query("DELETE FROM countries WHERE id = ?")
         .bind(id)
@@ -247,6 +289,9 @@ pub async fn delete_all_countries_and_holidays(
 ) -> Result<(), String> {
     warn!("DESTRUCTIVE OPERATION: Deleting all countries and holidays");
 
+    // Record first, the people lose their countries with it
+    record_country_holidays_deleted(pool.inner(), None).await?;
+
     // Clear people's country_id references
     sqlx::query("UPDATE people SET country_id = NULL")
         .execute(pool.inner())
@@ -1,11 +1,39 @@
 use crate::api;
+use crate::capacity::{record_holiday_changes, InputChangeAction};
 use crate::db::DbPool;
 use crate::models::{
     Country, CreateHolidayInput, Holiday, HolidayImportPreview, HolidayPreviewItem,
     HolidayWithCountry, ImportHolidaysResult, Person,
 };
 use log::{debug, error, info, warn};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+
+/// Validate a holiday's date range and optional partial-day amount
+fn validate_holiday_input(input: &CreateHolidayInput) -> Result<(), String> {
+    if input.start_date > input.end_date {
+        warn!("Invalid date range: start date after end date");
+        return Err("Start date must be on or before end date".to_string());
+    }
+
+    // Partial-day holidays are expressed either in hours or as a fraction of the day
+    if input.hours.is_some() && input.day_fraction.is_some() {
+        warn!("Invalid partial-day holiday: both hours and day fraction set");
+        return Err("Specify either hours or a fraction of the day, not both".to_string());
+    }
+    if input.hours.is_some_and(|hours| hours <= 0.0) {
+        warn!("Invalid partial-day holiday: hours must be positive");
+        return Err("Holiday hours must be greater than 0".to_string());
+    }
+    if input
+        .day_fraction
+        .is_some_and(|fraction| fraction <= 0.0 || fraction > 1.0)
+    {
+        warn!("Invalid partial-day holiday: day fraction out of range");
+        return Err("Fraction of the day must be greater than 0 and at most 1".to_string());
+    }
+
+    Ok(())
+}
 
 #[tauri::command]
 pub async fn list_holidays(
@@ -18,7 +46,7 @@ pub async fn list_holidays(
         debug!("Filtering by country ID: {}", cid);
         sqlx::query_as::<_, HolidayWithCountry>(
             "SELECT h.id, h.country_id, c.iso_code as country_iso_code, c.name as country_name,
-                    h.name, h.start_date, h.end_date, h.created_at
+                    h.name, h.start_date, h.end_date, h.hours, h.day_fraction, h.created_at
              FROM holidays h
              JOIN countries c ON h.country_id = c.id
              WHERE h.country_id = ?
@@ -30,7 +58,7 @@ pub async fn list_holidays(
     } else {
         sqlx::query_as::<_, HolidayWithCountry>(
             "SELECT h.id, h.country_id, c.iso_code as country_iso_code, c.name as country_name,
-                    h.name, h.start_date, h.end_date, h.created_at
+                    h.name, h.start_date, h.end_date, h.hours, h.day_fraction, h.created_at
              FROM holidays h
              JOIN countries c ON h.country_id = c.id
              ORDER BY c.name, h.start_date",
@@ -112,11 +140,7 @@ pub async fn create_holiday(
 ) -> Result<Holiday, String> {
     debug!("Creating holiday for country ID: {}", input.country_id);
 
-    // Validate dates: start_date <= end_date
-    if input.start_date > input.end_date {
-        warn!("Invalid date range: start date after end date");
-        return Err("Start date must be on or before end date".to_string());
-    }
+    validate_holiday_input(&input)?;
 
     // Check for overlapping holidays in the same country
     let overlapping_count = sqlx::query_scalar::<_, i64>(
@@ -144,12 +168,14 @@ pub async fn create_holiday(
     }
 
     let result = sqlx::query(
-        "INSERT INTO holidays (country_id, name, start_date, end_date) VALUES (?, ?, ?, ?)",
+        "INSERT INTO holidays (country_id, name, start_date, end_date, hours, day_fraction) VALUES (?, ?, ?, ?, ?, ?)",
     )
     .bind(input.country_id)
     .bind(&input.name)
     .bind(&input.start_date)
     .bind(&input.end_date)
+    .bind(input.hours)
+    .bind(input.day_fraction)
     .execute(pool.inner())
     .await
     .map_err(|e| {
@@ -169,6 +195,14 @@ pub async fn create_holiday(
             e.to_string()
         })?;
 
+    record_holiday_changes(
+        holiday.country_id,
+        &[vec![(holiday.start_date.clone(), holiday.end_date.clone())]],
+        InputChangeAction::Added,
+        pool.inner(),
+    )
+    .await?;
+
     info!("Successfully created holiday");
     Ok(holiday)
 }
@@ -181,11 +215,7 @@ pub async fn update_holiday(
 ) -> Result<Holiday, String> {
     debug!("Updating holiday ID: {}", id);
 
-    // Validate dates: start_date <= end_date
-    if input.start_date > input.end_date {
-        warn!("Invalid date range: start date after end date");
-        return Err("Start date must be on or before end date".to_string());
-    }
+    validate_holiday_input(&input)?;
 
     // Check for overlapping holidays in the same country (excluding current holiday)
     let overlapping_count = sqlx::query_scalar::<_, i64>(
@@ -214,13 +244,24 @@ pub async fn update_holiday(
         return Err("A holiday already exists for this country during this period. Overlapping holidays are not allowed.".to_string());
     }
 
+    let previous = sqlx::query_as::<_, Holiday>("SELECT * FROM holidays WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch holiday: {}", e);
+            e.to_string()
+        })?;
+
     sqlx::query(
-        "UPDATE holidays SET country_id = ?, name = ?, start_date = ?, end_date = ? WHERE id = ?",
+        "UPDATE holidays SET country_id = ?, name = ?, start_date = ?, end_date = ?, hours = ?, day_fraction = ? WHERE id = ?",
     )
     .bind(input.country_id)
     .bind(&input.name)
     .bind(&input.start_date)
     .bind(&input.end_date)
+    .bind(input.hours)
+    .bind(input.day_fraction)
     .bind(id)
     .execute(pool.inner())
     .await
@@ -238,6 +279,34 @@ pub async fn update_holiday(
             e.to_string()
         })?;
 
+    // Both the old and the new dates may fall into planning periods
+    let previous_range = (previous.start_date, previous.end_date);
+    let new_range = (holiday.start_date.clone(), holiday.end_date.clone());
+    if previous.country_id == holiday.country_id {
+        record_holiday_changes(
+            holiday.country_id,
+            &[vec![previous_range, new_range]],
+            InputChangeAction::Changed,
+            pool.inner(),
+        )
+        .await?;
+    } else {
+        record_holiday_changes(
+            previous.country_id,
+            &[vec![previous_range]],
+            InputChangeAction::Deleted,
+            pool.inner(),
+        )
+        .await?;
+        record_holiday_changes(
+            holiday.country_id,
+            &[vec![new_range]],
+            InputChangeAction::Added,
+            pool.inner(),
+        )
+        .await?;
+    }
+
     info!("Successfully updated holiday ID: {}", id);
     Ok(holiday)
 }
@@ -246,6 +315,15 @@ pub async fn update_holiday(
 pub async fn delete_holiday(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting holiday ID: {}", id);
 
+    let holiday = sqlx::query_as::<_, Holiday>("SELECT * FROM holidays WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch holiday: {}", e);
+            e.to_string()
+        })?;
+
     sqlx::query("DELETE FROM holidays WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -255,6 +333,16 @@ pub async fn delete_holiday(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(
             e.to_string()
         })?;
 
+    if let Some(holiday) = holiday {
+        record_holiday_changes(
+            holiday.country_id,
+            &[vec![(holiday.start_date, holiday.end_date)]],
+            InputChangeAction::Deleted,
+            pool.inner(),
+        )
+        .await?;
+    }
+
     info!("Successfully deleted holiday ID: {}", id);
     Ok(())
 }
@@ -272,25 +360,30 @@ pub async fn batch_create_holidays(
         e.to_string()
     })?;
 
+    let mut ranges_by_country: BTreeMap<i64, Vec<Vec<(String, String)>>> = BTreeMap::new();
     for holiday in holidays {
-        // Validate dates
-        if holiday.start_date > holiday.end_date {
-            return Err("Start date must be on or before end date for all holidays".to_string());
-        }
+        validate_holiday_input(&holiday)?;
 
         sqlx::query(
-            "INSERT INTO holidays (country_id, name, start_date, end_date) VALUES (?, ?, ?, ?)",
+            "INSERT INTO holidays (country_id, name, start_date, end_date, hours, day_fraction) VALUES (?, ?, ?, ?, ?, ?)",
         )
         .bind(holiday.country_id)
         .bind(&holiday.name)
         .bind(&holiday.start_date)
         .bind(&holiday.end_date)
+        .bind(holiday.hours)
+        .bind(holiday.day_fraction)
         .execute(&mut *tx)
         .await
         .map_err(|e| {
             error!("Failed to insert holiday in batch: {}", e);
             e.to_string()
         })?;
+
+        ranges_by_country
+            .entry(holiday.country_id)
+            .or_default()
+            .push(vec![(holiday.start_date, holiday.end_date)]);
     }
 
     tx.commit().await.map_err(|e| {
@@ -298,6 +391,16 @@ pub async fn batch_create_holidays(
         e.to_string()
     })?;
 
+    for (country_id, changed_ranges) in &ranges_by_country {
+        record_holiday_changes(
+            *country_id,
+            changed_ranges,
+            InputChangeAction::Added,
+            pool.inner(),
+        )
+        .await?;
+    }
+
     info!("Successfully batch created holidays");
     Ok(())
 }
@@ -415,6 +518,7 @@ pub async fn import_holidays_from_api(
         })?;
 
     let mut results = Vec::new();
+    let mut imported_ranges = Vec::new();
 
     for year in years {
         info!("Importing holidays for {} in {}", country_code_upper, year);
@@ -480,6 +584,7 @@ pub async fn import_holidays_from_api(
             })?;
 
             imported_count += 1;
+            imported_ranges.push(vec![(api_holiday.date.clone(), api_holiday.date.clone())]);
         }
 
         info!(
@@ -495,5 +600,13 @@ pub async fn import_holidays_from_api(
         });
     }
 
+    record_holiday_changes(
+        country.id,
+        &imported_ranges,
+        InputChangeAction::Added,
+        pool.inner(),
+    )
+    .await?;
+
     Ok(results)
 }
@@ -1,3 +1,6 @@
+use crate::capacity::{
+    input_subject_name, job_planning_periods, record_input_change, ChangedInput, InputChangeAction,
+};
 use crate::db::DbPool;
 use crate::models::{
     CreateJobInput, CreateJobOverheadTaskInput, CreatePersonJobAssignmentInput, Job,
@@ -6,6 +9,42 @@ use crate::models::{
 };
 use log::{debug, error, info};
 
+/// Mark the planning periods in which someone holds the job as stale
+async fn record_job_change(
+    pool: &DbPool,
+    job_id: i64,
+    input: ChangedInput,
+    action: InputChangeAction,
+) -> Result<(), String> {
+    let planning_period_ids = job_planning_periods(job_id, pool).await?;
+    let job_name = input_subject_name("jobs", job_id, pool).await?;
+    record_input_change(
+        &planning_period_ids,
+        input,
+        action,
+        job_name.as_deref(),
+        pool,
+    )
+    .await
+}
+
+/// Mark the planning period of a person's job assignment as stale
+async fn record_person_job_change(
+    pool: &DbPool,
+    assignment: &PersonJobAssignment,
+    action: InputChangeAction,
+) -> Result<(), String> {
+    let person_name = input_subject_name("people", assignment.person_id, pool).await?;
+    record_input_change(
+        &[assignment.planning_period_id],
+        ChangedInput::JobAssignment,
+        action,
+        person_name.as_deref(),
+        pool,
+    )
+    .await
+}
+
 // ============================================================================
 // Job Commands (CRUD for global job templates)
 // ============================================================================
@@ -171,6 +210,15 @@ pub async fn update_job(
 pub async fn delete_job(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting job ID: {}", id);
 
+    // Record first, the job's assignments go with it
+    record_job_change(
+        pool.inner(),
+        id,
+        ChangedInput::Job,
+        InputChangeAction::Deleted,
+    )
+    .await?;
+
     sqlx::query("DELETE FROM jobs WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -288,6 +336,14 @@ pub async fn create_job_overhead_task(
                 e.to_string()
             })?;
 
+    record_job_change(
+        pool.inner(),
+        task.job_id,
+        ChangedInput::OverheadTask,
+        InputChangeAction::Added,
+    )
+    .await?;
+
     info!("Successfully created job overhead task: {}", task.name);
     Ok(task)
 }
@@ -332,6 +388,14 @@ pub async fn update_job_overhead_task(
                 e.to_string()
             })?;
 
+    record_job_change(
+        pool.inner(),
+        task.job_id,
+        ChangedInput::OverheadTask,
+        InputChangeAction::Changed,
+    )
+    .await?;
+
     info!("Successfully updated job overhead task ID: {}", id);
     Ok(task)
 }
@@ -343,6 +407,16 @@ pub async fn delete_job_overhead_task(
 ) -> Result<(), String> {
     debug!("Deleting job overhead task ID: {}", id);
 
+    let task =
+        sqlx::query_as::<_, JobOverheadTask>("SELECT * FROM job_overhead_tasks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool.inner())
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch job overhead task: {}", e);
+                e.to_string()
+            })?;
+
     sqlx::query("DELETE FROM job_overhead_tasks WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -352,6 +426,16 @@ pub async fn delete_job_overhead_task(
             e.to_string()
         })?;
 
+    if let Some(task) = task {
+        record_job_change(
+            pool.inner(),
+            task.job_id,
+            ChangedInput::OverheadTask,
+            InputChangeAction::Deleted,
+        )
+        .await?;
+    }
+
     info!("Successfully deleted job overhead task ID: {}", id);
     Ok(())
 }
@@ -479,6 +563,8 @@ pub async fn create_person_job_assignment(
         e.to_string()
     })?;
 
+    record_person_job_change(pool.inner(), &assignment, InputChangeAction::Added).await?;
+
     info!("Successfully created person job assignment");
     Ok(assignment)
 }
@@ -550,6 +636,7 @@ pub async fn batch_create_person_job_assignments(
             e.to_string()
         })?;
 
+        record_person_job_change(pool.inner(), &assignment, InputChangeAction::Added).await?;
         assignments.push(assignment);
     }
 
@@ -567,6 +654,17 @@ pub async fn delete_person_job_assignment(
 ) -> Result<(), String> {
     debug!("Deleting person job assignment ID: {}", id);
 
+    let assignment = sqlx::query_as::<_, PersonJobAssignment>(
+        "SELECT * FROM person_job_assignments WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch person job assignment: {}", e);
+        e.to_string()
+    })?;
+
     sqlx::query("DELETE FROM person_job_assignments WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -576,6 +674,10 @@ pub async fn delete_person_job_assignment(
             e.to_string()
         })?;
 
+    if let Some(assignment) = assignment {
+        record_person_job_change(pool.inner(), &assignment, InputChangeAction::Deleted).await?;
+    }
+
     info!("Successfully deleted person job assignment ID: {}", id);
     Ok(())
 }
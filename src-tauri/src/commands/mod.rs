@@ -8,8 +8,13 @@ mod jobs;
 mod optimization;
 mod people;
 mod planning_periods;
+mod project_dependencies;
 mod projects;
 mod requirements;
+mod settings;
+mod simulation;
+mod skills;
+mod staffing;
 
 // Re-export all commands for lib.rs
 pub use absences::*;
@@ -21,5 +26,10 @@ pub use jobs::*;
 pub use optimization::*;
 pub use people::*;
 pub use planning_periods::*;
+pub use project_dependencies::*;
 pub use projects::*;
 pub use requirements::*;
+pub use settings::*;
+pub use simulation::*;
+pub use skills::*;
+pub use staffing::*;
@@ -1,4 +1,11 @@
-use crate::capacity::{optimize_assignments_proportional, OptimizationResult};
+use crate::capacity::{
+    apply_optimization_preview, compare_stored_optimization_runs, list_stored_optimization_runs,
+    load_auto_optimization_setting, load_stored_optimization_run, optimization_strategy,
+    optimize_assignments_with_strategy, optimize_planning_period_sequence, preview_optimization,
+    restore_stored_optimization_run, save_auto_optimization_setting, AutoOptimizationSetting,
+    MultiPeriodOptimizationResult, OptimizationOptions, OptimizationPreview, OptimizationResult,
+    OptimizationRun, OptimizationRunComparison, OptimizationRunSummary, RestoredOptimizationRun,
+};
 use crate::db::DbPool;
 use log::info;
 
@@ -6,14 +13,138 @@ use log::info;
 pub async fn optimize_assignments(
     pool: tauri::State<'_, DbPool>,
     planning_period_id: i64,
+    strategy: Option<String>,
+    options: Option<OptimizationOptions>,
 ) -> Result<OptimizationResult, String> {
+    let strategy = optimization_strategy(strategy.as_deref(), options.unwrap_or_default())?;
+
     info!(
-        "Running optimization for planning period ID: {}",
+        "Running {} optimization for planning period ID: {}",
+        strategy.name(),
         planning_period_id
     );
 
-    let result = optimize_assignments_proportional(planning_period_id, pool.inner()).await?;
+    let result =
+        optimize_assignments_with_strategy(planning_period_id, strategy.as_ref(), pool.inner())
+            .await?;
 
     info!("Optimization completed successfully");
     Ok(result)
 }
+
+#[tauri::command]
+pub async fn optimize_planning_periods(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_ids: Vec<i64>,
+    strategy: Option<String>,
+    options: Option<OptimizationOptions>,
+) -> Result<MultiPeriodOptimizationResult, String> {
+    let strategy = optimization_strategy(strategy.as_deref(), options.unwrap_or_default())?;
+
+    info!(
+        "Running {} optimization for planning period IDs: {:?}",
+        strategy.name(),
+        planning_period_ids
+    );
+
+    let result =
+        optimize_planning_period_sequence(&planning_period_ids, strategy.as_ref(), pool.inner())
+            .await?;
+
+    info!("Multi-period optimization completed successfully");
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn preview_assignment_optimization(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+    strategy: Option<String>,
+    options: Option<OptimizationOptions>,
+) -> Result<OptimizationPreview, String> {
+    let strategy = optimization_strategy(strategy.as_deref(), options.unwrap_or_default())?;
+
+    info!(
+        "Previewing {} optimization for planning period ID: {}",
+        strategy.name(),
+        planning_period_id
+    );
+
+    preview_optimization(planning_period_id, strategy.as_ref(), pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn apply_assignment_optimization(
+    pool: tauri::State<'_, DbPool>,
+    preview: OptimizationPreview,
+) -> Result<OptimizationResult, String> {
+    info!(
+        "Applying optimization preview for planning period ID: {}",
+        preview.planning_period_id
+    );
+
+    let result = apply_optimization_preview(&preview, pool.inner()).await?;
+
+    info!("Optimization preview applied successfully");
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_optimization_runs(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+) -> Result<Vec<OptimizationRunSummary>, String> {
+    list_stored_optimization_runs(planning_period_id, pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn get_optimization_run(
+    pool: tauri::State<'_, DbPool>,
+    id: i64,
+) -> Result<OptimizationRun, String> {
+    load_stored_optimization_run(id, pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn compare_optimization_runs(
+    pool: tauri::State<'_, DbPool>,
+    base_run_id: i64,
+    other_run_id: i64,
+) -> Result<OptimizationRunComparison, String> {
+    compare_stored_optimization_runs(base_run_id, other_run_id, pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn restore_optimization_run(
+    pool: tauri::State<'_, DbPool>,
+    id: i64,
+) -> Result<RestoredOptimizationRun, String> {
+    info!("Restoring optimization run ID: {}", id);
+    restore_stored_optimization_run(id, pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn get_auto_optimization(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+) -> Result<AutoOptimizationSetting, String> {
+    load_auto_optimization_setting(planning_period_id, pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn set_auto_optimization(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+    enabled: bool,
+    strategy: Option<String>,
+    options: Option<OptimizationOptions>,
+) -> Result<AutoOptimizationSetting, String> {
+    save_auto_optimization_setting(
+        planning_period_id,
+        enabled,
+        strategy.as_deref(),
+        options.unwrap_or_default(),
+        pool.inner(),
+    )
+    .await
+}
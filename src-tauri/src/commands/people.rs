@@ -1,6 +1,20 @@
+use crate::capacity::{
+    input_subject_name, person_planning_periods, record_input_change, ChangedInput,
+    InputChangeAction,
+};
 use crate::db::DbPool;
 use crate::models::{CreatePersonInput, Person, PersonDependencies, PersonWithCountry};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+
+/// Validate a planned utilization cap
+pub(crate) fn validate_max_utilization(pct: f64) -> Result<(), String> {
+    if pct > 0.0 && pct <= 100.0 {
+        Ok(())
+    } else {
+        warn!("Utilization cap validation failed: {} out of range", pct);
+        Err("Maximum utilization must be above 0 and at most 100".to_string())
+    }
+}
 
 #[tauri::command]
 pub async fn list_people(pool: tauri::State<'_, DbPool>) -> Result<Vec<Person>, String> {
@@ -34,6 +48,7 @@ pub async fn list_people_with_countries(
             c.iso_code as country_iso_code,
             c.name as country_name,
             p.working_days,
+            p.max_utilization_percentage,
             p.created_at
          FROM people p
          LEFT JOIN countries c ON p.country_id = c.id
@@ -58,14 +73,19 @@ pub async fn create_person(
     pool: tauri::State<'_, DbPool>,
     input: CreatePersonInput,
 ) -> Result<Person, String> {
+    if let Some(pct) = input.max_utilization_percentage {
+        validate_max_utilization(pct)?;
+    }
+
     let result = sqlx::query(
-        "INSERT INTO people (name, email, available_hours_per_week, country_id, working_days) VALUES (?, ?, ?, ?, ?)",
+        "INSERT INTO people (name, email, available_hours_per_week, country_id, working_days, max_utilization_percentage) VALUES (?, ?, ?, ?, ?, ?)",
     )
     .bind(&input.name)
     .bind(&input.email)
     .bind(input.available_hours_per_week)
     .bind(input.country_id)
     .bind(&input.working_days)
+    .bind(input.max_utilization_percentage)
     .execute(pool.inner())
     .await
     .map_err(|e| e.to_string())?;
@@ -87,14 +107,25 @@ pub async fn update_person(
     id: i64,
     input: CreatePersonInput,
 ) -> Result<Person, String> {
+    if let Some(pct) = input.max_utilization_percentage {
+        validate_max_utilization(pct)?;
+    }
+
+    let previous = sqlx::query_as::<_, Person>("SELECT * FROM people WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| e.to_string())?;
+
     sqlx::query(
-        "UPDATE people SET name = ?, email = ?, available_hours_per_week = ?, country_id = ?, working_days = ? WHERE id = ?",
+        "UPDATE people SET name = ?, email = ?, available_hours_per_week = ?, country_id = ?, working_days = ?, max_utilization_percentage = ? WHERE id = ?",
     )
     .bind(&input.name)
     .bind(&input.email)
     .bind(input.available_hours_per_week)
     .bind(input.country_id)
     .bind(&input.working_days)
+    .bind(input.max_utilization_percentage)
     .bind(id)
     .execute(pool.inner())
     .await
@@ -106,6 +137,23 @@ pub async fn update_person(
         .await
         .map_err(|e| e.to_string())?;
 
+    // Name and email don't change anyone's capacity
+    let capacity_changed = previous.available_hours_per_week != person.available_hours_per_week
+        || previous.country_id != person.country_id
+        || previous.working_days != person.working_days
+        || previous.max_utilization_percentage != person.max_utilization_percentage;
+    if capacity_changed {
+        let planning_period_ids = person_planning_periods(id, pool.inner()).await?;
+        record_input_change(
+            &planning_period_ids,
+            ChangedInput::Person,
+            InputChangeAction::Changed,
+            Some(&person.name),
+            pool.inner(),
+        )
+        .await?;
+    }
+
     Ok(person)
 }
 
@@ -113,11 +161,24 @@ pub async fn update_person(
 pub async fn delete_person(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting person ID: {}", id);
 
+    // Record first, the person's assignments go with them
+    let planning_period_ids = person_planning_periods(id, pool.inner()).await?;
+    let person_name = input_subject_name("people", id, pool.inner()).await?;
+    record_input_change(
+        &planning_period_ids,
+        ChangedInput::Person,
+        InputChangeAction::Deleted,
+        person_name.as_deref(),
+        pool.inner(),
+    )
+    .await?;
+
     // Clear calculated fields for assignments involving this person before deletion
     sqlx::query(
         "UPDATE assignments 
          SET calculated_allocation_percentage = NULL,
              calculated_effective_hours = NULL,
+             calculated_start_date = NULL,
              last_calculated_at = NULL
          WHERE person_id = ?",
     )
@@ -1,11 +1,17 @@
+use crate::capacity::{
+    load_all_calculation_staleness, load_auto_optimized_planning_periods, record_input_change,
+    ChangedInput, InputChangeAction,
+};
 use crate::db::DbPool;
-use crate::models::{CreatePlanningPeriodInput, PlanningPeriod, PlanningPeriodDependencies};
+use crate::models::{
+    CreatePlanningPeriodInput, PlanningPeriod, PlanningPeriodDependencies, PlanningPeriodWithStatus,
+};
 use log::{debug, error, info};
 
 #[tauri::command]
 pub async fn list_planning_periods(
     pool: tauri::State<'_, DbPool>,
-) -> Result<Vec<PlanningPeriod>, String> {
+) -> Result<Vec<PlanningPeriodWithStatus>, String> {
     debug!("Fetching all planning periods");
 
     let periods = sqlx::query_as::<_, PlanningPeriod>(
@@ -18,6 +24,26 @@ pub async fn list_planning_periods(
         e.to_string()
     })?;
 
+    // Flag the periods whose inputs changed since their last optimization
+    let mut staleness = load_all_calculation_staleness(pool.inner()).await?;
+    let auto_optimized = load_auto_optimized_planning_periods(pool.inner()).await?;
+    let periods: Vec<PlanningPeriodWithStatus> = periods
+        .into_iter()
+        .map(|period| {
+            let status = staleness.remove(&period.id).unwrap_or_default();
+            PlanningPeriodWithStatus {
+                id: period.id,
+                name: period.name,
+                start_date: period.start_date,
+                end_date: period.end_date,
+                created_at: period.created_at,
+                is_stale: status.is_stale,
+                stale_reasons: status.reasons,
+                auto_optimize: auto_optimized.contains(&period.id),
+            }
+        })
+        .collect();
+
     info!("Successfully fetched {} planning periods", periods.len());
     Ok(periods)
 }
@@ -53,6 +79,13 @@ pub async fn update_planning_period(
     id: i64,
     input: CreatePlanningPeriodInput,
 ) -> Result<PlanningPeriod, String> {
+    let previous =
+        sqlx::query_as::<_, PlanningPeriod>("SELECT * FROM planning_periods WHERE id = ?")
+            .bind(id)
+            .fetch_one(pool.inner())
+            .await
+            .map_err(|e| e.to_string())?;
+
     sqlx::query("UPDATE planning_periods SET name = ?, start_date = ?, end_date = ? WHERE id = ?")
         .bind(&input.name)
         .bind(&input.start_date)
@@ -68,6 +101,18 @@ pub async fn update_planning_period(
         .await
         .map_err(|e| e.to_string())?;
 
+    // Renaming doesn't change any calculation
+    if previous.start_date != period.start_date || previous.end_date != period.end_date {
+        record_input_change(
+            &[id],
+            ChangedInput::PlanningPeriod,
+            InputChangeAction::Changed,
+            None,
+            pool.inner(),
+        )
+        .await?;
+    }
+
     Ok(period)
 }
 
@@ -80,6 +125,7 @@ pub async fn delete_planning_period(pool: tauri::State<'_, DbPool>, id: i64) ->
         "UPDATE assignments 
          SET calculated_allocation_percentage = NULL,
              calculated_effective_hours = NULL,
+             calculated_start_date = NULL,
              last_calculated_at = NULL
          WHERE planning_period_id = ?",
     )
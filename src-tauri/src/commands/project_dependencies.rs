@@ -0,0 +1,145 @@
+use crate::capacity::{
+    creates_dependency_cycle, input_subject_name, project_planning_periods, record_input_change,
+    ChangedInput, InputChangeAction,
+};
+use crate::db::DbPool;
+use crate::models::{CreateProjectDependencyInput, ProjectDependency};
+use log::{debug, error, info, warn};
+
+/// Mark the planning periods of the dependent project as stale
+async fn record_dependency_change(
+    pool: &DbPool,
+    dependency: &ProjectDependency,
+    action: InputChangeAction,
+) -> Result<(), String> {
+    let planning_period_ids = project_planning_periods(dependency.project_id, pool).await?;
+    let project_name = input_subject_name("projects", dependency.project_id, pool).await?;
+    record_input_change(
+        &planning_period_ids,
+        ChangedInput::ProjectDependency,
+        action,
+        project_name.as_deref(),
+        pool,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_project_dependencies(
+    pool: tauri::State<'_, DbPool>,
+) -> Result<Vec<ProjectDependency>, String> {
+    debug!("Fetching all project dependencies");
+
+    let dependencies = sqlx::query_as::<_, ProjectDependency>(
+        "SELECT * FROM project_dependencies ORDER BY project_id, depends_on_project_id",
+    )
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch project dependencies: {}", e);
+        e.to_string()
+    })?;
+
+    info!(
+        "Successfully fetched {} project dependencies",
+        dependencies.len()
+    );
+    Ok(dependencies)
+}
+
+#[tauri::command]
+pub async fn create_project_dependency(
+    pool: tauri::State<'_, DbPool>,
+    input: CreateProjectDependencyInput,
+) -> Result<ProjectDependency, String> {
+    debug!(
+        "Creating dependency of project ID {} on project ID {}",
+        input.project_id, input.depends_on_project_id
+    );
+
+    let lag_days = input.lag_days.unwrap_or(0);
+    if lag_days < 0 {
+        return Err("Lag must be zero or more days".to_string());
+    }
+    if input.project_id == input.depends_on_project_id {
+        return Err("A project cannot depend on itself".to_string());
+    }
+
+    let existing = sqlx::query_as::<_, ProjectDependency>("SELECT * FROM project_dependencies")
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|e| format!("Failed to fetch project dependencies: {}", e))?;
+    if creates_dependency_cycle(&existing, input.project_id, input.depends_on_project_id) {
+        warn!(
+            "Validation failed: dependency of project ID {} on project ID {} is circular",
+            input.project_id, input.depends_on_project_id
+        );
+        return Err(
+            "Cannot create dependency: it would make the projects depend on each other in a circle"
+                .to_string(),
+        );
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO project_dependencies (project_id, depends_on_project_id, lag_days)
+         VALUES (?, ?, ?)",
+    )
+    .bind(input.project_id)
+    .bind(input.depends_on_project_id)
+    .bind(lag_days)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to insert project dependency: {}", e);
+        e.to_string()
+    })?;
+
+    let dependency =
+        sqlx::query_as::<_, ProjectDependency>("SELECT * FROM project_dependencies WHERE id = ?")
+            .bind(result.last_insert_rowid())
+            .fetch_one(pool.inner())
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch created project dependency: {}", e);
+                e.to_string()
+            })?;
+
+    record_dependency_change(pool.inner(), &dependency, InputChangeAction::Added).await?;
+
+    info!("Successfully created project dependency");
+    Ok(dependency)
+}
+
+#[tauri::command]
+pub async fn delete_project_dependency(
+    pool: tauri::State<'_, DbPool>,
+    id: i64,
+) -> Result<(), String> {
+    debug!("Deleting project dependency ID: {}", id);
+
+    let dependency =
+        sqlx::query_as::<_, ProjectDependency>("SELECT * FROM project_dependencies WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool.inner())
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch project dependency: {}", e);
+                e.to_string()
+            })?;
+
+    sqlx::query("DELETE FROM project_dependencies WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete project dependency: {}", e);
+            e.to_string()
+        })?;
+
+    if let Some(dependency) = dependency {
+        record_dependency_change(pool.inner(), &dependency, InputChangeAction::Deleted).await?;
+    }
+
+    info!("Successfully deleted project dependency");
+    Ok(())
+}
@@ -1,3 +1,7 @@
+use crate::capacity::{
+    input_subject_name, project_planning_periods, record_input_change, ChangedInput,
+    InputChangeAction,
+};
 use crate::db::DbPool;
 use crate::models::{CreateProjectInput, Project, ProjectDependencies};
 use log::{debug, error, info};
@@ -90,11 +94,24 @@ pub async fn update_project(
 pub async fn delete_project(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
     debug!("Deleting project ID: {}", id);
 
+    // Record first, the project's requirements and assignments go with it
+    let planning_period_ids = project_planning_periods(id, pool.inner()).await?;
+    let project_name = input_subject_name("projects", id, pool.inner()).await?;
+    record_input_change(
+        &planning_period_ids,
+        ChangedInput::Project,
+        InputChangeAction::Deleted,
+        project_name.as_deref(),
+        pool.inner(),
+    )
+    .await?;
+
     // Clear calculated fields for assignments involving this project before deletion
     sqlx::query(
         "UPDATE assignments 
          SET calculated_allocation_percentage = NULL,
              calculated_effective_hours = NULL,
+             calculated_start_date = NULL,
              last_calculated_at = NULL
          WHERE project_id = ?",
     )
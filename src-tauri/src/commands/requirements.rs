@@ -1,6 +1,45 @@
+use crate::capacity::{input_subject_name, record_input_change, ChangedInput, InputChangeAction};
 use crate::db::DbPool;
 use crate::models::{CreateProjectRequirementInput, ProjectRequirement};
 use log::{debug, error, info};
+use std::collections::HashMap;
+
+/// Mark the planning period of a project requirement as stale
+async fn record_requirement_change(
+    pool: &DbPool,
+    project_id: i64,
+    planning_period_id: i64,
+    action: InputChangeAction,
+) -> Result<(), String> {
+    let project_name = input_subject_name("projects", project_id, pool).await?;
+    record_input_change(
+        &[planning_period_id],
+        ChangedInput::Requirement,
+        action,
+        project_name.as_deref(),
+        pool,
+    )
+    .await
+}
+
+/// Requirement of a project in a planning period, if there is one
+async fn find_requirement(
+    pool: &DbPool,
+    project_id: i64,
+    planning_period_id: i64,
+) -> Result<Option<ProjectRequirement>, String> {
+    sqlx::query_as::<_, ProjectRequirement>(
+        "SELECT * FROM project_requirements WHERE project_id = ? AND planning_period_id = ?",
+    )
+    .bind(project_id)
+    .bind(planning_period_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch project requirement: {}", e);
+        e.to_string()
+    })
+}
 
 #[tauri::command]
 pub async fn list_project_requirements(
@@ -66,6 +105,9 @@ pub async fn upsert_project_requirement(
         input.project_id, input.planning_period_id
     );
 
+    let previous =
+        find_requirement(pool.inner(), input.project_id, input.planning_period_id).await?;
+
     // Use INSERT OR REPLACE for upsert functionality
     let result = sqlx::query(
         "INSERT INTO project_requirements (project_id, planning_period_id, required_hours)
@@ -95,6 +137,28 @@ pub async fn upsert_project_requirement(
                 e.to_string()
             })?;
 
+    match previous {
+        None => {
+            record_requirement_change(
+                pool.inner(),
+                input.project_id,
+                input.planning_period_id,
+                InputChangeAction::Added,
+            )
+            .await?
+        }
+        Some(previous) if previous.required_hours != input.required_hours => {
+            record_requirement_change(
+                pool.inner(),
+                input.project_id,
+                input.planning_period_id,
+                InputChangeAction::Changed,
+            )
+            .await?
+        }
+        Some(_) => {}
+    }
+
     info!("Successfully upserted project requirement");
     Ok(requirement)
 }
@@ -111,14 +175,46 @@ pub async fn batch_upsert_project_requirements(
         planning_period_id
     );
 
+    let existing: HashMap<(i64, i64), ProjectRequirement> =
+        sqlx::query_as::<_, ProjectRequirement>("SELECT * FROM project_requirements")
+            .fetch_all(pool.inner())
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch project requirements: {}", e);
+                e.to_string()
+            })?
+            .into_iter()
+            .map(|r| ((r.project_id, r.planning_period_id), r))
+            .collect();
+
     // Start a transaction
     let mut tx = pool.begin().await.map_err(|e| {
         error!("Failed to start transaction: {}", e);
         e.to_string()
     })?;
 
+    // Saving the whole list again only counts the requirements that differ
+    let mut changes = Vec::new();
     for req in requirements {
         let priority = req.priority.unwrap_or(10); // Default to Medium
+        match existing.get(&(req.project_id, req.planning_period_id)) {
+            None => changes.push((
+                req.project_id,
+                req.planning_period_id,
+                InputChangeAction::Added,
+            )),
+            Some(previous)
+                if previous.required_hours != req.required_hours
+                    || previous.priority != priority =>
+            {
+                changes.push((
+                    req.project_id,
+                    req.planning_period_id,
+                    InputChangeAction::Changed,
+                ))
+            }
+            Some(_) => {}
+        }
         sqlx::query(
             "INSERT INTO project_requirements (project_id, planning_period_id, required_hours, priority)
              VALUES (?, ?, ?, ?)
@@ -143,6 +239,10 @@ pub async fn batch_upsert_project_requirements(
         e.to_string()
     })?;
 
+    for (project_id, planning_period_id, action) in changes {
+        record_requirement_change(pool.inner(), project_id, planning_period_id, action).await?;
+    }
+
     info!("Successfully batch upserted project requirements");
     Ok(())
 }
@@ -154,6 +254,16 @@ pub async fn delete_project_requirement(
 ) -> Result<(), String> {
     debug!("Deleting project requirement ID: {}", id);
 
+    let requirement =
+        sqlx::query_as::<_, ProjectRequirement>("SELECT * FROM project_requirements WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool.inner())
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch project requirement: {}", e);
+                e.to_string()
+            })?;
+
     sqlx::query("DELETE FROM project_requirements WHERE id = ?")
         .bind(id)
         .execute(pool.inner())
@@ -163,6 +273,16 @@ pub async fn delete_project_requirement(
             e.to_string()
         })?;
 
+    if let Some(requirement) = requirement {
+        record_requirement_change(
+            pool.inner(),
+            requirement.project_id,
+            requirement.planning_period_id,
+            InputChangeAction::Deleted,
+        )
+        .await?;
+    }
+
     info!("Successfully deleted project requirement ID: {}", id);
     Ok(())
 }
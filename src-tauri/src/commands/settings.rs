@@ -0,0 +1,51 @@
+use super::people::validate_max_utilization;
+use crate::capacity::{
+    load_app_settings, record_input_change, staffed_planning_periods, ChangedInput,
+    InputChangeAction,
+};
+use crate::db::DbPool;
+use crate::models::AppSettings;
+use log::{debug, error, info};
+
+#[tauri::command]
+pub async fn get_app_settings(pool: tauri::State<'_, DbPool>) -> Result<AppSettings, String> {
+    debug!("Fetching app settings");
+    load_app_settings(pool.inner()).await
+}
+
+#[tauri::command]
+pub async fn update_app_settings(
+    pool: tauri::State<'_, DbPool>,
+    input: AppSettings,
+) -> Result<AppSettings, String> {
+    debug!("Updating app settings");
+    validate_max_utilization(input.default_max_utilization_percentage)?;
+    let previous = load_app_settings(pool.inner()).await?;
+
+    sqlx::query("UPDATE app_settings SET default_max_utilization_percentage = ? WHERE id = 1")
+        .bind(input.default_max_utilization_percentage)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to update app settings: {}", e);
+            e.to_string()
+        })?;
+
+    if previous.default_max_utilization_percentage != input.default_max_utilization_percentage {
+        let planning_period_ids = staffed_planning_periods(pool.inner()).await?;
+        record_input_change(
+            &planning_period_ids,
+            ChangedInput::Settings,
+            InputChangeAction::Changed,
+            None,
+            pool.inner(),
+        )
+        .await?;
+    }
+
+    info!(
+        "Default maximum utilization set to {}%",
+        input.default_max_utilization_percentage
+    );
+    load_app_settings(pool.inner()).await
+}
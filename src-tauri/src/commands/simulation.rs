@@ -0,0 +1,23 @@
+use crate::capacity::{simulate_capacity_monte_carlo, CapacitySimulation, DEFAULT_SIMULATION_RUNS};
+use crate::db::DbPool;
+use log::info;
+
+#[tauri::command]
+pub async fn simulate_capacity(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+    runs: Option<usize>,
+    seed: Option<u64>,
+) -> Result<CapacitySimulation, String> {
+    info!(
+        "Running capacity simulation for planning period ID: {}",
+        planning_period_id
+    );
+
+    let runs = runs.unwrap_or(DEFAULT_SIMULATION_RUNS);
+    let result =
+        simulate_capacity_monte_carlo(planning_period_id, runs, seed, pool.inner()).await?;
+
+    info!("Simulation completed successfully");
+    Ok(result)
+}
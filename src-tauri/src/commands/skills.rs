@@ -0,0 +1,358 @@
+use crate::capacity::{
+    proficiency_factor, ProductivitySuggestion, ProficiencyLevel, SkillData, PROFICIENCY_LEVELS,
+};
+use crate::db::DbPool;
+use crate::models::{
+    CreateProjectSkillRequirementInput, CreateSkillInput, PersonSkill, ProjectSkillRequirement,
+    SetPersonSkillInput, Skill,
+};
+use log::{debug, error, info, warn};
+
+#[tauri::command]
+pub async fn list_skills(pool: tauri::State<'_, DbPool>) -> Result<Vec<Skill>, String> {
+    debug!("Fetching all skills");
+
+    let skills = sqlx::query_as::<_, Skill>("SELECT * FROM skills ORDER BY name")
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch skills: {}", e);
+            e.to_string()
+        })?;
+
+    info!("Successfully fetched {} skills", skills.len());
+    Ok(skills)
+}
+
+#[tauri::command]
+pub async fn create_skill(
+    pool: tauri::State<'_, DbPool>,
+    input: CreateSkillInput,
+) -> Result<Skill, String> {
+    debug!("Creating skill: {}", input.name);
+
+    // Check if skill with same name already exists
+    let existing =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM skills WHERE LOWER(name) = LOWER(?)")
+            .bind(&input.name)
+            .fetch_one(pool.inner())
+            .await
+            .map_err(|e| {
+                error!("Failed to check existing skill: {}", e);
+                e.to_string()
+            })?;
+
+    if existing > 0 {
+        return Err(format!(
+            "A skill with the name '{}' already exists. Please use a different name.",
+            input.name
+        ));
+    }
+
+    let result = sqlx::query("INSERT INTO skills (name, description) VALUES (?, ?)")
+        .bind(&input.name)
+        .bind(&input.description)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to insert skill: {}", e);
+            e.to_string()
+        })?;
+
+    let skill = sqlx::query_as::<_, Skill>("SELECT * FROM skills WHERE id = ?")
+        .bind(result.last_insert_rowid())
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch created skill: {}", e);
+            e.to_string()
+        })?;
+
+    info!("Successfully created skill: {}", skill.name);
+    Ok(skill)
+}
+
+#[tauri::command]
+pub async fn update_skill(
+    pool: tauri::State<'_, DbPool>,
+    id: i64,
+    input: CreateSkillInput,
+) -> Result<Skill, String> {
+    debug!("Updating skill ID: {}", id);
+
+    // Check if another skill with same name already exists
+    let existing = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM skills WHERE LOWER(name) = LOWER(?) AND id != ?",
+    )
+    .bind(&input.name)
+    .bind(id)
+    .fetch_one(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to check existing skill: {}", e);
+        e.to_string()
+    })?;
+
+    if existing > 0 {
+        return Err(format!(
+            "A skill with the name '{}' already exists. Please use a different name.",
+            input.name
+        ));
+    }
+
+    sqlx::query("UPDATE skills SET name = ?, description = ? WHERE id = ?")
+        .bind(&input.name)
+        .bind(&input.description)
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to update skill: {}", e);
+            e.to_string()
+        })?;
+
+    let skill = sqlx::query_as::<_, Skill>("SELECT * FROM skills WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch updated skill: {}", e);
+            e.to_string()
+        })?;
+
+    info!("Successfully updated skill: {}", skill.name);
+    Ok(skill)
+}
+
+#[tauri::command]
+pub async fn delete_skill(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
+    debug!("Deleting skill ID: {}", id);
+
+    // Proficiencies and skill requirements are removed by ON DELETE CASCADE
+    sqlx::query("DELETE FROM skills WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete skill: {}", e);
+            e.to_string()
+        })?;
+
+    info!("Successfully deleted skill ID: {}", id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_proficiency_levels() -> Result<Vec<ProficiencyLevel>, String> {
+    debug!("Fetching proficiency levels");
+    Ok(PROFICIENCY_LEVELS.to_vec())
+}
+
+#[tauri::command]
+pub async fn list_person_skills(
+    pool: tauri::State<'_, DbPool>,
+    person_id: i64,
+) -> Result<Vec<PersonSkill>, String> {
+    debug!("Fetching skills for person ID: {}", person_id);
+
+    let skills = sqlx::query_as::<_, PersonSkill>(
+        "SELECT * FROM person_skills WHERE person_id = ? ORDER BY skill_id",
+    )
+    .bind(person_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch person skills: {}", e);
+        e.to_string()
+    })?;
+
+    info!("Successfully fetched {} person skills", skills.len());
+    Ok(skills)
+}
+
+#[tauri::command]
+pub async fn set_person_skill(
+    pool: tauri::State<'_, DbPool>,
+    input: SetPersonSkillInput,
+) -> Result<PersonSkill, String> {
+    debug!(
+        "Setting proficiency of person ID {} in skill ID {} to {}",
+        input.person_id, input.skill_id, input.proficiency
+    );
+
+    if proficiency_factor(&input.proficiency).is_none() {
+        warn!(
+            "Validation failed: unknown proficiency {}",
+            input.proficiency
+        );
+        let levels: Vec<&str> = PROFICIENCY_LEVELS.iter().map(|level| level.value).collect();
+        return Err(format!(
+            "Unknown proficiency '{}'. Expected one of: {}",
+            input.proficiency,
+            levels.join(", ")
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO person_skills (person_id, skill_id, proficiency)
+         VALUES (?, ?, ?)
+         ON CONFLICT(person_id, skill_id)
+         DO UPDATE SET proficiency = excluded.proficiency",
+    )
+    .bind(input.person_id)
+    .bind(input.skill_id)
+    .bind(&input.proficiency)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to set person skill: {}", e);
+        e.to_string()
+    })?;
+
+    let skill = sqlx::query_as::<_, PersonSkill>(
+        "SELECT * FROM person_skills WHERE person_id = ? AND skill_id = ?",
+    )
+    .bind(input.person_id)
+    .bind(input.skill_id)
+    .fetch_one(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch person skill: {}", e);
+        e.to_string()
+    })?;
+
+    info!("Successfully set person skill");
+    Ok(skill)
+}
+
+#[tauri::command]
+pub async fn delete_person_skill(pool: tauri::State<'_, DbPool>, id: i64) -> Result<(), String> {
+    debug!("Deleting person skill ID: {}", id);
+
+    sqlx::query("DELETE FROM person_skills WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete person skill: {}", e);
+            e.to_string()
+        })?;
+
+    info!("Successfully deleted person skill");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_project_skill_requirements(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+) -> Result<Vec<ProjectSkillRequirement>, String> {
+    debug!(
+        "Fetching project skill requirements for planning period ID: {}",
+        planning_period_id
+    );
+
+    let requirements = sqlx::query_as::<_, ProjectSkillRequirement>(
+        "SELECT * FROM project_skill_requirements WHERE planning_period_id = ?
+         ORDER BY project_id, skill_id",
+    )
+    .bind(planning_period_id)
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch project skill requirements: {}", e);
+        e.to_string()
+    })?;
+
+    info!(
+        "Successfully fetched {} project skill requirements",
+        requirements.len()
+    );
+    Ok(requirements)
+}
+
+#[tauri::command]
+pub async fn upsert_project_skill_requirement(
+    pool: tauri::State<'_, DbPool>,
+    input: CreateProjectSkillRequirementInput,
+) -> Result<ProjectSkillRequirement, String> {
+    debug!(
+        "Upserting skill ID {} requirement for project_id: {}, period_id: {}",
+        input.skill_id, input.project_id, input.planning_period_id
+    );
+
+    if input.required_hours < 0.0 {
+        return Err("Required hours must be zero or more".to_string());
+    }
+
+    sqlx::query(
+        "INSERT INTO project_skill_requirements
+         (project_id, planning_period_id, skill_id, required_hours)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(project_id, planning_period_id, skill_id)
+         DO UPDATE SET required_hours = excluded.required_hours",
+    )
+    .bind(input.project_id)
+    .bind(input.planning_period_id)
+    .bind(input.skill_id)
+    .bind(input.required_hours)
+    .execute(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to upsert project skill requirement: {}", e);
+        e.to_string()
+    })?;
+
+    let requirement = sqlx::query_as::<_, ProjectSkillRequirement>(
+        "SELECT * FROM project_skill_requirements
+         WHERE project_id = ? AND planning_period_id = ? AND skill_id = ?",
+    )
+    .bind(input.project_id)
+    .bind(input.planning_period_id)
+    .bind(input.skill_id)
+    .fetch_one(pool.inner())
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch upserted project skill requirement: {}", e);
+        e.to_string()
+    })?;
+
+    info!("Successfully upserted project skill requirement");
+    Ok(requirement)
+}
+
+#[tauri::command]
+pub async fn delete_project_skill_requirement(
+    pool: tauri::State<'_, DbPool>,
+    id: i64,
+) -> Result<(), String> {
+    debug!("Deleting project skill requirement ID: {}", id);
+
+    sqlx::query("DELETE FROM project_skill_requirements WHERE id = ?")
+        .bind(id)
+        .execute(pool.inner())
+        .await
+        .map_err(|e| {
+            error!("Failed to delete project skill requirement: {}", e);
+            e.to_string()
+        })?;
+
+    info!("Successfully deleted project skill requirement");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn suggest_productivity_factor(
+    pool: tauri::State<'_, DbPool>,
+    person_id: i64,
+    project_id: i64,
+    planning_period_id: i64,
+) -> Result<Option<ProductivitySuggestion>, String> {
+    debug!(
+        "Suggesting productivity of person ID {} on project ID {} in period ID {}",
+        person_id, project_id, planning_period_id
+    );
+
+    let skills = SkillData::load(planning_period_id, pool.inner()).await?;
+    Ok(skills.productivity_suggestion(person_id, project_id))
+}
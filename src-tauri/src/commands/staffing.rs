@@ -0,0 +1,53 @@
+use crate::capacity::{
+    create_proposed_assignments, optimization_strategy, recommend_staffing_for_period,
+    OptimizationOptions, ProposedAssignment, StaffingRecommendations,
+    DEFAULT_RECOMMENDATION_PRODUCTIVITY_FACTOR,
+};
+use crate::db::DbPool;
+use crate::models::Assignment;
+use log::info;
+
+#[tauri::command]
+pub async fn get_staffing_recommendations(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+    strategy: Option<String>,
+    options: Option<OptimizationOptions>,
+    productivity_factor: Option<f64>,
+) -> Result<StaffingRecommendations, String> {
+    let strategy = optimization_strategy(strategy.as_deref(), options.unwrap_or_default())?;
+
+    info!(
+        "Recommending staffing for planning period ID: {}",
+        planning_period_id
+    );
+
+    let recommendations = recommend_staffing_for_period(
+        planning_period_id,
+        strategy.as_ref(),
+        productivity_factor.unwrap_or(DEFAULT_RECOMMENDATION_PRODUCTIVITY_FACTOR),
+        pool.inner(),
+    )
+    .await?;
+
+    info!(
+        "Recommended staffing for {} infeasible projects",
+        recommendations.proposals.len()
+    );
+    Ok(recommendations)
+}
+
+#[tauri::command]
+pub async fn accept_staffing_recommendations(
+    pool: tauri::State<'_, DbPool>,
+    planning_period_id: i64,
+    assignments: Vec<ProposedAssignment>,
+) -> Result<Vec<Assignment>, String> {
+    info!(
+        "Accepting {} proposed assignments for planning period ID: {}",
+        assignments.len(),
+        planning_period_id
+    );
+
+    create_proposed_assignments(planning_period_id, &assignments, pool.inner()).await
+}
@@ -42,6 +42,23 @@ fn get_app_data_dir() -> PathBuf {
         .join(".capacity-planner")
 }
 
+/// Migrated in-memory database for tests
+#[cfg(test)]
+pub async fn init_test_database() -> DbPool {
+    // A single connection that never closes, since every connection would get its own database
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to open in-memory database");
+    run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
 async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
     debug!("Running database migrations");
 
@@ -368,6 +385,247 @@ async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
 
     debug!("Job overhead task optional_weight migration completed");
 
+    // Add partial-day columns to absences table if they don't exist
+    // NULL in both columns means the absence covers full days
+    sqlx::query("ALTER TABLE absences ADD COLUMN hours REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    sqlx::query("ALTER TABLE absences ADD COLUMN day_fraction REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    debug!("Partial-day absence migration completed");
+
+    // Add partial-day columns to holidays table if they don't exist
+    // NULL in both columns means the holiday covers full days
+    sqlx::query("ALTER TABLE holidays ADD COLUMN hours REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    sqlx::query("ALTER TABLE holidays ADD COLUMN day_fraction REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    debug!("Partial-day holiday migration completed");
+
+    // Create optimization_runs table (history of stored optimization results)
+    // Options, inputs summary, shortfalls and warnings are stored as JSON
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS optimization_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            planning_period_id INTEGER NOT NULL,
+            strategy TEXT NOT NULL,
+            options TEXT NOT NULL,
+            inputs_summary TEXT NOT NULL,
+            infeasible_projects TEXT NOT NULL,
+            warnings TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (planning_period_id) REFERENCES planning_periods(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Create optimization_run_calculations table (per-assignment results of a run)
+    // No foreign key on assignment_id, so the history outlives deleted assignments
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS optimization_run_calculations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id INTEGER NOT NULL,
+            assignment_id INTEGER NOT NULL,
+            person_id INTEGER NOT NULL,
+            project_id INTEGER NOT NULL,
+            allocation_percentage REAL NOT NULL,
+            effective_hours REAL NOT NULL,
+            is_pinned BOOLEAN NOT NULL DEFAULT 0,
+            FOREIGN KEY (run_id) REFERENCES optimization_runs(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_optimization_runs_period ON optimization_runs(planning_period_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_optimization_run_calculations_run ON optimization_run_calculations(run_id)")
+        .execute(pool)
+        .await?;
+
+    debug!("Optimization run history migration completed");
+
+    // Add allocation bounds to assignments if they don't exist
+    // NULL means the optimizer is free to go down to 0% or up to 100%
+    sqlx::query("ALTER TABLE assignments ADD COLUMN min_allocation_percentage REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+    sqlx::query("ALTER TABLE assignments ADD COLUMN max_allocation_percentage REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    debug!("Assignment allocation bounds migration completed");
+
+    // Add a planned utilization cap to people if it doesn't exist
+    // NULL means the person uses the app-wide default
+    sqlx::query("ALTER TABLE people ADD COLUMN max_utilization_percentage REAL")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    // App-wide settings live in a single row
+    // Default utilization cap of 100% keeps the existing planning behaviour
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS app_settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            default_max_utilization_percentage REAL NOT NULL DEFAULT 100.0
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("INSERT OR IGNORE INTO app_settings (id) VALUES (1)")
+        .execute(pool)
+        .await?;
+
+    debug!("Utilization cap migration completed");
+
+    // Create project_dependencies table (finish-to-start links between projects)
+    // The dependent project starts lag_days after its predecessor completes
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS project_dependencies (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            depends_on_project_id INTEGER NOT NULL,
+            lag_days INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+            FOREIGN KEY (depends_on_project_id) REFERENCES projects(id) ON DELETE CASCADE,
+            UNIQUE(project_id, depends_on_project_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Add the start the optimizer scheduled to assignments and stored runs if it doesn't exist
+    // NULL means the assignment starts on its own start date
+    sqlx::query("ALTER TABLE assignments ADD COLUMN calculated_start_date TEXT")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+    sqlx::query("ALTER TABLE optimization_run_calculations ADD COLUMN calculated_start_date TEXT")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists (SQLite limitation)
+
+    debug!("Project dependency migration completed");
+
+    // Create planning_period_input_changes table (inputs changed since the last optimization)
+    // Rows up to the last optimization are removed when its results are saved
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS planning_period_input_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            planning_period_id INTEGER NOT NULL,
+            input TEXT NOT NULL,
+            action TEXT NOT NULL,
+            subject TEXT,
+            change_count INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (planning_period_id) REFERENCES planning_periods(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    debug!("Input change tracking migration completed");
+
+    // Create planning_period_auto_optimization table (periods re-optimized when inputs change)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS planning_period_auto_optimization (
+            planning_period_id INTEGER PRIMARY KEY,
+            strategy TEXT NOT NULL,
+            options TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (planning_period_id) REFERENCES planning_periods(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    debug!("Auto-optimization migration completed");
+
+    // Create skills table
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS skills (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Create person_skills table (proficiency level of a person per skill)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS person_skills (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            person_id INTEGER NOT NULL,
+            skill_id INTEGER NOT NULL,
+            proficiency TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (person_id) REFERENCES people(id) ON DELETE CASCADE,
+            FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE,
+            UNIQUE(person_id, skill_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Create project_skill_requirements table (hours per skill a project needs in a period)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS project_skill_requirements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            planning_period_id INTEGER NOT NULL,
+            skill_id INTEGER NOT NULL,
+            required_hours REAL NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+            FOREIGN KEY (planning_period_id) REFERENCES planning_periods(id) ON DELETE CASCADE,
+            FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE,
+            UNIQUE(project_id, planning_period_id, skill_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    debug!("Skills migration completed");
+
     info!("Database migrations completed successfully");
     Ok(())
 }
@@ -6,26 +6,38 @@ pub mod logger;
 mod models;
 
 use commands::{
-    batch_create_holidays, batch_create_person_job_assignments, batch_upsert_project_requirements,
+    accept_staffing_recommendations, apply_assignment_optimization, batch_create_holidays,
+    batch_create_person_job_assignments, batch_upsert_project_requirements,
     check_country_dependencies, check_job_dependencies, check_person_dependencies,
-    check_planning_period_dependencies, check_project_dependencies, create_absence,
-    create_assignment, create_country, create_holiday, create_job, create_job_overhead_task,
-    create_person, create_person_job_assignment, create_planning_period, create_project,
-    delete_absence, delete_all_countries_and_holidays, delete_assignment, delete_country,
-    delete_holiday, delete_job, delete_job_overhead_task, delete_person,
-    delete_person_job_assignment, delete_planning_period, delete_project,
-    delete_project_requirement, fetch_available_countries_for_import, get_capacity_overview,
-    get_job, get_person_capacity, get_project_requirement, get_project_staffing,
-    import_countries_from_api, import_holidays_from_api, list_absences, list_assignments,
-    list_countries, list_holidays, list_holidays_for_person, list_job_overhead_tasks, list_jobs,
-    list_people, list_people_with_countries, list_person_job_assignments,
-    list_person_jobs_for_person, list_planning_periods, list_project_requirements, list_projects,
-    optimize_assignments, preview_holiday_import, update_absence, update_assignment,
-    update_country, update_holiday, update_job, update_job_overhead_task, update_person,
-    update_planning_period, update_project, upsert_project_requirement,
+    check_planning_period_dependencies, check_project_dependencies, compare_optimization_runs,
+    create_absence, create_assignment, create_country, create_holiday, create_job,
+    create_job_overhead_task, create_person, create_person_job_assignment, create_planning_period,
+    create_project, create_project_dependency, create_skill, delete_absence,
+    delete_all_countries_and_holidays, delete_assignment, delete_country, delete_holiday,
+    delete_job, delete_job_overhead_task, delete_person, delete_person_job_assignment,
+    delete_person_skill, delete_planning_period, delete_project, delete_project_dependency,
+    delete_project_requirement, delete_project_skill_requirement, delete_skill,
+    fetch_available_countries_for_import, get_app_settings, get_auto_optimization,
+    get_capacity_overview, get_capacity_timeline, get_job, get_optimization_run,
+    get_person_capacity, get_project_requirement, get_project_staffing,
+    get_staffing_recommendations, import_countries_from_api, import_holidays_from_api,
+    list_absences, list_assignments, list_countries, list_holidays, list_holidays_for_person,
+    list_job_overhead_tasks, list_jobs, list_optimization_runs, list_people,
+    list_people_with_countries, list_person_job_assignments, list_person_jobs_for_person,
+    list_person_skills, list_planning_periods, list_proficiency_levels, list_project_dependencies,
+    list_project_requirements, list_project_skill_requirements, list_projects, list_skills,
+    optimize_assignments, optimize_planning_periods, preview_assignment_optimization,
+    preview_holiday_import, restore_optimization_run, set_auto_optimization, set_person_skill,
+    simulate_capacity, suggest_productivity_factor, update_absence, update_app_settings,
+    update_assignment, update_country, update_holiday, update_job, update_job_overhead_task,
+    update_person, update_planning_period, update_project, update_skill,
+    upsert_project_requirement, upsert_project_skill_requirement,
 };
 use db::init_database;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+
+/// Event emitted with the result of every automatic re-optimization
+const AUTO_OPTIMIZATION_EVENT: &str = "auto-optimization-completed";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -39,6 +51,14 @@ pub fn run() {
                     .expect("Failed to initialize database")
             });
 
+            // Re-optimize opted-in planning periods when their inputs change
+            let app_handle = app.handle().clone();
+            capacity::start_auto_optimizer(pool.clone(), move |event| {
+                if let Err(e) = app_handle.emit(AUTO_OPTIMIZATION_EVENT, event) {
+                    log::error!("Failed to emit auto-optimization event: {}", e);
+                }
+            });
+
             // Manage database pool state
             app.manage(pool);
 
@@ -66,6 +86,9 @@ pub fn run() {
             upsert_project_requirement,
             batch_upsert_project_requirements,
             delete_project_requirement,
+            list_project_dependencies,
+            create_project_dependency,
+            delete_project_dependency,
             list_assignments,
             create_assignment,
             update_assignment,
@@ -106,9 +129,36 @@ pub fn run() {
             preview_holiday_import,
             import_holidays_from_api,
             optimize_assignments,
+            optimize_planning_periods,
+            preview_assignment_optimization,
+            apply_assignment_optimization,
+            list_optimization_runs,
+            get_optimization_run,
+            compare_optimization_runs,
+            restore_optimization_run,
+            get_auto_optimization,
+            set_auto_optimization,
+            list_skills,
+            create_skill,
+            update_skill,
+            delete_skill,
+            list_proficiency_levels,
+            list_person_skills,
+            set_person_skill,
+            delete_person_skill,
+            list_project_skill_requirements,
+            upsert_project_skill_requirement,
+            delete_project_skill_requirement,
+            suggest_productivity_factor,
             get_capacity_overview,
+            get_capacity_timeline,
             get_person_capacity,
             get_project_staffing,
+            simulate_capacity,
+            get_staffing_recommendations,
+            accept_staffing_recommendations,
+            get_app_settings,
+            update_app_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -17,6 +17,7 @@ pub struct Person {
     pub available_hours_per_week: f64,
     pub country_id: Option<i64>, // Optional reference to country
     pub working_days: String,    // Comma-separated day codes (e.g., "Mon,Tue,Wed,Thu,Fri")
+    pub max_utilization_percentage: Option<f64>, // Planned utilization cap, None uses the app-wide default
     pub created_at: String,
 }
 
@@ -38,21 +39,44 @@ pub struct Assignment {
     pub productivity_factor: f64,
     pub start_date: String,
     pub end_date: String,
+    pub is_pinned: bool, // Locked at pinned_allocation_percentage, the optimizer leaves it alone
+    pub pinned_allocation_percentage: Option<f64>,
+    pub min_allocation_percentage: Option<f64>, // Bounds every optimization strategy respects
+    pub max_allocation_percentage: Option<f64>,
     pub calculated_allocation_percentage: Option<f64>,
     pub calculated_effective_hours: Option<f64>,
+    pub calculated_start_date: Option<String>, // Later start forced by project dependencies
     pub last_calculated_at: Option<String>,
     pub created_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Absence {
+    pub id: i64,
+    pub person_id: i64,
+    pub start_date: String,
+    pub end_date: String,
+    pub days: i64,          // Working days between the dates, excluding public holidays
+    pub hours: Option<f64>, // Hours absent on each day (partial-day absence)
+    pub day_fraction: Option<f64>, // Fraction of each day absent, 0.0 to 1.0 (partial-day absence)
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+// Extended absence model with the deducted days and hours for UI display
+#[derive(Debug, Serialize)]
+pub struct AbsenceWithHours {
     pub id: i64,
     pub person_id: i64,
     pub start_date: String,
     pub end_date: String,
     pub days: i64,
+    pub hours: Option<f64>,
+    pub day_fraction: Option<f64>,
     pub reason: Option<String>,
     pub created_at: String,
+    pub absence_days: f64,  // Deducted days, counting partial days as fractions
+    pub absence_hours: f64, // Deducted hours based on the person's hours per day
 }
 
 // ============================================================================
@@ -115,6 +139,7 @@ pub struct CreatePersonInput {
     pub available_hours_per_week: f64,
     pub country_id: Option<i64>, // Optional country assignment
     pub working_days: String,    // Comma-separated day codes (e.g., "Mon,Tue,Wed,Thu,Fri")
+    pub max_utilization_percentage: Option<f64>, // Above 0 up to 100, omit for the app-wide default
 }
 
 #[derive(Debug, Deserialize)]
@@ -132,6 +157,22 @@ pub struct CreateAssignmentInput {
     pub productivity_factor: f64,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    pub is_pinned: Option<bool>,                   // Defaults to false
+    pub pinned_allocation_percentage: Option<f64>, // Required when pinned, 0 to 100
+    // Bounds left out keep their current values on update, null clears them
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub min_allocation_percentage: Option<Option<f64>>, // 0 to 100, at most the maximum
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub max_allocation_percentage: Option<Option<f64>>, // 0 to 100
+}
+
+/// Tell a field sent as null (Some(None)) from one left out (None, via serde's default)
+fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Deserialize)]
@@ -139,7 +180,9 @@ pub struct CreateAbsenceInput {
     pub person_id: i64,
     pub start_date: String,
     pub end_date: String,
-    pub days: i64,
+    pub days: Option<i64>, // Derived from the dates; a mismatching value is corrected
+    pub hours: Option<f64>, // Hours absent on each day; omit for full days
+    pub day_fraction: Option<f64>, // Fraction of each day absent; omit for full days
     pub reason: Option<String>,
 }
 
@@ -235,6 +278,7 @@ pub struct PersonWithCountry {
     pub country_iso_code: Option<String>,
     pub country_name: Option<String>,
     pub working_days: String, // Comma-separated day codes (e.g., "Mon,Tue,Wed,Thu,Fri")
+    pub max_utilization_percentage: Option<f64>,
     pub created_at: String,
 }
 
@@ -252,9 +296,11 @@ pub struct CreateCountryInput {
 pub struct Holiday {
     pub id: i64,
     pub country_id: i64,
-    pub name: Option<String>, // Optional holiday name
-    pub start_date: String,   // ISO 8601 date format
-    pub end_date: String,     // ISO 8601 date format
+    pub name: Option<String>,      // Optional holiday name
+    pub start_date: String,        // ISO 8601 date format
+    pub end_date: String,          // ISO 8601 date format
+    pub hours: Option<f64>,        // Hours off on each day (partial-day holiday)
+    pub day_fraction: Option<f64>, // Fraction of each day off, 0.0 to 1.0 (partial-day holiday)
     pub created_at: String,
 }
 
@@ -264,6 +310,8 @@ pub struct CreateHolidayInput {
     pub name: Option<String>,
     pub start_date: String,
     pub end_date: String,
+    pub hours: Option<f64>,
+    pub day_fraction: Option<f64>,
 }
 
 // Extended model with country information for UI display
@@ -276,6 +324,8 @@ pub struct HolidayWithCountry {
     pub name: Option<String>,
     pub start_date: String,
     pub end_date: String,
+    pub hours: Option<f64>,
+    pub day_fraction: Option<f64>,
     pub created_at: String,
 }
 
@@ -352,3 +402,116 @@ pub struct ImportHolidaysResult {
     pub imported_count: usize,
     pub skipped_count: usize,
 }
+
+// ============================================================================
+// Project Dependency Models
+// ============================================================================
+
+/// Finish-to-start link: project_id cannot start before depends_on_project_id completes
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProjectDependency {
+    pub id: i64,
+    pub project_id: i64,
+    pub depends_on_project_id: i64,
+    pub lag_days: i64, // Calendar days between the predecessor's completion and the start
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProjectDependencyInput {
+    pub project_id: i64,
+    pub depends_on_project_id: i64,
+    pub lag_days: Option<i64>,
+}
+
+// ============================================================================
+// Skill Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Skill {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSkillInput {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// How well a person masters a skill
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PersonSkill {
+    pub id: i64,
+    pub person_id: i64,
+    pub skill_id: i64,
+    pub proficiency: String, // Proficiency level, e.g. "expert"
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPersonSkillInput {
+    pub person_id: i64,
+    pub skill_id: i64,
+    pub proficiency: String,
+}
+
+/// Hours of a skill a project needs in a planning period
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProjectSkillRequirement {
+    pub id: i64,
+    pub project_id: i64,
+    pub planning_period_id: i64,
+    pub skill_id: i64,
+    pub required_hours: f64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProjectSkillRequirementInput {
+    pub project_id: i64,
+    pub planning_period_id: i64,
+    pub skill_id: i64,
+    pub required_hours: f64,
+}
+
+// ============================================================================
+// Input Change Models
+// ============================================================================
+
+/// An input of a planning period that changed since its last optimization
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PlanningPeriodInputChange {
+    pub id: i64,
+    pub planning_period_id: i64,
+    pub input: String,           // e.g. 'absence', 'holiday', 'requirement'
+    pub action: String,          // 'added', 'changed' or 'deleted'
+    pub subject: Option<String>, // e.g. the project of a requirement
+    pub change_count: i64,
+    pub created_at: String,
+}
+
+// Extended planning period model with whether its calculations are out of date
+#[derive(Debug, Serialize)]
+pub struct PlanningPeriodWithStatus {
+    pub id: i64,
+    pub name: Option<String>,
+    pub start_date: String,
+    pub end_date: String,
+    pub created_at: String,
+    pub is_stale: bool,             // Inputs changed since the last optimization
+    pub stale_reasons: Vec<String>, // e.g. "3 absences added"
+    pub auto_optimize: bool,        // Re-optimized automatically when inputs change
+}
+
+// ============================================================================
+// App Settings Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AppSettings {
+    pub default_max_utilization_percentage: f64, // Cap for people without one of their own
+}
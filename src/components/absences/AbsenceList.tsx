@@ -1,9 +1,9 @@
 import { ActionIcon, Badge, Table, Text } from "@mantine/core";
 import { IconEdit, IconTrash } from "@tabler/icons-react";
-import type { Absence } from "../../types";
+import type { Absence, AbsenceWithHours } from "../../types";
 
 interface AbsenceListProps {
-  absences: Absence[];
+  absences: AbsenceWithHours[];
   onEdit: (absence: Absence) => void;
   onDelete: (id: number) => void;
 }
@@ -44,7 +44,9 @@ export function AbsenceList({ absences, onEdit, onDelete }: AbsenceListProps) {
             </Table.Td>
             <Table.Td>
               <Badge size="md" variant="light" color="blue">
-                {absence.days} {absence.days === 1 ? "day" : "days"}
+                {Number(absence.absence_days.toFixed(2))}{" "}
+                {absence.absence_days === 1 ? "day" : "days"} ·{" "}
+                {absence.absence_hours.toFixed(1)}h
               </Badge>
             </Table.Td>
             <Table.Td>
@@ -18,14 +18,18 @@ import {
   updateAbsence,
   deleteAbsence,
 } from "../../lib/tauri";
-import type { Absence, CreateAbsenceInput } from "../../types";
+import type {
+  Absence,
+  AbsenceWithHours,
+  CreateAbsenceInput,
+} from "../../types";
 
 interface AbsenceManagerProps {
   personId: number;
 }
 
 export function AbsenceManager({ personId }: AbsenceManagerProps) {
-  const [absences, setAbsences] = useState<Absence[]>([]);
+  const [absences, setAbsences] = useState<AbsenceWithHours[]>([]);
   const [loading, setLoading] = useState(true);
   const [formOpened, setFormOpened] = useState(false);
   const [selectedAbsence, setSelectedAbsence] = useState<Absence | null>(null);
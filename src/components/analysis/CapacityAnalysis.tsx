@@ -17,6 +17,8 @@ import {
   Tooltip,
   Center,
   Avatar,
+  Select,
+  Switch,
 } from "@mantine/core";
 import { notifications } from "@mantine/notifications";
 import {
@@ -30,11 +32,27 @@ import {
   IconClock,
   IconInfoCircle,
 } from "@tabler/icons-react";
-import { optimizeAssignments, getCapacityOverview } from "../../lib/tauri";
+import {
+  optimizeAssignments,
+  getCapacityOverview,
+  getStaffingRecommendations,
+  acceptStaffingRecommendations,
+  getAutoOptimization,
+  setAutoOptimization,
+  onAutoOptimizationCompleted,
+} from "../../lib/tauri";
 import { useGravatarUrl } from "../../lib/gravatar";
-import type { CapacityOverview, OptimizationResult } from "../../types";
+import type {
+  CapacityOverview,
+  OptimizationResult,
+  OptimizationStrategy,
+  StaffingRecommendations,
+} from "../../types";
 import { CapacityPieChart } from "./CapacityPieChart";
-import { getProficiencyLabel } from "../../constants/proficiency";
+import {
+  getProficiencyLabel,
+  useProficiencyLevels,
+} from "../../lib/proficiency";
 
 interface CapacityAnalysisProps {
   periodId: number;
@@ -45,6 +63,13 @@ interface PersonCapacityRowProps {
   getUtilizationColor: (utilization: number) => string;
 }
 
+const STRATEGY_OPTIONS: { value: OptimizationStrategy; label: string }[] = [
+  { value: "proportional", label: "Proportional" },
+  { value: "priority_first", label: "Priority first" },
+  { value: "even_spread", label: "Even spread" },
+  { value: "linear", label: "Linear programming" },
+];
+
 function PersonCapacityRow({
   person,
   getUtilizationColor,
@@ -79,8 +104,16 @@ function PersonCapacityRow({
               color={getUtilizationColor(person.utilization_percentage)}
               size="lg"
             />
-            <Text size="xs" ta="center" mt={4} className="numeric-data">
+            <Text
+              size="xs"
+              ta="center"
+              mt={4}
+              className="numeric-data"
+              c={person.is_over_utilization_cap ? "orange" : undefined}
+            >
               {person.utilization_percentage.toFixed(1)}% utilized
+              {person.max_utilization_percentage < 100 &&
+                ` (cap ${person.max_utilization_percentage.toFixed(0)}%)`}
             </Text>
           </div>
         </Group>
@@ -323,14 +356,64 @@ function PersonCapacityRow({
 export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
   const [loading, setLoading] = useState(false);
   const [optimizing, setOptimizing] = useState(false);
+  const [strategy, setStrategy] =
+    useState<OptimizationStrategy>("proportional");
   const [overview, setOverview] = useState<CapacityOverview | null>(null);
   const [lastOptimization, setLastOptimization] =
     useState<OptimizationResult | null>(null);
+  const [recommendations, setRecommendations] =
+    useState<StaffingRecommendations | null>(null);
+  const [recommending, setRecommending] = useState(false);
+  const [accepting, setAccepting] = useState(false);
+  const [autoOptimize, setAutoOptimize] = useState(false);
+  const proficiencyLevels = useProficiencyLevels();
 
   useEffect(() => {
     loadOverview();
+    loadAutoOptimization();
+  }, [periodId]);
+
+  // Refresh when the period was re-optimized in the background
+  useEffect(() => {
+    const unlisten = onAutoOptimizationCompleted((event) => {
+      if (event.planning_period_id !== periodId) return;
+      setLastOptimization(event.result);
+      notifications.show({
+        title: "Allocations Updated",
+        message: `Re-optimized ${event.result.calculations.length} assignments after input changes`,
+        color: "blue",
+      });
+      loadOverview();
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
   }, [periodId]);
 
+  const loadAutoOptimization = async () => {
+    try {
+      const setting = await getAutoOptimization(periodId);
+      setAutoOptimize(setting.enabled);
+      if (setting.enabled) setStrategy(setting.strategy);
+    } catch (error) {
+      console.error("Failed to load auto-optimization setting:", error);
+    }
+  };
+
+  const handleToggleAutoOptimize = async (enabled: boolean) => {
+    try {
+      const setting = await setAutoOptimization(periodId, enabled, strategy);
+      setAutoOptimize(setting.enabled);
+    } catch (error) {
+      notifications.show({
+        title: "Error",
+        message: "Failed to update automatic re-optimization",
+        color: "red",
+      });
+      console.error("Failed to update automatic re-optimization:", error);
+    }
+  };
+
   const loadOverview = async () => {
     try {
       setLoading(true);
@@ -351,7 +434,7 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
   const handleOptimize = async () => {
     try {
       setOptimizing(true);
-      const result = await optimizeAssignments(periodId);
+      const result = await optimizeAssignments(periodId, strategy);
       setLastOptimization(result);
 
       if (result.success) {
@@ -391,6 +474,49 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
     }
   };
 
+  const handleRecommendStaffing = async () => {
+    try {
+      setRecommending(true);
+      setRecommendations(await getStaffingRecommendations(periodId, strategy));
+    } catch (error) {
+      notifications.show({
+        title: "Error",
+        message: "Failed to recommend staffing",
+        color: "red",
+      });
+      console.error("Failed to recommend staffing:", error);
+    } finally {
+      setRecommending(false);
+    }
+  };
+
+  const handleAcceptRecommendations = async () => {
+    if (!recommendations) return;
+    try {
+      setAccepting(true);
+      const created = await acceptStaffingRecommendations(
+        periodId,
+        recommendations.proposals.flatMap((proposal) => proposal.assignments),
+      );
+      notifications.show({
+        title: "Assignments Created",
+        message: `Created ${created.length} proposed assignments`,
+        color: "green",
+      });
+      setRecommendations(null);
+      await handleOptimize();
+    } catch (error) {
+      notifications.show({
+        title: "Error",
+        message: "Failed to create proposed assignments",
+        color: "red",
+      });
+      console.error("Failed to create proposed assignments:", error);
+    } finally {
+      setAccepting(false);
+    }
+  };
+
   const getUtilizationColor = (utilization: number) => {
     if (utilization > 100) return "red"; // Overcommitted
     if (utilization >= 96) return "red"; // At capacity (96-100%)
@@ -424,16 +550,56 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
               Review resource utilization and project staffing
             </Text>
           </div>
-          <Button
-            leftSection={<IconCalculator size={16} />}
-            onClick={handleOptimize}
-            loading={optimizing}
-            size="lg"
-          >
-            Calculate Optimal Allocations
-          </Button>
+          <Group gap="sm">
+            <Tooltip
+              label="Re-run the optimization with the selected strategy whenever absences, holidays, assignments, requirements or job overhead change"
+              multiline
+              w={280}
+            >
+              <Switch
+                label="Auto-update"
+                checked={autoOptimize}
+                onChange={(event) =>
+                  handleToggleAutoOptimize(event.currentTarget.checked)
+                }
+              />
+            </Tooltip>
+            <Select
+              aria-label="Optimization strategy"
+              data={STRATEGY_OPTIONS}
+              value={strategy}
+              onChange={(value) =>
+                value && setStrategy(value as OptimizationStrategy)
+              }
+              allowDeselect={false}
+              size="lg"
+              w={220}
+            />
+            <Button
+              leftSection={<IconCalculator size={16} />}
+              onClick={handleOptimize}
+              loading={optimizing}
+              size="lg"
+            >
+              Calculate Optimal Allocations
+            </Button>
+          </Group>
         </Group>
 
+        {/* Out-of-date allocations */}
+        {overview?.staleness.is_stale && (
+          <Alert
+            icon={<IconAlertTriangle size={16} />}
+            title="Allocations Out of Date"
+            color="yellow"
+          >
+            <Text size="sm">
+              Inputs changed since the last optimization:{" "}
+              {overview.staleness.reasons.join(", ")}
+            </Text>
+          </Alert>
+        )}
+
         {/* Optimization Results */}
         {lastOptimization && (
           <Alert
@@ -456,21 +622,122 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
                 ? `Calculated allocations for ${lastOptimization.calculations.length} assignments`
                 : "Failed to calculate optimal allocations"}
             </Text>
+            <Text size="sm" mt="xs">
+              <strong>Strategy:</strong>{" "}
+              {STRATEGY_OPTIONS.find(
+                (option) => option.value === lastOptimization.strategy,
+              )?.label ?? lastOptimization.strategy}
+            </Text>
             {lastOptimization.infeasible_projects.length > 0 && (
               <Text size="sm" mt="xs">
                 <strong>Infeasible Projects:</strong>{" "}
                 {lastOptimization.infeasible_projects.length}
               </Text>
             )}
+            {lastOptimization.infeasible_projects.map((shortfall) => (
+              <div key={shortfall.project_id}>
+                <Text size="sm" mt="xs">
+                  <strong>{shortfall.project_name}:</strong>{" "}
+                  {shortfall.shortfall.toFixed(1)}h short of{" "}
+                  {shortfall.required_hours.toFixed(1)}h
+                </Text>
+                {shortfall.explanation.map((person) => (
+                  <Text size="xs" c="dimmed" key={person.assignment_id}>
+                    {person.person_name}: {person.available_hours.toFixed(1)}h
+                    available after {person.absence_hours.toFixed(1)}h absence,{" "}
+                    {person.holiday_hours.toFixed(1)}h holidays,{" "}
+                    {person.overhead_hours.toFixed(1)}h overhead and{" "}
+                    {person.optional_overhead_hours.toFixed(1)}h optional
+                    overhead; {person.effective_hours.toFixed(1)}h to this
+                    project
+                    {person.competing_projects.length > 0 &&
+                      `, ${person.competing_projects
+                        .map(
+                          (project) =>
+                            `${project.allocated_hours.toFixed(1)}h to ${project.project_name}`,
+                        )
+                        .join(", ")}`}
+                  </Text>
+                ))}
+              </div>
+            ))}
+            {lastOptimization.blocked_projects.map((blocked) => (
+              <Text size="sm" mt="xs" key={blocked.project_id}>
+                <strong>{blocked.project_name}:</strong>{" "}
+                {blocked.earliest_start_date
+                  ? `waits for its dependencies until ${blocked.earliest_start_date}`
+                  : "cannot start in this period because of its dependencies"}
+              </Text>
+            ))}
             {lastOptimization.warnings.length > 0 && (
               <Text size="sm" mt="xs">
                 <strong>Warnings:</strong>{" "}
                 {lastOptimization.warnings.join(", ")}
               </Text>
             )}
+            {lastOptimization.infeasible_projects.length > 0 && (
+              <Button
+                variant="light"
+                size="xs"
+                mt="sm"
+                onClick={handleRecommendStaffing}
+                loading={recommending}
+              >
+                Recommend Staffing
+              </Button>
+            )}
           </Alert>
         )}
 
+        {/* Staffing Recommendations */}
+        {recommendations && (
+          <Card withBorder>
+            <Group justify="space-between" mb="sm">
+              <Title order={4}>Staffing Recommendations</Title>
+              <Button
+                size="xs"
+                onClick={handleAcceptRecommendations}
+                loading={accepting}
+                disabled={recommendations.proposals.every(
+                  (proposal) => proposal.assignments.length === 0,
+                )}
+              >
+                Create Proposed Assignments
+              </Button>
+            </Group>
+            <Text size="xs" c="dimmed" mb="sm">
+              Assuming a productivity factor of{" "}
+              {(recommendations.productivity_factor * 100).toFixed(0)}%
+            </Text>
+            <Stack gap="sm">
+              {recommendations.proposals.map((proposal) => (
+                <div key={proposal.project_id}>
+                  <Text size="sm">
+                    <strong>{proposal.project_name}:</strong>{" "}
+                    {proposal.shortfall.toFixed(1)}h short
+                    {proposal.assignments.length === 0
+                      ? ", no one with spare capacity can help"
+                      : `, ${proposal.remaining_shortfall.toFixed(1)}h short with the proposed assignments`}
+                  </Text>
+                  {proposal.assignments.map((assignment) => (
+                    <Text size="xs" key={assignment.person_id}>
+                      Assign {assignment.person_name} at{" "}
+                      {assignment.allocation_percentage.toFixed(1)}% (
+                      {assignment.effective_hours.toFixed(1)}h)
+                    </Text>
+                  ))}
+                  {proposal.project_deltas.map((delta) => (
+                    <Text size="xs" c="dimmed" key={delta.project_id}>
+                      {delta.project_name}: {delta.delta_hours > 0 ? "+" : ""}
+                      {delta.delta_hours.toFixed(1)}h
+                    </Text>
+                  ))}
+                </div>
+              ))}
+            </Stack>
+          </Card>
+        )}
+
         {overview && (
           <>
             {/* Summary Cards */}
@@ -601,6 +868,36 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
                             </Text>
                           </Alert>
                         )}
+                        {project.skill_staffing.length > 0 && (
+                          <div>
+                            <Text size="sm" fw={500}>
+                              Skills:
+                            </Text>
+                            <Group gap="xs" mt="xs">
+                              {project.skill_staffing.map((skill) => (
+                                <Tooltip
+                                  key={skill.skill_id}
+                                  label={
+                                    skill.is_viable
+                                      ? `${skill.effective_hours.toFixed(1)}h of ${skill.required_hours.toFixed(1)}h required`
+                                      : `${skill.shortfall.toFixed(1)}h short of ${skill.required_hours.toFixed(1)}h required`
+                                  }
+                                  withArrow
+                                >
+                                  <Badge
+                                    variant="light"
+                                    color={getStaffingColor(
+                                      skill.staffing_percentage,
+                                    )}
+                                  >
+                                    {skill.skill_name}{" "}
+                                    {skill.staffing_percentage.toFixed(0)}%
+                                  </Badge>
+                                </Tooltip>
+                              ))}
+                            </Group>
+                          </div>
+                        )}
                         <div>
                           <Text size="sm" fw={500}>
                             Assigned People:
@@ -629,6 +926,7 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
                                     >
                                       <Text size="sm">
                                         {getProficiencyLabel(
+                                          proficiencyLevels,
                                           person.productivity_factor,
                                         )}
                                       </Text>
@@ -743,6 +1041,7 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
 
             {/* Warnings */}
             {(overview.over_committed_people > 0 ||
+              overview.over_utilization_cap_people > 0 ||
               overview.under_staffed_projects > 0) && (
               <Alert
                 icon={<IconAlertCircle size={16} />}
@@ -756,6 +1055,12 @@ export function CapacityAnalysis({ periodId }: CapacityAnalysisProps) {
                       capacity (≥85% allocation)
                     </Text>
                   )}
+                  {overview.over_utilization_cap_people > 0 && (
+                    <Text size="sm">
+                      • {overview.over_utilization_cap_people} people are
+                      planned above their maximum utilization
+                    </Text>
+                  )}
                   {overview.under_staffed_projects > 0 && (
                     <Text size="sm">
                       • {overview.under_staffed_projects} projects are
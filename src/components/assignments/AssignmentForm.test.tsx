@@ -9,17 +9,27 @@ import type {
   Person,
   Project,
   PlanningPeriod,
+  ProficiencyLevel,
 } from "../../types";
-import { listPeople, listProjects } from "../../lib/tauri";
+import {
+  listPeople,
+  listProjects,
+  listProficiencyLevels,
+  suggestProductivityFactor,
+} from "../../lib/tauri";
 
 // Mock the tauri module
 vi.mock("../../lib/tauri", () => ({
   listPeople: vi.fn(),
   listProjects: vi.fn(),
+  listProficiencyLevels: vi.fn(),
+  suggestProductivityFactor: vi.fn(),
 }));
 
 const mockListPeople = vi.mocked(listPeople);
 const mockListProjects = vi.mocked(listProjects);
+const mockListProficiencyLevels = vi.mocked(listProficiencyLevels);
+const mockSuggestProductivityFactor = vi.mocked(suggestProductivityFactor);
 
 describe("AssignmentForm", () => {
   const mockOnClose = vi.fn();
@@ -33,6 +43,21 @@ describe("AssignmentForm", () => {
     created_at: new Date().toISOString(),
   };
 
+  const mockProficiencyLevels: ProficiencyLevel[] = [
+    {
+      value: "expert",
+      factor: 0.8,
+      label: "Expert",
+      description: "Deep expertise and experience.",
+    },
+    {
+      value: "proficient",
+      factor: 0.5,
+      label: "Proficient",
+      description: "Solid understanding and competence.",
+    },
+  ];
+
   const mockPeople: Person[] = [
     {
       id: 1,
@@ -41,6 +66,7 @@ describe("AssignmentForm", () => {
       available_hours_per_week: 40,
       country_id: null,
       working_days: "Mon,Tue,Wed,Thu,Fri",
+      max_utilization_percentage: null,
       created_at: new Date().toISOString(),
     },
     {
@@ -50,6 +76,7 @@ describe("AssignmentForm", () => {
       available_hours_per_week: 32,
       country_id: 1,
       working_days: "Mon,Tue,Wed,Thu",
+      max_utilization_percentage: null,
       created_at: new Date().toISOString(),
     },
   ];
@@ -85,6 +112,8 @@ describe("AssignmentForm", () => {
     mockOnSubmit.mockResolvedValue(undefined);
     mockListPeople.mockResolvedValue(mockPeople);
     mockListProjects.mockResolvedValue(mockProjects);
+    mockListProficiencyLevels.mockResolvedValue(mockProficiencyLevels);
+    mockSuggestProductivityFactor.mockResolvedValue(null);
   });
 
   it("renders form fields correctly", async () => {
@@ -168,6 +197,8 @@ describe("AssignmentForm", () => {
         productivity_factor: 0.5, // Default "Proficient" level
         start_date: "2024-01-01",
         end_date: "2024-03-31",
+        min_allocation_percentage: null,
+        max_allocation_percentage: null,
       } satisfies CreateAssignmentInput);
     });
   });
@@ -190,8 +221,13 @@ describe("AssignmentForm", () => {
       productivity_factor: 0.8, // Expert level
       start_date: "2024-02-01",
       end_date: "2024-02-28",
+      is_pinned: false,
+      pinned_allocation_percentage: null,
+      min_allocation_percentage: null,
+      max_allocation_percentage: null,
       calculated_allocation_percentage: null,
       calculated_effective_hours: null,
+      calculated_start_date: null,
       last_calculated_at: null,
       created_at: new Date().toISOString(),
     };
@@ -212,6 +248,13 @@ describe("AssignmentForm", () => {
     // Check dates are pre-filled
     expect(screen.getByLabelText(/start date/i)).toHaveValue("2024-02-01");
     expect(screen.getByLabelText(/end date/i)).toHaveValue("2024-02-28");
+
+    // The factor matches a preset from the backend
+    await waitFor(() => {
+      expect(
+        screen.getByPlaceholderText(/select proficiency level/i),
+      ).toHaveValue("Expert (80%)");
+    });
   });
 
   it("shows 'Update' button when editing", async () => {
@@ -223,8 +266,13 @@ describe("AssignmentForm", () => {
       productivity_factor: 0.5,
       start_date: "2024-01-01",
       end_date: "2024-03-31",
+      is_pinned: false,
+      pinned_allocation_percentage: null,
+      min_allocation_percentage: null,
+      max_allocation_percentage: null,
       calculated_allocation_percentage: null,
       calculated_effective_hours: null,
+      calculated_start_date: null,
       last_calculated_at: null,
       created_at: new Date().toISOString(),
     };
@@ -425,7 +473,7 @@ describe("AssignmentForm", () => {
 
     // Change proficiency to Expert (0.8)
     await user.click(screen.getByPlaceholderText(/select proficiency level/i));
-    await user.click(screen.getByText("Expert (80%)"));
+    await user.click(await screen.findByText("Expert (80%)"));
 
     await user.click(screen.getByRole("button", { name: /create/i }));
 
@@ -16,14 +16,20 @@ import type {
   Person,
   Project,
   PlanningPeriod,
+  ProductivitySuggestion,
 } from "../../types";
-import { listPeople, listProjects } from "../../lib/tauri";
 import {
-  PROFICIENCY_LEVELS,
+  listPeople,
+  listProjects,
+  suggestProductivityFactor,
+} from "../../lib/tauri";
+import {
   DEFAULT_PROFICIENCY_FACTOR,
   CUSTOM_PROFICIENCY_VALUE,
   getProficiencyByFactor,
-} from "../../constants/proficiency";
+  getProficiencyLabelWithPercentage,
+  useProficiencyLevels,
+} from "../../lib/proficiency";
 
 interface AssignmentFormProps {
   opened: boolean;
@@ -46,9 +52,13 @@ export function AssignmentForm({
   const [people, setPeople] = useState<Person[]>([]);
   const [projects, setProjects] = useState<Project[]>([]);
   const [loadingData, setLoadingData] = useState(true);
+  const proficiencyLevels = useProficiencyLevels();
   const [isCustomProficiency, setIsCustomProficiency] = useState(false);
   const [selectedProficiency, setSelectedProficiency] =
     useState<string>("proficient");
+  const [suggestion, setSuggestion] = useState<ProductivitySuggestion | null>(
+    null,
+  );
 
   useEffect(() => {
     if (opened) {
@@ -80,6 +90,8 @@ export function AssignmentForm({
       productivity_factor: DEFAULT_PROFICIENCY_FACTOR,
       start_date: planningPeriod.start_date,
       end_date: planningPeriod.end_date,
+      min_allocation_percentage: null,
+      max_allocation_percentage: null,
     },
     validate: {
       person_id: (value) => (value <= 0 ? "Please select a person" : null),
@@ -112,6 +124,16 @@ export function AssignmentForm({
         }
         return null;
       },
+      min_allocation_percentage: (value, values) => {
+        if (
+          value != null &&
+          values.max_allocation_percentage != null &&
+          value > values.max_allocation_percentage
+        ) {
+          return "Minimum allocation cannot exceed the maximum";
+        }
+        return null;
+      },
     },
   });
 
@@ -120,17 +142,6 @@ export function AssignmentForm({
     if (opened) {
       if (assignment) {
         // Edit mode - populate with assignment's data
-        const factor = assignment.productivity_factor;
-        const preset = getProficiencyByFactor(factor);
-
-        if (preset) {
-          setSelectedProficiency(preset.value);
-          setIsCustomProficiency(false);
-        } else {
-          setSelectedProficiency(CUSTOM_PROFICIENCY_VALUE);
-          setIsCustomProficiency(true);
-        }
-
         form.setValues({
           person_id: assignment.person_id,
           project_id: assignment.project_id,
@@ -138,6 +149,8 @@ export function AssignmentForm({
           productivity_factor: assignment.productivity_factor,
           start_date: assignment.start_date,
           end_date: assignment.end_date,
+          min_allocation_percentage: assignment.min_allocation_percentage,
+          max_allocation_percentage: assignment.max_allocation_percentage,
         });
         form.clearErrors();
       } else {
@@ -152,12 +165,60 @@ export function AssignmentForm({
           productivity_factor: DEFAULT_PROFICIENCY_FACTOR,
           start_date: planningPeriod.start_date,
           end_date: planningPeriod.end_date,
+          min_allocation_percentage: null,
+          max_allocation_percentage: null,
         });
         form.clearErrors();
       }
     }
   }, [opened, assignment, planningPeriod]);
 
+  // Match the assignment's factor to a preset once the presets are loaded
+  useEffect(() => {
+    if (opened && assignment) {
+      const preset = getProficiencyByFactor(
+        proficiencyLevels,
+        assignment.productivity_factor,
+      );
+
+      if (preset) {
+        setSelectedProficiency(preset.value);
+        setIsCustomProficiency(false);
+      } else {
+        setSelectedProficiency(CUSTOM_PROFICIENCY_VALUE);
+        setIsCustomProficiency(true);
+      }
+    }
+  }, [opened, assignment, proficiencyLevels]);
+
+  // Suggest a productivity factor from the person's proficiency in the project's skills
+  useEffect(() => {
+    const { person_id, project_id } = form.values;
+    setSuggestion(null);
+    if (!opened || person_id <= 0 || project_id <= 0) return;
+
+    let cancelled = false;
+    suggestProductivityFactor(person_id, project_id, planningPeriod.id)
+      .then((result) => {
+        if (!cancelled) setSuggestion(result ?? null);
+      })
+      .catch((error) => {
+        console.error("Failed to suggest productivity factor:", error);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [opened, form.values.person_id, form.values.project_id, planningPeriod]);
+
+  const applySuggestion = () => {
+    if (!suggestion) return;
+    const factor = Math.round(suggestion.productivity_factor * 100) / 100;
+    const preset = getProficiencyByFactor(proficiencyLevels, factor);
+    setSelectedProficiency(preset ? preset.value : CUSTOM_PROFICIENCY_VALUE);
+    setIsCustomProficiency(!preset);
+    form.setFieldValue("productivity_factor", factor);
+  };
+
   // Handle proficiency selection change
   const handleProficiencyChange = (value: string | null) => {
     if (!value) return;
@@ -169,7 +230,7 @@ export function AssignmentForm({
     } else {
       setIsCustomProficiency(false);
       setSelectedProficiency(value);
-      const level = PROFICIENCY_LEVELS.find((l) => l.value === value);
+      const level = proficiencyLevels.find((l) => l.value === value);
       if (level) {
         form.setFieldValue("productivity_factor", level.factor);
       }
@@ -201,7 +262,7 @@ export function AssignmentForm({
 
   // Build proficiency options for dropdown
   const proficiencyOptions = [
-    ...PROFICIENCY_LEVELS.map((level) => ({
+    ...proficiencyLevels.map((level) => ({
       value: level.value,
       label: `${level.label} (${(level.factor * 100).toFixed(0)}%)`,
     })),
@@ -269,6 +330,28 @@ export function AssignmentForm({
             onChange={handleProficiencyChange}
           />
 
+          {suggestion && (
+            <Group justify="space-between" wrap="nowrap">
+              <Text size="sm" c="dimmed">
+                Skills suggest{" "}
+                {getProficiencyLabelWithPercentage(
+                  proficiencyLevels,
+                  Math.round(suggestion.productivity_factor * 100) / 100,
+                )}
+                :{" "}
+                {suggestion.skills
+                  .map(
+                    (skill) =>
+                      `${skill.skill_name} ${skill.proficiency ?? "missing"}`,
+                  )
+                  .join(", ")}
+              </Text>
+              <Button variant="light" size="xs" onClick={applySuggestion}>
+                Use Suggestion
+              </Button>
+            </Group>
+          )}
+
           {isCustomProficiency && (
             <NumberInput
               label="Custom Productivity Factor"
@@ -299,6 +382,39 @@ export function AssignmentForm({
             />
           </Group>
 
+          <Group grow>
+            <NumberInput
+              label="Minimum Allocation (%)"
+              placeholder="No minimum"
+              description="Share of the person's time this assignment always gets"
+              min={0}
+              max={100}
+              value={form.values.min_allocation_percentage ?? ""}
+              onChange={(value) =>
+                form.setFieldValue(
+                  "min_allocation_percentage",
+                  typeof value === "number" ? value : null,
+                )
+              }
+              error={form.errors.min_allocation_percentage}
+            />
+
+            <NumberInput
+              label="Maximum Allocation (%)"
+              placeholder="No maximum"
+              description="Share of the person's time this assignment never exceeds"
+              min={0}
+              max={100}
+              value={form.values.max_allocation_percentage ?? ""}
+              onChange={(value) =>
+                form.setFieldValue(
+                  "max_allocation_percentage",
+                  typeof value === "number" ? value : null,
+                )
+              }
+            />
+          </Group>
+
           <Group justify="flex-end" mt="md">
             <Button variant="subtle" onClick={onClose} disabled={loading}>
               Cancel
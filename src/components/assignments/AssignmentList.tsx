@@ -1,8 +1,16 @@
 import { ActionIcon, Avatar, Group, Table, Text, Tooltip } from "@mantine/core";
 import { IconEdit, IconTrash } from "@tabler/icons-react";
 import { useGravatarUrl } from "../../lib/gravatar";
-import type { Assignment, Person, Project } from "../../types";
-import { getProficiencyLabelWithPercentage } from "../../constants/proficiency";
+import {
+  getProficiencyLabelWithPercentage,
+  useProficiencyLevels,
+} from "../../lib/proficiency";
+import type {
+  Assignment,
+  Person,
+  ProficiencyLevel,
+  Project,
+} from "../../types";
 
 interface AssignmentListProps {
   assignments: Assignment[];
@@ -16,6 +24,7 @@ interface AssignmentRowProps {
   assignment: Assignment;
   person: Person | undefined;
   project: Project | undefined;
+  proficiencyLevels: ProficiencyLevel[];
   onEdit: (assignment: Assignment) => void;
   onDelete: (id: number) => void;
 }
@@ -24,6 +33,7 @@ function AssignmentRow({
   assignment,
   person,
   project,
+  proficiencyLevels,
   onEdit,
   onDelete,
 }: AssignmentRowProps) {
@@ -33,6 +43,11 @@ function AssignmentRow({
     name: person?.name,
   });
 
+  const proficiencyLabel = getProficiencyLabelWithPercentage(
+    proficiencyLevels,
+    assignment.productivity_factor,
+  );
+
   return (
     <Table.Tr key={assignment.id}>
       <Table.Td>
@@ -43,13 +58,8 @@ function AssignmentRow({
       </Table.Td>
       <Table.Td>{project?.name || "Unknown"}</Table.Td>
       <Table.Td>
-        <Tooltip
-          label={`Productivity factor: ${getProficiencyLabelWithPercentage(assignment.productivity_factor)}`}
-          withArrow
-        >
-          <Text size="sm">
-            {getProficiencyLabelWithPercentage(assignment.productivity_factor)}
-          </Text>
+        <Tooltip label={`Productivity factor: ${proficiencyLabel}`} withArrow>
+          <Text size="sm">{proficiencyLabel}</Text>
         </Tooltip>
       </Table.Td>
       <Table.Td>
@@ -111,6 +121,8 @@ export function AssignmentList({
   onEdit,
   onDelete,
 }: AssignmentListProps) {
+  const proficiencyLevels = useProficiencyLevels();
+
   if (assignments.length === 0) {
     return (
       <Text c="dimmed" ta="center" py="xl">
@@ -143,6 +155,7 @@ export function AssignmentList({
             assignment={assignment}
             person={personMap.get(assignment.person_id)}
             project={projectMap.get(assignment.project_id)}
+            proficiencyLevels={proficiencyLevels}
             onEdit={onEdit}
             onDelete={onDelete}
           />
@@ -55,6 +55,9 @@ export function HolidayForm({
           name: holiday.name || "",
           start_date: holiday.start_date,
           end_date: holiday.end_date,
+          // Keep partial-day settings so editing doesn't turn a half day into a full day
+          hours: holiday.hours,
+          day_fraction: holiday.day_fraction,
         });
         form.clearErrors();
       } else {
@@ -3,15 +3,39 @@ import { screen, waitFor } from "@testing-library/react";
 import userEvent from "@testing-library/user-event";
 import { render } from "../../test/test-utils";
 import { PersonForm } from "./PersonForm";
-import type { Person, CreatePersonInput, Country } from "../../types";
-import { listCountries } from "../../lib/tauri";
+import type {
+  Person,
+  CreatePersonInput,
+  Country,
+  Skill,
+  PersonSkill,
+  ProficiencyLevel,
+} from "../../types";
+import {
+  listCountries,
+  listSkills,
+  listPersonSkills,
+  listProficiencyLevels,
+  setPersonSkill,
+  deletePersonSkill,
+} from "../../lib/tauri";
 
 // Mock the tauri module
 vi.mock("../../lib/tauri", () => ({
   listCountries: vi.fn(),
+  listSkills: vi.fn(),
+  listPersonSkills: vi.fn(),
+  listProficiencyLevels: vi.fn(),
+  setPersonSkill: vi.fn(),
+  deletePersonSkill: vi.fn(),
 }));
 
 const mockListCountries = vi.mocked(listCountries);
+const mockListSkills = vi.mocked(listSkills);
+const mockListPersonSkills = vi.mocked(listPersonSkills);
+const mockListProficiencyLevels = vi.mocked(listProficiencyLevels);
+const mockSetPersonSkill = vi.mocked(setPersonSkill);
+const mockDeletePersonSkill = vi.mocked(deletePersonSkill);
 
 describe("PersonForm", () => {
   const mockOnClose = vi.fn();
@@ -38,6 +62,21 @@ describe("PersonForm", () => {
     },
   ];
 
+  const mockProficiencyLevels: ProficiencyLevel[] = [
+    {
+      value: "expert",
+      factor: 0.8,
+      label: "Expert",
+      description: "Deep expertise and experience.",
+    },
+    {
+      value: "beginner",
+      factor: 0.2,
+      label: "Beginner",
+      description: "Basic familiarity only.",
+    },
+  ];
+
   const defaultProps = {
     opened: true,
     onClose: mockOnClose,
@@ -50,6 +89,9 @@ describe("PersonForm", () => {
     vi.clearAllMocks();
     mockOnSubmit.mockResolvedValue(undefined);
     mockListCountries.mockResolvedValue(mockCountries);
+    mockListSkills.mockResolvedValue([]);
+    mockListPersonSkills.mockResolvedValue([]);
+    mockListProficiencyLevels.mockResolvedValue(mockProficiencyLevels);
   });
 
   it("renders form fields correctly", async () => {
@@ -152,6 +194,7 @@ describe("PersonForm", () => {
         available_hours_per_week: 40,
         country_id: null,
         working_days: "Mon,Tue,Wed,Thu,Fri",
+        max_utilization_percentage: null,
       } satisfies CreatePersonInput);
     });
   });
@@ -180,6 +223,7 @@ describe("PersonForm", () => {
         available_hours_per_week: 20,
         country_id: null,
         working_days: "Mon,Tue,Wed,Thu",
+        max_utilization_percentage: null,
       });
     });
   });
@@ -201,6 +245,7 @@ describe("PersonForm", () => {
       available_hours_per_week: 35,
       country_id: 2,
       working_days: "Mon,Tue,Wed,Thu",
+      max_utilization_percentage: null,
       created_at: new Date().toISOString(),
     };
 
@@ -241,6 +286,7 @@ describe("PersonForm", () => {
       available_hours_per_week: 40,
       country_id: null,
       working_days: "Mon,Tue,Wed,Thu,Fri",
+      max_utilization_percentage: null,
       created_at: new Date().toISOString(),
     };
 
@@ -306,6 +352,7 @@ describe("PersonForm", () => {
       available_hours_per_week: 35,
       country_id: null,
       working_days: "Mon,Wed,Fri",
+      max_utilization_percentage: null,
       created_at: new Date().toISOString(),
     };
 
@@ -371,4 +418,92 @@ describe("PersonForm", () => {
       expect(mockOnClose).toHaveBeenCalled();
     });
   });
+
+  it("saves changed skill proficiencies when updating a person", async () => {
+    const existingPerson: Person = {
+      id: 1,
+      name: "Jane Smith",
+      email: "jane@example.com",
+      available_hours_per_week: 40,
+      country_id: null,
+      working_days: "Mon,Tue,Wed,Thu,Fri",
+      max_utilization_percentage: null,
+      created_at: new Date().toISOString(),
+    };
+    const skills: Skill[] = [
+      {
+        id: 1,
+        name: "Rust",
+        description: null,
+        created_at: new Date().toISOString(),
+      },
+      {
+        id: 2,
+        name: "React",
+        description: null,
+        created_at: new Date().toISOString(),
+      },
+    ];
+    const personSkills: PersonSkill[] = [
+      {
+        id: 7,
+        person_id: 1,
+        skill_id: 2,
+        proficiency: "beginner",
+        created_at: new Date().toISOString(),
+      },
+    ];
+    mockListSkills.mockResolvedValue(skills);
+    mockListPersonSkills.mockResolvedValue(personSkills);
+
+    const user = userEvent.setup();
+    render(
+      <PersonForm
+        {...defaultProps}
+        person={existingPerson}
+        title="Edit Person"
+      />,
+    );
+
+    // Proficiencies use the presets from the backend
+    const react = await screen.findByLabelText("React");
+    await waitFor(() => {
+      expect(react).toHaveValue("Beginner (20%)");
+    });
+
+    await user.click(screen.getByLabelText("Rust"));
+    await user.click(await screen.findByText("Expert (80%)"));
+    await user.click(screen.getByRole("button", { name: /update/i }));
+
+    await waitFor(() => {
+      expect(mockOnSubmit).toHaveBeenCalled();
+      expect(mockSetPersonSkill).toHaveBeenCalledWith({
+        person_id: 1,
+        skill_id: 1,
+        proficiency: "expert",
+      });
+    });
+    // Unchanged proficiencies are left alone
+    expect(mockSetPersonSkill).toHaveBeenCalledTimes(1);
+    expect(mockDeletePersonSkill).not.toHaveBeenCalled();
+  });
+
+  it("does not show skills when creating a person", async () => {
+    mockListSkills.mockResolvedValue([
+      {
+        id: 1,
+        name: "Rust",
+        description: null,
+        created_at: new Date().toISOString(),
+      },
+    ]);
+
+    render(<PersonForm {...defaultProps} />);
+
+    await waitFor(() => {
+      expect(mockListCountries).toHaveBeenCalled();
+    });
+    expect(mockListSkills).not.toHaveBeenCalled();
+    expect(screen.queryByText("Skills")).not.toBeInTheDocument();
+  });
 });
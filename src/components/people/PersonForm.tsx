@@ -8,10 +8,24 @@ import {
   NumberInput,
   Select,
   Checkbox,
+  Text,
 } from "@mantine/core";
 import { useForm } from "@mantine/form";
-import type { Person, CreatePersonInput, Country } from "../../types";
-import { listCountries } from "../../lib/tauri";
+import type {
+  Person,
+  CreatePersonInput,
+  Country,
+  Skill,
+  PersonSkill,
+} from "../../types";
+import {
+  listCountries,
+  listSkills,
+  listPersonSkills,
+  setPersonSkill,
+  deletePersonSkill,
+} from "../../lib/tauri";
+import { useProficiencyLevels } from "../../lib/proficiency";
 
 interface PersonFormProps {
   opened: boolean;
@@ -30,6 +44,13 @@ export function PersonForm({
 }: PersonFormProps) {
   const [loading, setLoading] = useState(false);
   const [countries, setCountries] = useState<Country[]>([]);
+  const [skills, setSkills] = useState<Skill[]>([]);
+  const [personSkills, setPersonSkills] = useState<PersonSkill[]>([]);
+  // Proficiency per skill ID as edited, null for skills the person lacks
+  const [proficiencies, setProficiencies] = useState<
+    Record<number, string | null>
+  >({});
+  const proficiencyLevels = useProficiencyLevels();
   const [workingDaysArray, setWorkingDaysArray] = useState<string[]>([
     "Mon",
     "Tue",
@@ -45,6 +66,7 @@ export function PersonForm({
       available_hours_per_week: 40,
       country_id: null,
       working_days: "Mon,Tue,Wed,Thu,Fri",
+      max_utilization_percentage: null,
     },
     validate: {
       name: (value) => (!value ? "Name is required" : null),
@@ -79,6 +101,54 @@ export function PersonForm({
     }
   };
 
+  // Load skills when editing a person, since skills are stored per person
+  useEffect(() => {
+    if (opened && person) {
+      loadSkills(person.id);
+    } else {
+      setSkills([]);
+      setPersonSkills([]);
+      setProficiencies({});
+    }
+  }, [opened, person]);
+
+  const loadSkills = async (personId: number) => {
+    try {
+      const [skillsData, personSkillsData] = await Promise.all([
+        listSkills(),
+        listPersonSkills(personId),
+      ]);
+      setSkills(skillsData);
+      setPersonSkills(personSkillsData);
+      setProficiencies(
+        Object.fromEntries(
+          personSkillsData.map((s) => [s.skill_id, s.proficiency]),
+        ),
+      );
+    } catch (error) {
+      console.error("Failed to load skills:", error);
+    }
+  };
+
+  // Store the proficiencies that were changed in the form
+  const saveSkills = async (personId: number) => {
+    for (const skill of skills) {
+      const existing = personSkills.find((s) => s.skill_id === skill.id);
+      const proficiency = proficiencies[skill.id] ?? null;
+      if (proficiency === (existing?.proficiency ?? null)) continue;
+
+      if (proficiency) {
+        await setPersonSkill({
+          person_id: personId,
+          skill_id: skill.id,
+          proficiency,
+        });
+      } else if (existing) {
+        await deletePersonSkill(existing.id);
+      }
+    }
+  };
+
   // Update form values when modal opens or person changes
   useEffect(() => {
     if (opened) {
@@ -95,6 +165,7 @@ export function PersonForm({
           available_hours_per_week: person.available_hours_per_week,
           country_id: person.country_id,
           working_days: person.working_days,
+          max_utilization_percentage: person.max_utilization_percentage,
         });
         form.clearErrors();
       } else {
@@ -116,6 +187,9 @@ export function PersonForm({
       // Convert working days array to comma-separated string
       const workingDaysString = workingDaysArray.join(",");
       await onSubmit({ ...values, working_days: workingDaysString });
+      if (person) {
+        await saveSkills(person.id);
+      }
       form.reset();
       setWorkingDaysArray(["Mon", "Tue", "Wed", "Thu", "Fri"]);
       onClose();
@@ -174,6 +248,22 @@ export function PersonForm({
             {...form.getInputProps("available_hours_per_week")}
           />
 
+          <NumberInput
+            label="Max Planned Utilization (%)"
+            placeholder="App default"
+            description="Share of available hours the optimizer may plan, leaving slack for interrupts"
+            min={1}
+            max={100}
+            step={5}
+            value={form.values.max_utilization_percentage ?? ""}
+            onChange={(value) =>
+              form.setFieldValue(
+                "max_utilization_percentage",
+                typeof value === "number" ? value : null,
+              )
+            }
+          />
+
           <Checkbox.Group
             label="Working Days"
             description="Select the days this person works for the company"
@@ -197,6 +287,36 @@ export function PersonForm({
             </Group>
           </Checkbox.Group>
 
+          {person && skills.length > 0 && (
+            <Stack gap="xs">
+              <div>
+                <Text size="sm" fw={500}>
+                  Skills
+                </Text>
+                <Text size="xs" c="dimmed">
+                  Proficiency in each skill, used to suggest productivity
+                  factors for assignments
+                </Text>
+              </div>
+              {skills.map((skill) => (
+                <Select
+                  key={skill.id}
+                  label={skill.name}
+                  placeholder="No experience"
+                  data={proficiencyLevels.map((level) => ({
+                    value: level.value,
+                    label: `${level.label} (${(level.factor * 100).toFixed(0)}%)`,
+                  }))}
+                  value={proficiencies[skill.id] ?? null}
+                  onChange={(value) =>
+                    setProficiencies({ ...proficiencies, [skill.id]: value })
+                  }
+                  clearable
+                />
+              ))}
+            </Stack>
+          )}
+
           <Group justify="flex-end" mt="md">
             <Button variant="subtle" onClick={onClose} disabled={loading}>
               Cancel
@@ -0,0 +1,147 @@
+/**
+ * Proficiency/Productivity Level Presets
+ *
+ * These presets represent how productive a person is expected to be on a specific
+ * project based on their expertise/familiarity with the technology, domain, or tools.
+ *
+ * The productivity factor is a multiplier (0.0 - 1.0) applied to available hours:
+ * Effective Hours = Available Hours × Productivity Factor × Allocation %
+ *
+ * The presets themselves come from the backend, which stores person skills with
+ * them and derives productivity suggestions from them.
+ */
+
+import { useState, useEffect } from "react";
+import type { ProficiencyLevel } from "../types";
+import { listProficiencyLevels } from "./tauri";
+
+/**
+ * Default proficiency factor for new assignments
+ * Set to "Proficient" (0.5) as a reasonable baseline
+ */
+export const DEFAULT_PROFICIENCY_FACTOR = 0.5;
+
+/**
+ * Special value indicating custom productivity factor
+ */
+export const CUSTOM_PROFICIENCY_VALUE = "custom";
+
+let proficiencyLevelsRequest: Promise<ProficiencyLevel[]> | null = null;
+
+/**
+ * Load the proficiency presets from the backend
+ * The presets don't change while the app runs, so they are only fetched once
+ */
+export function loadProficiencyLevels(): Promise<ProficiencyLevel[]> {
+  if (!proficiencyLevelsRequest) {
+    proficiencyLevelsRequest = listProficiencyLevels().catch((error) => {
+      // Let the next caller try again
+      proficiencyLevelsRequest = null;
+      throw error;
+    });
+  }
+  return proficiencyLevelsRequest;
+}
+
+/**
+ * React hook for the proficiency presets
+ *
+ * @returns ProficiencyLevel[] - Empty until the presets are loaded
+ */
+export function useProficiencyLevels(): ProficiencyLevel[] {
+  const [levels, setLevels] = useState<ProficiencyLevel[]>([]);
+
+  useEffect(() => {
+    let mounted = true;
+
+    loadProficiencyLevels()
+      .then((data) => {
+        if (mounted) {
+          setLevels(data);
+        }
+      })
+      .catch((error) => {
+        console.error("Failed to load proficiency levels:", error);
+      });
+
+    return () => {
+      mounted = false;
+    };
+  }, []);
+
+  return levels;
+}
+
+/**
+ * Get proficiency label by factor value
+ * Returns the matching preset label, or formats as custom percentage
+ */
+export function getProficiencyLabel(
+  levels: ProficiencyLevel[],
+  factor: number,
+): string {
+  const level = levels.find((l) => l.factor === factor);
+  if (level) {
+    return level.label;
+  }
+  // If not a preset, show as custom percentage
+  return `Custom (${(factor * 100).toFixed(0)}%)`;
+}
+
+/**
+ * Get proficiency label with percentage
+ * Used for detailed displays (e.g., "Expert (80%)")
+ */
+export function getProficiencyLabelWithPercentage(
+  levels: ProficiencyLevel[],
+  factor: number,
+): string {
+  const level = levels.find((l) => l.factor === factor);
+  if (level) {
+    return `${level.label} (${(level.factor * 100).toFixed(0)}%)`;
+  }
+  return `Custom (${(factor * 100).toFixed(0)}%)`;
+}
+
+/**
+ * Get full proficiency level details by factor value
+ * Returns the matching preset, or null if custom
+ */
+export function getProficiencyByFactor(
+  levels: ProficiencyLevel[],
+  factor: number,
+): ProficiencyLevel | null {
+  return levels.find((l) => l.factor === factor) || null;
+}
+
+/**
+ * Find closest proficiency preset for a given factor
+ * Useful for mapping arbitrary values to nearest preset
+ */
+export function getClosestProficiency(
+  levels: ProficiencyLevel[],
+  factor: number,
+): ProficiencyLevel | null {
+  let closest: ProficiencyLevel | null = null;
+  let minDiff = Infinity;
+
+  for (const level of levels) {
+    const diff = Math.abs(factor - level.factor);
+    if (diff < minDiff) {
+      minDiff = diff;
+      closest = level;
+    }
+  }
+
+  return closest;
+}
+
+/**
+ * Check if a factor matches a preset exactly
+ */
+export function isPresetFactor(
+  levels: ProficiencyLevel[],
+  factor: number,
+): boolean {
+  return levels.some((l) => l.factor === factor);
+}
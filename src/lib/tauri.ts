@@ -1,15 +1,18 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type {
   Person,
   PersonWithCountry,
   CreatePersonInput,
   PlanningPeriod,
+  PlanningPeriodWithStatus,
   CreatePlanningPeriodInput,
   Project,
   CreateProjectInput,
   Assignment,
   CreateAssignmentInput,
   Absence,
+  AbsenceWithHours,
   CreateAbsenceInput,
   Job,
   JobWithTasks,
@@ -22,6 +25,8 @@ import type {
   JobDependencies,
   ProjectRequirement,
   CreateProjectRequirementInput,
+  ProjectDependency,
+  CreateProjectDependencyInput,
   Country,
   CreateCountryInput,
   CountryDependencies,
@@ -31,13 +36,37 @@ import type {
   NagerDateCountry,
   HolidayImportPreview,
   ImportHolidaysResult,
+  OptimizationOptions,
+  AutoOptimizationSetting,
+  AutoOptimizationEvent,
+  OptimizationPreview,
   OptimizationResult,
+  OptimizationRun,
+  OptimizationRunComparison,
+  OptimizationRunSummary,
+  OptimizationStrategy,
+  MultiPeriodOptimizationResult,
+  RestoredOptimizationRun,
+  CapacitySimulation,
+  StaffingRecommendations,
+  ProposedAssignment,
   CapacityOverview,
+  CapacityTimeline,
   PersonCapacity,
+  TimelineGranularity,
   ProjectStaffing,
   PersonDependencies,
   ProjectDependencies,
   PlanningPeriodDependencies,
+  AppSettings,
+  Skill,
+  CreateSkillInput,
+  ProficiencyLevel,
+  PersonSkill,
+  SetPersonSkillInput,
+  ProjectSkillRequirement,
+  CreateProjectSkillRequirementInput,
+  ProductivitySuggestion,
 } from "../types";
 
 // ============================================================================
@@ -77,7 +106,9 @@ export async function checkPersonDependencies(
 // Planning Period Commands
 // ============================================================================
 
-export async function listPlanningPeriods(): Promise<PlanningPeriod[]> {
+export async function listPlanningPeriods(): Promise<
+  PlanningPeriodWithStatus[]
+> {
   return await invoke("list_planning_periods");
 }
 
@@ -140,6 +171,24 @@ export async function checkProjectDependencies(
   return await invoke("check_project_dependencies", { id });
 }
 
+// ============================================================================
+// Project Dependency Commands
+// ============================================================================
+
+export async function listProjectDependencies(): Promise<ProjectDependency[]> {
+  return await invoke("list_project_dependencies");
+}
+
+export async function createProjectDependency(
+  input: CreateProjectDependencyInput,
+): Promise<ProjectDependency> {
+  return await invoke("create_project_dependency", { input });
+}
+
+export async function deleteProjectDependency(id: number): Promise<void> {
+  return await invoke("delete_project_dependency", { id });
+}
+
 // ============================================================================
 // Project Requirement Commands
 // ============================================================================
@@ -180,6 +229,77 @@ export async function deleteProjectRequirement(id: number): Promise<void> {
   return await invoke("delete_project_requirement", { id });
 }
 
+// ============================================================================
+// Skill Commands
+// ============================================================================
+
+export async function listSkills(): Promise<Skill[]> {
+  return await invoke("list_skills");
+}
+
+export async function createSkill(input: CreateSkillInput): Promise<Skill> {
+  return await invoke("create_skill", { input });
+}
+
+export async function updateSkill(
+  id: number,
+  input: CreateSkillInput,
+): Promise<Skill> {
+  return await invoke("update_skill", { id, input });
+}
+
+export async function deleteSkill(id: number): Promise<void> {
+  return await invoke("delete_skill", { id });
+}
+
+export async function listProficiencyLevels(): Promise<ProficiencyLevel[]> {
+  return await invoke("list_proficiency_levels");
+}
+
+export async function listPersonSkills(
+  personId: number,
+): Promise<PersonSkill[]> {
+  return await invoke("list_person_skills", { personId });
+}
+
+export async function setPersonSkill(
+  input: SetPersonSkillInput,
+): Promise<PersonSkill> {
+  return await invoke("set_person_skill", { input });
+}
+
+export async function deletePersonSkill(id: number): Promise<void> {
+  return await invoke("delete_person_skill", { id });
+}
+
+export async function listProjectSkillRequirements(
+  planningPeriodId: number,
+): Promise<ProjectSkillRequirement[]> {
+  return await invoke("list_project_skill_requirements", { planningPeriodId });
+}
+
+export async function upsertProjectSkillRequirement(
+  input: CreateProjectSkillRequirementInput,
+): Promise<ProjectSkillRequirement> {
+  return await invoke("upsert_project_skill_requirement", { input });
+}
+
+export async function deleteProjectSkillRequirement(id: number): Promise<void> {
+  return await invoke("delete_project_skill_requirement", { id });
+}
+
+export async function suggestProductivityFactor(
+  personId: number,
+  projectId: number,
+  planningPeriodId: number,
+): Promise<ProductivitySuggestion | null> {
+  return await invoke("suggest_productivity_factor", {
+    personId,
+    projectId,
+    planningPeriodId,
+  });
+}
+
 // ============================================================================
 // Assignment Commands
 // ============================================================================
@@ -207,24 +327,13 @@ export async function deleteAssignment(id: number): Promise<void> {
   return await invoke("delete_assignment", { id });
 }
 
-export async function pinAssignment(
-  id: number,
-  allocationPercent: number,
-): Promise<void> {
-  // This will be implemented when we add pin/unpin functionality
-  return await invoke("pin_assignment", { id, allocationPercent });
-}
-
-export async function unpinAssignment(id: number): Promise<void> {
-  // This will be implemented when we add pin/unpin functionality
-  return await invoke("unpin_assignment", { id });
-}
-
 // ============================================================================
 // Absence Commands
 // ============================================================================
 
-export async function listAbsences(personId: number): Promise<Absence[]> {
+export async function listAbsences(
+  personId: number,
+): Promise<AbsenceWithHours[]> {
   return await invoke("list_absences", { personId });
 }
 
@@ -353,8 +462,128 @@ export async function deletePersonJobAssignment(id: number): Promise<void> {
 
 export async function optimizeAssignments(
   planningPeriodId: number,
+  strategy?: OptimizationStrategy,
+  options?: OptimizationOptions,
+): Promise<OptimizationResult> {
+  return await invoke("optimize_assignments", {
+    planningPeriodId,
+    strategy,
+    options,
+  });
+}
+
+export async function optimizePlanningPeriods(
+  planningPeriodIds: number[],
+  strategy?: OptimizationStrategy,
+  options?: OptimizationOptions,
+): Promise<MultiPeriodOptimizationResult> {
+  return await invoke("optimize_planning_periods", {
+    planningPeriodIds,
+    strategy,
+    options,
+  });
+}
+
+export async function previewAssignmentOptimization(
+  planningPeriodId: number,
+  strategy?: OptimizationStrategy,
+  options?: OptimizationOptions,
+): Promise<OptimizationPreview> {
+  return await invoke("preview_assignment_optimization", {
+    planningPeriodId,
+    strategy,
+    options,
+  });
+}
+
+export async function applyAssignmentOptimization(
+  preview: OptimizationPreview,
 ): Promise<OptimizationResult> {
-  return await invoke("optimize_assignments", { planningPeriodId });
+  return await invoke("apply_assignment_optimization", { preview });
+}
+
+export async function listOptimizationRuns(
+  planningPeriodId: number,
+): Promise<OptimizationRunSummary[]> {
+  return await invoke("list_optimization_runs", { planningPeriodId });
+}
+
+export async function getOptimizationRun(id: number): Promise<OptimizationRun> {
+  return await invoke("get_optimization_run", { id });
+}
+
+export async function compareOptimizationRuns(
+  baseRunId: number,
+  otherRunId: number,
+): Promise<OptimizationRunComparison> {
+  return await invoke("compare_optimization_runs", { baseRunId, otherRunId });
+}
+
+export async function restoreOptimizationRun(
+  id: number,
+): Promise<RestoredOptimizationRun> {
+  return await invoke("restore_optimization_run", { id });
+}
+
+export async function getAutoOptimization(
+  planningPeriodId: number,
+): Promise<AutoOptimizationSetting> {
+  return await invoke("get_auto_optimization", { planningPeriodId });
+}
+
+export async function setAutoOptimization(
+  planningPeriodId: number,
+  enabled: boolean,
+  strategy?: OptimizationStrategy,
+  options?: OptimizationOptions,
+): Promise<AutoOptimizationSetting> {
+  return await invoke("set_auto_optimization", {
+    planningPeriodId,
+    enabled,
+    strategy,
+    options,
+  });
+}
+
+export async function onAutoOptimizationCompleted(
+  handler: (event: AutoOptimizationEvent) => void,
+): Promise<UnlistenFn> {
+  return await listen<AutoOptimizationEvent>(
+    "auto-optimization-completed",
+    (event) => handler(event.payload),
+  );
+}
+
+export async function simulateCapacity(
+  planningPeriodId: number,
+  runs?: number,
+  seed?: number,
+): Promise<CapacitySimulation> {
+  return await invoke("simulate_capacity", { planningPeriodId, runs, seed });
+}
+
+export async function getStaffingRecommendations(
+  planningPeriodId: number,
+  strategy?: OptimizationStrategy,
+  options?: OptimizationOptions,
+  productivityFactor?: number,
+): Promise<StaffingRecommendations> {
+  return await invoke("get_staffing_recommendations", {
+    planningPeriodId,
+    strategy,
+    options,
+    productivityFactor,
+  });
+}
+
+export async function acceptStaffingRecommendations(
+  planningPeriodId: number,
+  assignments: ProposedAssignment[],
+): Promise<Assignment[]> {
+  return await invoke("accept_staffing_recommendations", {
+    planningPeriodId,
+    assignments,
+  });
 }
 
 // ============================================================================
@@ -381,6 +610,16 @@ export async function getProjectStaffing(
   return await invoke("get_project_staffing", { projectId, planningPeriodId });
 }
 
+export async function getCapacityTimeline(
+  planningPeriodId: number,
+  granularity: TimelineGranularity,
+): Promise<CapacityTimeline> {
+  return await invoke("get_capacity_timeline", {
+    planningPeriodId,
+    granularity,
+  });
+}
+
 // ============================================================================
 // Country Commands
 // ============================================================================
@@ -491,6 +730,16 @@ export async function importHolidaysFromApi(
 // App Commands
 // ============================================================================
 
+export async function getAppSettings(): Promise<AppSettings> {
+  return await invoke("get_app_settings");
+}
+
+export async function updateAppSettings(
+  input: AppSettings,
+): Promise<AppSettings> {
+  return await invoke("update_app_settings", { input });
+}
+
 export async function getAppVersion(): Promise<string> {
   const { getVersion } = await import("@tauri-apps/api/app");
   return await getVersion();
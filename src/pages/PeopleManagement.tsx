@@ -9,6 +9,7 @@ import {
   Paper,
   Text,
   Alert,
+  NumberInput,
 } from "@mantine/core";
 import { notifications } from "@mantine/notifications";
 import { modals } from "@mantine/modals";
@@ -22,6 +23,8 @@ import {
   updatePerson,
   deletePerson,
   checkPersonDependencies,
+  getAppSettings,
+  updateAppSettings,
 } from "../lib/tauri";
 import type { PersonWithCountry, CreatePersonInput } from "../types";
 
@@ -32,11 +35,47 @@ export function PeopleManagementPage() {
   const [formOpened, setFormOpened] = useState(false);
   const [selectedPerson, setSelectedPerson] =
     useState<PersonWithCountry | null>(null);
+  const [defaultMaxUtilization, setDefaultMaxUtilization] = useState<
+    number | string
+  >(100);
 
   useEffect(() => {
     loadPeople();
+    loadAppSettings();
   }, []);
 
+  const loadAppSettings = async () => {
+    try {
+      const settings = await getAppSettings();
+      setDefaultMaxUtilization(settings.default_max_utilization_percentage);
+    } catch (error) {
+      console.error("Failed to load app settings:", error);
+    }
+  };
+
+  const handleSaveDefaultMaxUtilization = async () => {
+    if (typeof defaultMaxUtilization !== "number") return;
+
+    try {
+      const settings = await updateAppSettings({
+        default_max_utilization_percentage: defaultMaxUtilization,
+      });
+      setDefaultMaxUtilization(settings.default_max_utilization_percentage);
+      notifications.show({
+        title: "Success",
+        message: "Default maximum utilization updated",
+        color: "green",
+      });
+    } catch (error) {
+      notifications.show({
+        title: "Error",
+        message: "Failed to update default maximum utilization",
+        color: "red",
+      });
+      console.error("Failed to update app settings:", error);
+    }
+  };
+
   const loadPeople = async () => {
     try {
       setLoading(true);
@@ -190,12 +229,25 @@ export function PeopleManagementPage() {
       <Stack gap="lg">
         <Group justify="space-between" align="center">
           <Title order={1}>People Management</Title>
-          <Button
-            leftSection={<IconPlus size={18} />}
-            onClick={() => setFormOpened(true)}
-          >
-            Add Person
-          </Button>
+          <Group align="flex-end">
+            <NumberInput
+              label="Default Max Utilization (%)"
+              description="For people without their own cap"
+              min={1}
+              max={100}
+              step={5}
+              w={220}
+              value={defaultMaxUtilization}
+              onChange={setDefaultMaxUtilization}
+              onBlur={handleSaveDefaultMaxUtilization}
+            />
+            <Button
+              leftSection={<IconPlus size={18} />}
+              onClick={() => setFormOpened(true)}
+            >
+              Add Person
+            </Button>
+          </Group>
         </Group>
 
         <Paper shadow="xs" p="md" pos="relative">
@@ -8,6 +8,17 @@ export interface PlanningPeriod {
   created_at: string;
 }
 
+export interface PlanningPeriodWithStatus extends PlanningPeriod {
+  is_stale: boolean; // Inputs changed since the last optimization
+  stale_reasons: string[]; // e.g. "3 absences added"
+  auto_optimize: boolean; // Re-optimized automatically when inputs change
+}
+
+export interface CalculationStaleness {
+  is_stale: boolean;
+  reasons: string[]; // e.g. "requirement for Project X changed"
+}
+
 export interface Person {
   id: number;
   name: string;
@@ -15,6 +26,7 @@ export interface Person {
   available_hours_per_week: number;
   country_id: number | null; // NEW: Optional country assignment
   working_days: string; // Comma-separated day codes (e.g., "Mon,Tue,Wed,Thu,Fri")
+  max_utilization_percentage: number | null; // Planned utilization cap, null uses the app-wide default
   created_at: string;
 }
 
@@ -39,22 +51,42 @@ export interface Assignment {
   productivity_factor: number; // 0.0 to 1.0
   start_date: string;
   end_date: string;
+  is_pinned: boolean; // Locked at pinned_allocation_percentage, the optimizer leaves it alone
+  pinned_allocation_percentage: number | null;
+  min_allocation_percentage: number | null; // Bounds every optimization strategy respects
+  max_allocation_percentage: number | null;
   calculated_allocation_percentage: number | null;
   calculated_effective_hours: number | null;
+  calculated_start_date: string | null; // Set when project dependencies delay the start
   last_calculated_at: string | null;
   created_at: string;
 }
 
+export interface ProjectDependency {
+  id: number;
+  project_id: number;
+  depends_on_project_id: number; // Must complete before project_id starts
+  lag_days: number; // Days between the predecessor's completion and the start
+  created_at: string;
+}
+
 export interface Absence {
   id: number;
   person_id: number;
   start_date: string;
   end_date: string;
-  days: number;
+  days: number; // Working days between the dates, excluding public holidays
+  hours: number | null; // Hours absent on each day (partial-day absence)
+  day_fraction: number | null; // Fraction of each day absent, 0.0 to 1.0 (partial-day absence)
   reason: string | null;
   created_at: string;
 }
 
+export interface AbsenceWithHours extends Absence {
+  absence_days: number; // Deducted days, counting partial days as fractions
+  absence_hours: number; // Deducted hours based on the person's hours per day
+}
+
 // ============================================================================
 // Job Types (Global Job Templates with Overhead Tasks)
 // ============================================================================
@@ -115,11 +147,14 @@ export interface PersonCapacity {
   person_id: number;
   person_name: string;
   person_email: string;
+  working_days: number; // Calendar dates in the period matching the person's working days
   total_available_hours: number;
   total_allocated_hours: number;
   total_effective_hours: number;
   utilization_percentage: number;
-  is_over_committed: boolean;
+  is_over_committed: boolean; // Planned above 100% of available hours
+  max_utilization_percentage: number; // Planned utilization cap
+  is_over_utilization_cap: boolean; // Planned above the cap
   assignments: AssignmentSummary[];
   absence_days: number;
   absence_hours: number;
@@ -140,6 +175,17 @@ export interface ProjectStaffing {
   is_viable: boolean;
   shortfall: number;
   assigned_people: PersonAssignmentSummary[];
+  skill_staffing: SkillStaffing[]; // Empty when the project requires no skills
+}
+
+export interface SkillStaffing {
+  skill_id: number;
+  skill_name: string;
+  required_hours: number;
+  effective_hours: number; // From assigned people who have the skill
+  staffing_percentage: number;
+  is_viable: boolean;
+  shortfall: number;
 }
 
 export interface AssignmentSummary {
@@ -163,17 +209,180 @@ export interface PersonAssignmentSummary {
   optional_overhead_hours: number;
 }
 
+export type TimelineGranularity = "week" | "month";
+
+export interface CapacityTimeline {
+  planning_period_id: number;
+  granularity: TimelineGranularity;
+  buckets: TimelineBucket[];
+  people: PersonTimeline[];
+  projects: ProjectTimeline[];
+  staleness: CalculationStaleness; // Whether the allocations shown are out of date
+}
+
+export interface TimelineBucket {
+  label: string; // e.g. "2024-W05" or "2024-01"
+  start_date: string; // Clipped to the planning period
+  end_date: string;
+}
+
+export interface PersonTimeline {
+  person_id: number;
+  person_name: string;
+  points: TimelinePoint[]; // One point per bucket, in bucket order
+}
+
+export interface ProjectTimeline {
+  project_id: number;
+  project_name: string;
+  required_hours: number;
+  points: TimelinePoint[]; // One point per bucket, in bucket order
+}
+
+export interface TimelinePoint {
+  available_hours: number;
+  allocated_hours: number;
+  effective_hours: number;
+}
+
 export interface OptimizationResult {
   success: boolean;
   calculations: AssignmentCalculation[];
+  pinned_calculations: AssignmentCalculation[]; // Assignments locked at their pinned allocation
+  infeasible_projects: ProjectShortfall[];
+  warnings: string[];
+  skipped_assignment_ids: number[]; // Not calculated, e.g. projects without requirement
+  strategy: OptimizationStrategy; // Strategy that produced the result
+  options: OptimizationOptions; // Options the strategy ran with, defaults filled in
+  unsatisfied_constraints: UnsatisfiedConstraint[]; // Allocation bounds that could not be met
+  blocked_projects: BlockedProject[]; // Projects held back by their dependencies
+}
+
+export interface BlockedProject {
+  project_id: number;
+  project_name: string;
+  predecessor_ids: number[]; // Projects it waits for
+  earliest_start_date: string | null; // null when it cannot start in this period
+}
+
+export type AllocationBound = "minimum" | "maximum";
+
+export interface UnsatisfiedConstraint {
+  assignment_id: number;
+  person_id: number;
+  project_id: number;
+  bound: AllocationBound;
+  bound_percentage: number;
+  allocation_percentage: number; // What the assignment got instead
+}
+
+export type OptimizationStrategy =
+  | "proportional"
+  | "priority_first"
+  | "even_spread"
+  | "linear";
+
+export interface OptimizationOptions {
+  priority_weight_step?: number | null; // Linear: objective weight added per priority level
+}
+
+export interface AutoOptimizationSetting {
+  planning_period_id: number;
+  enabled: boolean;
+  strategy: OptimizationStrategy; // Strategy the automatic runs use
+  options: OptimizationOptions;
+}
+
+export interface AutoOptimizationEvent {
+  planning_period_id: number;
+  result: OptimizationResult;
+}
+
+export interface AssignmentDiff {
+  assignment_id: number;
+  person_id: number;
+  project_id: number;
+  old_allocation_percentage: number | null; // null if not calculated
+  new_allocation_percentage: number | null; // null if not calculated or cleared
+  old_effective_hours: number | null;
+  new_effective_hours: number | null;
+}
+
+export interface ProjectStaffingDelta {
+  project_id: number;
+  project_name: string;
+  old_effective_hours: number;
+  new_effective_hours: number;
+  delta_hours: number;
+}
+
+export interface OptimizationPreview {
+  planning_period_id: number;
+  input_fingerprint: string; // Identifies the data the preview was calculated from
+  result: OptimizationResult;
+  assignment_diffs: AssignmentDiff[];
+  project_deltas: ProjectStaffingDelta[]; // Sorted by project name
+}
+
+export interface OptimizationInputsSummary {
+  input_fingerprint: string;
+  people: number;
+  assignments: number;
+  pinned_assignments: number;
+  required_projects: number;
+  required_hours: number;
+}
+
+export interface OptimizationRunCalculation {
+  assignment_id: number;
+  person_id: number;
+  project_id: number;
+  allocation_percentage: number;
+  effective_hours: number;
+  is_pinned: boolean;
+  calculated_start_date: string | null;
+}
+
+export interface OptimizationRun {
+  id: number;
+  planning_period_id: number;
+  strategy: OptimizationStrategy;
+  options: OptimizationOptions;
+  inputs: OptimizationInputsSummary;
+  calculations: OptimizationRunCalculation[];
   infeasible_projects: ProjectShortfall[];
   warnings: string[];
+  created_at: string;
+}
+
+export interface OptimizationRunSummary {
+  id: number;
+  planning_period_id: number;
+  strategy: OptimizationStrategy;
+  inputs: OptimizationInputsSummary;
+  infeasible_projects: number;
+  warnings: number;
+  created_at: string;
+}
+
+export interface OptimizationRunComparison {
+  base_run_id: number;
+  other_run_id: number;
+  assignment_diffs: AssignmentDiff[]; // Old values from the base run
+  project_deltas: ProjectStaffingDelta[]; // Sorted by project name
+}
+
+export interface RestoredOptimizationRun {
+  run_id: number;
+  restored_assignments: number;
+  missing_assignment_ids: number[]; // Assignments deleted since the run
 }
 
 export interface AssignmentCalculation {
   assignment_id: number;
   calculated_allocation_percentage: number;
   calculated_effective_hours: number;
+  calculated_start_date: string | null; // Set when project dependencies delay the start
 }
 
 export interface ProjectShortfall {
@@ -183,6 +392,128 @@ export interface ProjectShortfall {
   available_effective_hours: number;
   shortfall: number;
   shortfall_percentage: number;
+  explanation: ShortfallPersonExplanation[]; // One entry per assigned person
+}
+
+export interface ShortfallPersonExplanation {
+  assignment_id: number;
+  person_id: number;
+  person_name: string;
+  available_hours: number; // Inside the assignment window, after the deductions below
+  absence_hours: number;
+  holiday_hours: number;
+  overhead_hours: number;
+  optional_overhead_hours: number;
+  allocation_percentage: number; // Share the short project got
+  effective_hours: number;
+  competing_projects: CompetingProjectHours[]; // Higher- or equal-priority projects
+}
+
+export interface CompetingProjectHours {
+  project_id: number;
+  project_name: string;
+  priority: number;
+  allocated_hours: number;
+}
+
+export interface MultiPeriodOptimizationResult {
+  strategy: OptimizationStrategy;
+  periods: PeriodOptimizationResult[]; // In date order
+  projects: ProjectProgress[]; // Sorted by project name
+}
+
+export interface PeriodOptimizationResult {
+  planning_period_id: number;
+  planning_period_name: string | null;
+  carried_in: CarriedDemand[]; // Added to this period's requirements
+  result: OptimizationResult;
+}
+
+export interface CarriedDemand {
+  project_id: number;
+  project_name: string;
+  hours: number;
+}
+
+export interface ProjectProgress {
+  project_id: number;
+  project_name: string;
+  periods: ProjectPeriodProgress[]; // Periods with a requirement or carried demand
+  unmet_hours: number; // Still missing after the last period
+}
+
+export interface ProjectPeriodProgress {
+  planning_period_id: number;
+  required_hours: number; // The period's own requirement
+  carried_in_hours: number; // Unmet in earlier periods
+  effective_hours: number;
+  carried_out_hours: number; // Unmet, moved on to the next period
+  cumulative_required_hours: number;
+  cumulative_effective_hours: number;
+}
+
+export interface StaffingRecommendations {
+  planning_period_id: number;
+  strategy: OptimizationStrategy; // Strategy the proposals were evaluated with
+  productivity_factor: number; // Assumed for every proposed assignment
+  spare_capacity: PersonSpareCapacity[]; // Most unallocated hours first
+  proposals: StaffingProposal[]; // One per infeasible project
+}
+
+export interface PersonSpareCapacity {
+  person_id: number;
+  person_name: string;
+  available_hours: number; // Over the whole period, limited by the utilization cap
+  allocated_hours: number;
+  unallocated_hours: number;
+}
+
+export interface StaffingProposal {
+  project_id: number;
+  project_name: string;
+  shortfall: number; // Before the proposed assignments
+  remaining_shortfall: number; // After the proposed assignments
+  assignments: ProposedAssignment[];
+  project_deltas: ProjectStaffingDelta[]; // Other projects whose effective hours change
+}
+
+export interface ProposedAssignment {
+  person_id: number;
+  person_name: string;
+  project_id: number;
+  planning_period_id: number;
+  productivity_factor: number;
+  start_date: string;
+  end_date: string;
+  allocation_percentage: number; // What the optimization gave the new assignment
+  effective_hours: number;
+}
+
+export interface CapacitySimulation {
+  planning_period_id: number;
+  runs: number;
+  people: PersonSimulation[];
+  projects: ProjectSimulation[];
+}
+
+export interface PersonSimulation {
+  person_id: number;
+  person_name: string;
+  available_hours: SimulationDistribution;
+}
+
+export interface ProjectSimulation {
+  project_id: number;
+  project_name: string;
+  required_hours: number;
+  staffing_percentage: SimulationDistribution;
+  viable_probability: number; // Share of runs in which the project is fully staffed (0.0 to 1.0)
+}
+
+export interface SimulationDistribution {
+  p10: number;
+  p50: number;
+  p90: number;
 }
 
 export interface ProjectFeasibility {
@@ -199,9 +530,11 @@ export interface CapacityOverview {
   total_people: number;
   total_projects: number;
   over_committed_people: number;
+  over_utilization_cap_people: number; // Planned above their cap, including over-committed
   under_staffed_projects: number;
   people_capacity: PersonCapacity[];
   project_staffing: ProjectStaffing[];
+  staleness: CalculationStaleness; // Whether the allocations shown are out of date
 }
 
 // Form input types
@@ -218,6 +551,7 @@ export interface CreatePersonInput {
   available_hours_per_week: number;
   country_id?: number | null; // NEW: Optional country assignment
   working_days: string; // Comma-separated day codes (e.g., "Mon,Tue,Wed,Thu,Fri")
+  max_utilization_percentage?: number | null; // Above 0 up to 100, null for the app-wide default
 }
 
 export interface CreateProjectInput {
@@ -233,13 +567,19 @@ export interface CreateAssignmentInput {
   productivity_factor: number;
   start_date?: string; // Defaults to period start
   end_date?: string; // Defaults to period end
+  is_pinned?: boolean; // Defaults to false; omit on update to keep the current pin
+  pinned_allocation_percentage?: number; // Required when pinned, 0 to 100
+  min_allocation_percentage?: number | null; // 0 to 100, at most the maximum; omit on update to keep, null clears
+  max_allocation_percentage?: number | null; // 0 to 100; omit on update to keep, null clears
 }
 
 export interface CreateAbsenceInput {
   person_id: number;
   start_date: string;
   end_date: string;
-  days: number;
+  days?: number; // Derived from the dates by the backend; a mismatching value is corrected
+  hours?: number | null; // Hours absent on each day; omit for full days
+  day_fraction?: number | null; // Fraction of each day absent; omit for full days
   reason?: string;
 }
 
@@ -273,6 +613,12 @@ export interface JobDependencies {
   assignment_count: number;
 }
 
+export interface CreateProjectDependencyInput {
+  project_id: number;
+  depends_on_project_id: number;
+  lag_days?: number; // Defaults to 0
+}
+
 export interface CreateProjectRequirementInput {
   project_id: number;
   planning_period_id: number;
@@ -328,6 +674,8 @@ export interface Holiday {
   name: string | null; // Optional holiday name
   start_date: string; // ISO 8601
   end_date: string;
+  hours: number | null; // Hours off on each day (partial-day holiday)
+  day_fraction: number | null; // Fraction of each day off (partial-day holiday)
   created_at: string;
 }
 
@@ -341,6 +689,8 @@ export interface CreateHolidayInput {
   name?: string;
   start_date: string;
   end_date: string;
+  hours?: number | null; // Hours off on each day; omit for full days
+  day_fraction?: number | null; // Fraction of each day off; omit for full days
 }
 
 // UI-specific types
@@ -390,3 +740,75 @@ export interface ImportHolidaysResult {
   imported_count: number;
   skipped_count: number;
 }
+
+// ============================================================================
+// App Settings Types
+// ============================================================================
+
+export interface AppSettings {
+  default_max_utilization_percentage: number; // Cap for people without one of their own
+}
+
+export interface Skill {
+  id: number;
+  name: string;
+  description: string | null;
+  created_at: string;
+}
+
+export interface CreateSkillInput {
+  name: string;
+  description?: string | null;
+}
+
+export interface ProficiencyLevel {
+  value: string; // Stored with person skills, e.g. "expert"
+  factor: number;
+  label: string;
+  description: string;
+}
+
+export interface PersonSkill {
+  id: number;
+  person_id: number;
+  skill_id: number;
+  proficiency: string; // Proficiency level, e.g. "expert"
+  created_at: string;
+}
+
+export interface SetPersonSkillInput {
+  person_id: number;
+  skill_id: number;
+  proficiency: string;
+}
+
+export interface ProjectSkillRequirement {
+  id: number;
+  project_id: number;
+  planning_period_id: number;
+  skill_id: number;
+  required_hours: number;
+  created_at: string;
+}
+
+export interface CreateProjectSkillRequirementInput {
+  project_id: number;
+  planning_period_id: number;
+  skill_id: number;
+  required_hours: number;
+}
+
+export interface SkillMatch {
+  skill_id: number;
+  skill_name: string;
+  required_hours: number;
+  proficiency: string | null; // null when the person doesn't have the skill
+  productivity_factor: number;
+}
+
+export interface ProductivitySuggestion {
+  person_id: number;
+  project_id: number;
+  productivity_factor: number; // Average over the skills, weighted by their required hours
+  skills: SkillMatch[];
+}